@@ -1,14 +1,7 @@
 use leptos::*;
-use serde::Serialize;
 use web_sys::SubmitEvent;
 
-#[derive(Serialize, Clone)]
-struct MetadataInput {
-    name: String,
-    symbol: String,
-    description: String,
-    image: String,
-}
+use crate::metadata::OffchainMetadata;
 
 #[component]
 pub fn MetadataForm() -> impl IntoView {
@@ -17,15 +10,22 @@ pub fn MetadataForm() -> impl IntoView {
     let (description, set_description) = create_signal(String::new());
     let (image, set_image) = create_signal(String::new());
     let (metadata_uri, set_metadata_uri) = create_signal(String::new());
+    let (error, set_error) = create_signal(Option::<String>::None);
 
     let on_submit = move |ev: SubmitEvent| {
         ev.prevent_default();
-        let metadata = MetadataInput {
+        let metadata = OffchainMetadata {
             name: name.get(),
             symbol: symbol.get(),
             description: description.get(),
             image: image.get(),
+            ..OffchainMetadata::default()
         };
+        if let Err(e) = metadata.validate() {
+            set_error.set(Some(e.to_string()));
+            return;
+        }
+        set_error.set(None);
 
         spawn_local(async move {
             let client = reqwest::Client::new();
@@ -68,6 +68,7 @@ pub fn MetadataForm() -> impl IntoView {
                 />
                 <button type="submit">"Upload Metadata"</button>
             </form>
+            {move || error.get().map(|e| view! { <p class="error">{e}</p> })}
             {move || metadata_uri.get().is_empty().then(|| view! {
                 <CreateTokenButton metadata_uri=metadata_uri.get()/>
             })}
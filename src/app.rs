@@ -3,8 +3,17 @@ use leptos_meta::*;
 use leptos_router::*;
 use leptos::ev::SubmitEvent;
 use crate::wallet::{WalletProvider, WalletContext, WalletType};
-use crate::token::{create_token, CreateTokenParams, NetworkType};
-use crate::utils::pinata::upload_metadata_to_pinata;
+use crate::jobs::{
+    forget_job, poll_job, remember_job, remembered_job, submit_token_job, token_job_status, watch_job, JobStage, JobStatus,
+};
+use crate::metadata::{Extensions, OffchainMetadata, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH};
+use crate::registry::{list_tokens, TokenRecord};
+use crate::token::{CreateTokenParams, NetworkType};
+use crate::token::details::{get_token_details, MetadataSource, TokenDetails};
+use crate::token::holders::{get_holders, HolderSnapshot};
+use crate::token::risk::{get_risk_report, RiskReport};
+use crate::upload::UploadedImage;
+use crate::token::erc20::{Erc20DeployResult, Erc20Params};
 
 #[component]
 pub fn App() -> impl IntoView {
@@ -30,6 +39,8 @@ pub fn App() -> impl IntoView {
                         <Route path="/" view=HomePage/>
                         <Route path="/create" view=CreateTokenPage/>
                         <Route path="/send" view=SendTokenPage/>
+                        <Route path="/tokens" view=MyTokensPage/>
+                        <Route path="/token/:mint" view=TokenPage ssr=SsrMode::Async/>
                     </Routes>
                 </main>
             </Router>
@@ -39,6 +50,16 @@ pub fn App() -> impl IntoView {
 
 #[component]
 fn HomePage() -> impl IntoView {
+    let (lookup, set_lookup) = create_signal(String::new());
+    let navigate = use_navigate();
+    let open_token = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        let mint = lookup.get_untracked();
+        if !mint.trim().is_empty() {
+            navigate(&format!("/token/{}", mint.trim()), Default::default());
+        }
+    };
+
     view! {
         <div class="container">
             <h2 class="token-management">"Token Management"</h2>
@@ -54,6 +75,25 @@ fn HomePage() -> impl IntoView {
                     <p>"Send tokens to any address"</p>
                     <A href="/send" class="button">"Send"</A>
                 </div>
+
+                <div class="feature-card">
+                    <h3>"My Tokens"</h3>
+                    <p>"Tokens created with your wallet"</p>
+                    <A href="/tokens" class="button">"View"</A>
+                </div>
+
+                <div class="feature-card">
+                    <h3>"Look Up Token"</h3>
+                    <p>"Supply, authorities, metadata and holders of any mint"</p>
+                    <form on:submit=open_token>
+                        <input
+                            type="text"
+                            placeholder="Mint address"
+                            on:input=move |ev| set_lookup(event_target_value(&ev))
+                        />
+                        <button type="submit" class="button">"Open"</button>
+                    </form>
+                </div>
             </div>
         </div>
     }
@@ -62,9 +102,15 @@ fn HomePage() -> impl IntoView {
 #[component]
 fn CreateTokenPage() -> impl IntoView {
     let wallet_ctx = use_context::<WalletContext>().expect("WalletContext not found");
+    let wallet_state = wallet_ctx.state;
+    let is_metamask = move || wallet_state.get().wallet_type == Some(WalletType::MetaMask);
     let (token_name, set_token_name) = create_signal(String::new());
     let (token_symbol, set_token_symbol) = create_signal(String::new());
     let (token_uri, set_token_uri) = create_signal(String::new());
+    let (logo, set_logo) = create_signal(Option::<UploadedImage>::None);
+    let (uploading_logo, set_uploading_logo) = create_signal(false);
+    let (website, set_website) = create_signal(String::new());
+    let (twitter, set_twitter) = create_signal(String::new());
     let (decimals, set_decimals) = create_signal(9u8);
     let (initial_supply, set_initial_supply) = create_signal(1_000_000_000u64);
     let (is_mutable, set_is_mutable) = create_signal(true);
@@ -73,68 +119,146 @@ fn CreateTokenPage() -> impl IntoView {
     let (transfer_fee, set_transfer_fee) = create_signal(Option::<u16>::None);
     let (max_transfer_amount, set_max_transfer_amount) = create_signal(Option::<u64>::None);
     let (network, set_network) = create_signal(NetworkType::Devnet);
-    let (loading, _set_loading) = create_signal(false);
-    let (error, _set_error) = create_signal(Option::<String>::None);
-    let (success, _set_success) = create_signal(Option::<String>::None);
-    let (status, set_status) = create_signal(String::new());
-
-    let handle_submit = move |ev: SubmitEvent| {
-        ev.prevent_default();
-        
-        let token_name = token_name.get_untracked();
-        let token_name_clone = token_name.clone();
-        let token_symbol = token_symbol.get_untracked();
-        let token_uri = token_uri.get_untracked();
-        let network = network.get();
+    let (error, set_error) = create_signal(Option::<String>::None);
+    let (job, set_job) = create_signal(Option::<JobStatus>::None);
+    let loading = move || job.with(|job| job.as_ref().is_some_and(|job| !job.stage.is_finished()));
 
-        set_status.set("Creating token metadata...".to_string());
+    let apply_status = move |status: JobStatus| {
+        if status.stage.is_finished() {
+            forget_job();
+        }
+        set_job.set(Some(status));
+    };
 
+    // Falls back to polling when the event stream can't be used.
+    let fall_back_to_polling = move |id: String| {
         spawn_local(async move {
-            let metadata = serde_json::json!({
-                "name": token_name,
-                "symbol": token_symbol,
-                "description": format!("{} token", token_name),
-                "image": token_uri,
-                "attributes": []
+            if let Err(e) = poll_job(id, apply_status).await {
+                set_error.set(Some(format!("Lost track of token creation: {}", e)));
+            }
+        });
+    };
+
+    let follow_job = move |status: JobStatus| {
+        let id = status.id.clone();
+        let finished = status.stage.is_finished();
+        apply_status(status);
+        if !finished {
+            let poll_id = id.clone();
+            if let Err(e) = watch_job(&id, apply_status, move || fall_back_to_polling(poll_id.clone())) {
+                log::warn!("{}", e);
+                fall_back_to_polling(id);
+            }
+        }
+    };
+
+    // Resume a job started before the page was reloaded.
+    create_effect(move |_| {
+        if let Some(id) = remembered_job() {
+            spawn_local(async move {
+                match token_job_status(id).await {
+                    Ok(status) => follow_job(status),
+                    Err(_) => forget_job(),
+                }
             });
+        }
+    });
 
-            match upload_metadata_to_pinata(
-                &std::env::var("PINATA_API_KEY").unwrap_or_default(),
-                &std::env::var("PINATA_SECRET_KEY").unwrap_or_default(),
-                &metadata
-            ).await {
-                Ok(metadata_uri) => {
-                    set_status.set("Creating token...".to_string());
-
-                    let params = CreateTokenParams {
-                        name: token_name,
-                        symbol: token_symbol,
-                        description: format!("{} token", token_name_clone),
-                        metadata_uri,
-                        decimals: decimals.get_untracked(),
-                        initial_supply: initial_supply.get_untracked(),
-                        is_mutable: is_mutable.get_untracked(),
-                        freeze_authority: freeze_authority.get_untracked(),
-                        rate_limit: rate_limit.get_untracked(),
-                        transfer_fee: transfer_fee.get_untracked(),
-                        max_transfer_amount: max_transfer_amount.get_untracked(),
-                        network,
-                        #[cfg(not(target_arch = "wasm32"))]
-                        payer: None,
-                    };
-
-                    match create_token(params).await {
-                        Ok(result) => {
-                            set_status.set(format!("View on Explorer: {}", result.explorer_url));
-                        }
-                        Err(e) => {
-                            set_status.set(format!("Token creation failed: {}", e));
-                        }
+    let handle_logo = move |ev: leptos::ev::Event| {
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+
+            let Some(file) = ev
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok())
+                .and_then(|input| input.files())
+                .and_then(|files| files.get(0))
+            else {
+                return;
+            };
+            if wallet_state.get_untracked().session.is_none() {
+                set_error.set(Some("Sign in with your wallet to upload a logo".to_string()));
+                return;
+            }
+
+            set_error.set(None);
+            set_uploading_logo.set(true);
+            spawn_local(async move {
+                match crate::upload::upload_file(file).await {
+                    Ok(uploaded) => {
+                        set_token_uri.set(uploaded.image.clone());
+                        set_logo.set(Some(uploaded));
                     }
+                    Err(e) => set_error.set(Some(e)),
                 }
-                Err(e) => {
-                    set_status.set(format!("Metadata upload failed: {}", e));
+                set_uploading_logo.set(false);
+            });
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = (ev, set_logo, set_uploading_logo);
+    };
+
+    let handle_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+        set_error.set(None);
+
+        if wallet_state.get_untracked().session.is_none() {
+            set_error.set(Some("Sign in with your wallet to create tokens".to_string()));
+            return;
+        }
+
+        let token_name = token_name.get_untracked();
+        let params = CreateTokenParams {
+            description: format!("{} token", token_name),
+            name: token_name,
+            symbol: token_symbol.get_untracked(),
+            metadata_uri: String::new(),
+            decimals: decimals.get_untracked(),
+            initial_supply: initial_supply.get_untracked(),
+            is_mutable: is_mutable.get_untracked(),
+            freeze_authority: freeze_authority.get_untracked(),
+            rate_limit: rate_limit.get_untracked(),
+            transfer_fee: transfer_fee.get_untracked(),
+            max_transfer_amount: max_transfer_amount.get_untracked(),
+            network: network.get_untracked(),
+            #[cfg(not(target_arch = "wasm32"))]
+            payer: None,
+        };
+        let image = token_uri.get_untracked();
+        // Only describe the uploaded files if the URI still points at them.
+        let files = logo
+            .get_untracked()
+            .filter(|logo| logo.image == image)
+            .map(|logo| logo.files)
+            .unwrap_or_default();
+        let optional = |value: String| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        let metadata = OffchainMetadata {
+            name: params.name.clone(),
+            symbol: params.symbol.clone(),
+            description: params.description.clone(),
+            image,
+            extensions: Extensions {
+                website: optional(website.get_untracked()),
+                twitter: optional(twitter.get_untracked()),
+                ..Extensions::default()
+            },
+            ..OffchainMetadata::default()
+        }
+        .with_files(files);
+
+        if let Err(e) = params.validate().and_then(|_| metadata.validate()) {
+            set_error.set(Some(e.to_string()));
+            return;
+        }
+
+        spawn_local(async move {
+            match submit_token_job(params, metadata).await {
+                Ok(status) => {
+                    remember_job(&status.id);
+                    follow_job(status);
                 }
+                Err(e) => set_error.set(Some(format!("Failed to start token creation: {}", e))),
             }
         });
     };
@@ -142,205 +266,856 @@ fn CreateTokenPage() -> impl IntoView {
     view! {
         <div class="container">
             <h2 class="token-management">"Create Token"</h2>
-            <div class="token-forms">
-                <form class="token-form" on:submit=handle_submit>
-                    <div class="form-group">
-                        <label for="token_name">"Token Name"</label>
-                        <input
-                            type="text"
-                            id="token_name"
-                            required
-                            placeholder="Enter token name"
-                            on:input=move |ev| {
-                                set_token_name.set(event_target_value(&ev));
-                            }
-                        />
-                    </div>
-
-                    <div class="form-group">
-                        <label for="token_symbol">"Token Symbol"</label>
-                        <input
-                            type="text"
-                            id="token_symbol"
-                            required
-                            placeholder="Enter token symbol"
-                            on:input=move |ev| {
-                                set_token_symbol.set(event_target_value(&ev));
-                            }
-                        />
-                    </div>
-
-                    <div class="form-group">
-                        <label for="token_uri">"Token URI"</label>
-                        <input
-                            type="text"
-                            id="token_uri"
-                            placeholder="Enter token URI"
-                            on:input=move |ev| {
-                                set_token_uri.set(event_target_value(&ev));
-                            }
-                        />
-                    </div>
-
-                    <div class="form-group">
-                        <label for="decimals">"Decimals (0-9)"</label>
-                        <input
-                            type="number"
-                            id="decimals"
-                            min="0"
-                            max="9"
-                            required
-                            value="9"
-                            on:input=move |ev| {
-                                if let Ok(value) = event_target_value(&ev).parse() {
-                                    set_decimals.set(value);
+            <Show when=move || !is_metamask() fallback=|| view! { <Erc20TokenForm/> }>
+                <div class="token-forms">
+                    <form class="token-form" on:submit=handle_submit>
+                        <div class="form-group">
+                            <label for="token_name">"Token Name"</label>
+                            <input
+                                type="text"
+                                id="token_name"
+                                required
+                                maxlength=MAX_NAME_LENGTH
+                                placeholder="Enter token name"
+                                on:input=move |ev| {
+                                    set_token_name.set(event_target_value(&ev));
                                 }
-                            }
-                        />
-                    </div>
+                            />
+                        </div>
 
-                    <div class="form-group">
-                        <label for="initial_supply">"Initial Supply"</label>
-                        <input
-                            type="number"
-                            id="initial_supply"
-                            min="0"
-                            required
-                            value="1000000000"
-                            on:input=move |ev| {
-                                if let Ok(value) = event_target_value(&ev).parse() {
-                                    set_initial_supply.set(value);
+                        <div class="form-group">
+                            <label for="token_symbol">"Token Symbol"</label>
+                            <input
+                                type="text"
+                                id="token_symbol"
+                                required
+                                maxlength=MAX_SYMBOL_LENGTH
+                                placeholder="Enter token symbol"
+                                on:input=move |ev| {
+                                    set_token_symbol.set(event_target_value(&ev));
                                 }
-                            }
-                        />
-                    </div>
+                            />
+                        </div>
 
-                    <div class="form-group checkbox-group">
-                        <label>
+                        <div class="form-group">
+                            <label for="token_uri">"Token URI"</label>
                             <input
-                                type="checkbox"
-                                checked=true
-                                on:change=move |ev| {
-                                    set_is_mutable.set(event_target_checked(&ev));
+                                type="text"
+                                id="token_uri"
+                                placeholder="Enter image URL or upload a logo"
+                                prop:value=token_uri
+                                on:input=move |ev| {
+                                    set_token_uri.set(event_target_value(&ev));
                                 }
                             />
-                            "Token metadata is mutable"
-                        </label>
-                    </div>
+                        </div>
 
-                    <div class="form-group checkbox-group">
-                        <label>
+                        <div class="form-group">
+                            <label for="token_logo">"Token Logo"</label>
                             <input
-                                type="checkbox"
-                                checked=true
-                                on:change=move |ev| {
-                                    set_freeze_authority.set(event_target_checked(&ev));
+                                type="file"
+                                id="token_logo"
+                                accept="image/png,image/jpeg,image/gif,image/webp"
+                                disabled=uploading_logo
+                                on:change=handle_logo
+                            />
+                            {move || uploading_logo.get().then(|| view! {
+                                <p class="status-message">"Processing logo..."</p>
+                            })}
+                            {move || logo.get().map(|logo| view! {
+                                <img class="logo-preview" src=logo.preview_url alt="Token logo preview"/>
+                            })}
+                        </div>
+
+                        <div class="form-group">
+                            <label for="token_website">"Website (optional)"</label>
+                            <input
+                                type="url"
+                                id="token_website"
+                                placeholder="https://example.com"
+                                on:input=move |ev| {
+                                    set_website.set(event_target_value(&ev));
                                 }
                             />
-                            "Enable freeze authority"
-                        </label>
-                    </div>
+                        </div>
 
-                    <div class="form-group">
-                        <label>"Smart Contract Settings"</label>
-                        
-                        <div class="form-row">
-                            <label for="rate_limit">"Rate Limit (tokens per day)"</label>
+                        <div class="form-group">
+                            <label for="token_twitter">"Twitter (optional)"</label>
                             <input
-                                type="number"
-                                id="rate_limit"
-                                min="0"
-                                placeholder="Optional: Enter max tokens per day"
+                                type="text"
+                                id="token_twitter"
+                                placeholder="https://x.com/yourtoken"
                                 on:input=move |ev| {
-                                    let value = event_target_value(&ev).parse::<u64>().ok();
-                                    set_rate_limit.set(value);
+                                    set_twitter.set(event_target_value(&ev));
                                 }
                             />
                         </div>
 
-                        <div class="form-row">
-                            <label for="transfer_fee">"Transfer Fee (basis points)"</label>
+                        <div class="form-group">
+                            <label for="decimals">"Decimals (0-9)"</label>
                             <input
                                 type="number"
-                                id="transfer_fee"
+                                id="decimals"
                                 min="0"
-                                max="10000"
-                                placeholder="Optional: Enter fee in basis points (0-10000)"
+                                max="9"
+                                required
+                                value="9"
                                 on:input=move |ev| {
-                                    let value = event_target_value(&ev).parse::<u16>().ok();
-                                    set_transfer_fee.set(value);
+                                    if let Ok(value) = event_target_value(&ev).parse() {
+                                        set_decimals.set(value);
+                                    }
                                 }
                             />
                         </div>
 
-                        <div class="form-row">
-                            <label for="max_transfer">"Max Transfer Amount"</label>
+                        <div class="form-group">
+                            <label for="initial_supply">"Initial Supply"</label>
                             <input
                                 type="number"
-                                id="max_transfer"
+                                id="initial_supply"
                                 min="0"
-                                placeholder="Optional: Enter max tokens per transfer"
+                                required
+                                value="1000000000"
                                 on:input=move |ev| {
-                                    let value = event_target_value(&ev).parse::<u64>().ok();
-                                    set_max_transfer_amount.set(value);
+                                    if let Ok(value) = event_target_value(&ev).parse() {
+                                        set_initial_supply.set(value);
+                                    }
                                 }
                             />
                         </div>
-                    </div>
 
-                    <div class="form-group">
-                        <label>"Network"</label>
-                        <select 
-                            class="select-input"
-                            on:change=move |ev| {
-                                let value = event_target_value(&ev);
-                                set_network(if value == "mainnet" {
-                                    NetworkType::Mainnet
-                                } else {
-                                    NetworkType::Devnet
-                                });
-                            }
-                        >
-                            <option value="devnet" selected=move || network.get() == NetworkType::Devnet>
-                                "Devnet"
-                            </option>
-                            <option value="mainnet" selected=move || network.get() == NetworkType::Mainnet>
-                                "Mainnet"
-                            </option>
-                        </select>
-                    </div>
+                        <div class="form-group checkbox-group">
+                            <label>
+                                <input
+                                    type="checkbox"
+                                    checked=true
+                                    on:change=move |ev| {
+                                        set_is_mutable.set(event_target_checked(&ev));
+                                    }
+                                />
+                                "Token metadata is mutable"
+                            </label>
+                        </div>
 
-                    <div id="creation-status" class="status-message">
-                        {move || status.get()}
-                    </div>
+                        <div class="form-group checkbox-group">
+                            <label>
+                                <input
+                                    type="checkbox"
+                                    checked=true
+                                    on:change=move |ev| {
+                                        set_freeze_authority.set(event_target_checked(&ev));
+                                    }
+                                />
+                                "Enable freeze authority"
+                            </label>
+                        </div>
+
+                        <div class="form-group">
+                            <label>"Smart Contract Settings"</label>
+                        
+                            <div class="form-row">
+                                <label for="rate_limit">"Rate Limit (tokens per day)"</label>
+                                <input
+                                    type="number"
+                                    id="rate_limit"
+                                    min="0"
+                                    placeholder="Optional: Enter max tokens per day"
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev).parse::<u64>().ok();
+                                        set_rate_limit.set(value);
+                                    }
+                                />
+                            </div>
 
-                    {move || error.get().map(|err| view! {
-                        <div class="error-message">
-                            {err}
+                            <div class="form-row">
+                                <label for="transfer_fee">"Transfer Fee (basis points)"</label>
+                                <input
+                                    type="number"
+                                    id="transfer_fee"
+                                    min="0"
+                                    max="10000"
+                                    placeholder="Optional: Enter fee in basis points (0-10000)"
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev).parse::<u16>().ok();
+                                        set_transfer_fee.set(value);
+                                    }
+                                />
+                            </div>
+
+                            <div class="form-row">
+                                <label for="max_transfer">"Max Transfer Amount"</label>
+                                <input
+                                    type="number"
+                                    id="max_transfer"
+                                    min="0"
+                                    placeholder="Optional: Enter max tokens per transfer"
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev).parse::<u64>().ok();
+                                        set_max_transfer_amount.set(value);
+                                    }
+                                />
+                            </div>
                         </div>
-                    })}
 
-                    {move || success.get().map(|msg| view! {
-                        <div class="success-message">
-                            {msg}
+                        <div class="form-group">
+                            <label>"Network"</label>
+                            <select 
+                                class="select-input"
+                                on:change=move |ev| {
+                                    let value = event_target_value(&ev);
+                                    set_network(if value == "mainnet" {
+                                        NetworkType::Mainnet
+                                    } else {
+                                        NetworkType::Devnet
+                                    });
+                                }
+                            >
+                                <option value="devnet" selected=move || network.get() == NetworkType::Devnet>
+                                    "Devnet"
+                                </option>
+                                <option value="mainnet" selected=move || network.get() == NetworkType::Mainnet>
+                                    "Mainnet"
+                                </option>
+                            </select>
                         </div>
+
+                        {move || job.get().map(|job| view! { <JobProgress job=job/> })}
+
+                        {move || error.get().map(|err| view! {
+                            <div class="error-message">
+                                {err}
+                            </div>
+                        })}
+
+                        <button 
+                            type="submit" 
+                            class="button"
+                            disabled=move || loading() || uploading_logo.get()
+                        >
+                            {move || if loading() {
+                                "Creating Token..."
+                            } else if wallet_state.get().connected {
+                                "Create Token"
+                            } else {
+                                "Connect Wallet First"
+                            }}
+                        </button>
+                    </form>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn MyTokensPage() -> impl IntoView {
+    let wallet_ctx = use_context::<WalletContext>().expect("WalletContext not found");
+    let wallet_state = wallet_ctx.state;
+    let tokens = create_resource(
+        move || wallet_state.get().address,
+        |address| async move {
+            match address {
+                Some(address) => list_tokens(address).await.map_err(|e| e.to_string()),
+                None => Ok(Vec::new()),
+            }
+        },
+    );
+
+    view! {
+        <div class="container">
+            <h2 class="token-management">"My Tokens"</h2>
+            <Show
+                when=move || wallet_state.get().connected
+                fallback=|| view! { <p class="status-message">"Connect your wallet to see the tokens you created"</p> }
+            >
+                <Suspense fallback=|| view! { <p class="status-message">"Loading tokens..."</p> }>
+                    {move || tokens.get().map(|result| match result {
+                        Ok(records) if records.is_empty() => view! {
+                            <p class="status-message">"No tokens created with this wallet yet"</p>
+                        }.into_view(),
+                        Ok(records) => view! { <TokenTable records=records/> }.into_view(),
+                        Err(err) => view! { <div class="error-message">{err}</div> }.into_view(),
                     })}
+                </Suspense>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn TokenTable(records: Vec<TokenRecord>) -> impl IntoView {
+    let rows = records
+        .into_iter()
+        .map(|record| {
+            let explorer_url = record.explorer_url();
+            let network = match record.network {
+                NetworkType::Devnet => "Devnet",
+                NetworkType::Mainnet => "Mainnet",
+            };
+            let created = chrono::DateTime::from_timestamp(record.created_at, 0)
+                .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_default();
+            let token_url = format!("/token/{}?network={}", record.mint, network_param(&record.network));
+            view! {
+                <tr>
+                    <td><A href=token_url>{record.params.name}</A></td>
+                    <td>{record.params.symbol}</td>
+                    <td>
+                        <a href=explorer_url target="_blank" rel="noopener noreferrer">{record.mint}</a>
+                    </td>
+                    <td>{network}</td>
+                    <td>{created}</td>
+                </tr>
+            }
+        })
+        .collect_view();
 
-                    <button 
-                        type="submit" 
-                        class="button"
-                        disabled=move || loading.get()
-                    >
-                        {move || if loading.get() {
-                            "Creating Token..."
-                        } else if wallet_ctx.state.get().connected {
-                            "Create Token"
-                        } else {
-                            "Connect Wallet First"
-                        }}
-                    </button>
-                </form>
+    view! {
+        <table class="token-table">
+            <thead>
+                <tr>
+                    <th>"Name"</th>
+                    <th>"Symbol"</th>
+                    <th>"Mint"</th>
+                    <th>"Network"</th>
+                    <th>"Created"</th>
+                </tr>
+            </thead>
+            <tbody>{rows}</tbody>
+        </table>
+    }
+}
+
+/// `?network=` value of token pages.
+fn network_param(network: &NetworkType) -> &'static str {
+    match network {
+        NetworkType::Devnet => "devnet",
+        NetworkType::Mainnet => "mainnet",
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TokenTab {
+    Overview,
+    Holders,
+    Risk,
+}
+
+#[component]
+fn TokenPage() -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let mint = move || params.with(|params| params.get("mint").cloned().unwrap_or_default());
+    let network = move || match query.with(|query| query.get("network").cloned()).as_deref() {
+        Some("mainnet") => NetworkType::Mainnet,
+        _ => NetworkType::Devnet,
+    };
+    let (tab, set_tab) = create_signal(TokenTab::Overview);
+    let tab_class = move |this: TokenTab| if tab.get() == this { "tab active" } else { "tab" };
+    let details = create_resource(
+        move || (mint(), network()),
+        |(mint, network)| async move { get_token_details(mint, network).await.map_err(|e| e.to_string()) },
+    );
+    let title = move || {
+        details
+            .get()
+            .and_then(|result| result.ok())
+            .and_then(|details| details.metadata)
+            .map(|metadata| format!("{} ({})", metadata.name, metadata.symbol))
+            .unwrap_or_else(|| "Token".to_string())
+    };
+
+    view! {
+        <div class="container">
+            <Title text=title/>
+            <Suspense fallback=|| view! { <h2 class="token-management">"Token"</h2> }>
+                <h2 class="token-management">{title}</h2>
+            </Suspense>
+            <p class="token-address">
+                <a href=move || network().explorer_url().replace("{}", &mint()) target="_blank" rel="noopener noreferrer">
+                    {mint}
+                </a>
+            </p>
+            <nav class="tabs">
+                <button class=move || tab_class(TokenTab::Overview) on:click=move |_| set_tab(TokenTab::Overview)>
+                    "Overview"
+                </button>
+                <button class=move || tab_class(TokenTab::Holders) on:click=move |_| set_tab(TokenTab::Holders)>
+                    "Holders"
+                </button>
+                <button class=move || tab_class(TokenTab::Risk) on:click=move |_| set_tab(TokenTab::Risk)>
+                    "Risk"
+                </button>
+            </nav>
+            {move || match tab.get() {
+                TokenTab::Overview => view! {
+                    <Suspense fallback=|| view! { <p class="status-message">"Loading token..."</p> }>
+                        {move || details.get().map(|result| match result {
+                            Ok(details) => view! { <TokenOverview details=details/> }.into_view(),
+                            Err(err) => view! { <div class="error-message">{err}</div> }.into_view(),
+                        })}
+                    </Suspense>
+                }.into_view(),
+                TokenTab::Holders => view! { <HoldersTab mint=mint() network=network()/> }.into_view(),
+                TokenTab::Risk => view! { <RiskTab mint=mint() network=network()/> }.into_view(),
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn TokenOverview(details: TokenDetails) -> impl IntoView {
+    let wallet_ctx = use_context::<WalletContext>().expect("WalletContext not found");
+    let wallet_state = wallet_ctx.state;
+    let explorer = {
+        let network = details.network.clone();
+        move |address: &str| network.explorer_url().replace("{}", address)
+    };
+    let address_cell = {
+        let explorer = explorer.clone();
+        move |address: Option<String>| match address {
+            Some(address) => view! {
+                <a href=explorer(&address) target="_blank" rel="noopener noreferrer">{address}</a>
+            }.into_view(),
+            None => "None".into_view(),
+        }
+    };
+    let offchain = details.offchain.clone().unwrap_or_default();
+    let description = (!offchain.description.is_empty()).then_some(offchain.description);
+    let uri_url = details.uri_url.clone();
+    let metadata_rows = details.metadata.clone().map(|metadata| {
+        let source = match metadata.source {
+            MetadataSource::Metaplex => "Metaplex",
+            MetadataSource::Token2022 => "Token-2022 extension",
+        };
+        view! {
+            <tr><th>"Metadata"</th><td>{source}</td></tr>
+            <tr><th>"Metadata account"</th><td>{address_cell(Some(metadata.address))}</td></tr>
+            <tr><th>"Update authority"</th><td>{address_cell(metadata.update_authority)}</td></tr>
+            <tr><th>"Mutable"</th><td>{if metadata.is_mutable { "Yes" } else { "No" }}</td></tr>
+            <tr>
+                <th>"URI"</th>
+                <td>
+                    {match uri_url {
+                        Some(url) => view! { <a href=url target="_blank" rel="noopener noreferrer">{metadata.uri}</a> }.into_view(),
+                        None => metadata.uri.into_view(),
+                    }}
+                </td>
+            </tr>
+        }
+    });
+    let extensions = (!details.extensions.is_empty()).then(|| {
+        let items = details
+            .extensions
+            .iter()
+            .map(|extension| view! { <li>{extension.describe()}</li> })
+            .collect_view();
+        view! {
+            <h3>"Extensions"</h3>
+            <ul class="extension-list">{items}</ul>
+        }
+    });
+    let actions = {
+        let details = details.clone();
+        move || {
+            let wallet = wallet_state.get().address?;
+            let actions = details.authority_actions(&wallet);
+            if actions.is_empty() {
+                return None;
+            }
+            let items = actions
+                .into_iter()
+                .map(|action| view! {
+                    <li>
+                        <span>{action.label}</span>
+                        <code>{action.command}</code>
+                    </li>
+                })
+                .collect_view();
+            Some(view! {
+                <h3>"Your authority"</h3>
+                <ul class="authority-actions">{items}</ul>
+            })
+        }
+    };
+
+    view! {
+        <div class="token-overview">
+            {details.image_url.map(|url| view! { <img class="token-image" src=url alt="Token image"/> })}
+            {description.map(|description| view! { <p>{description}</p> })}
+            {details.offchain_error.map(|err| view! {
+                <div class="error-message">"Off-chain metadata unavailable: " {err}</div>
+            })}
+            <table class="token-table">
+                <tbody>
+                    <tr><th>"Program"</th><td>{details.program}</td></tr>
+                    <tr><th>"Supply"</th><td>{details.ui_supply}</td></tr>
+                    <tr><th>"Decimals"</th><td>{details.decimals}</td></tr>
+                    <tr><th>"Mint authority"</th><td>{address_cell(details.mint_authority)}</td></tr>
+                    <tr><th>"Freeze authority"</th><td>{address_cell(details.freeze_authority)}</td></tr>
+                    {metadata_rows}
+                </tbody>
+            </table>
+            {extensions}
+            {actions}
+        </div>
+    }
+}
+
+#[component]
+fn HoldersTab(mint: String, network: NetworkType) -> impl IntoView {
+    let (exclude_programs, set_exclude_programs) = create_signal(true);
+    let snapshot = create_action({
+        let mint = mint.clone();
+        let network = network.clone();
+        move |exclude_programs: &bool| get_holders(mint.clone(), network.clone(), *exclude_programs)
+    });
+    let export_url = move |extension: &str| {
+        format!(
+            "/v1/tokens/{}/holders{}?network={:?}&exclude_programs={}",
+            mint,
+            extension,
+            network,
+            exclude_programs.get()
+        )
+    };
+
+    view! {
+        <div class="holders">
+            <div class="form-group">
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || exclude_programs.get()
+                        on:change=move |ev| set_exclude_programs(event_target_checked(&ev))
+                    />
+                    " Exclude PDAs and program-owned accounts"
+                </label>
             </div>
+            <button
+                class="button"
+                disabled=move || snapshot.pending().get()
+                on:click=move |_| snapshot.dispatch(exclude_programs.get_untracked())
+            >
+                {move || if snapshot.pending().get() { "Reading accounts..." } else { "Take snapshot" }}
+            </button>
+            <a class="button" href={
+                let export_url = export_url.clone();
+                move || export_url(".csv")
+            }>"Export CSV"</a>
+            <a class="button" href=move || export_url("") target="_blank">"Export JSON"</a>
+            {move || snapshot.value().get().map(|result| match result {
+                Ok(snapshot) => view! { <HolderTable snapshot=snapshot/> }.into_view(),
+                Err(err) => view! { <div class="error-message">{err.to_string()}</div> }.into_view(),
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn RiskTab(mint: String, network: NetworkType) -> impl IntoView {
+    let report = create_resource(
+        move || (mint.clone(), network.clone()),
+        |(mint, network)| async move { get_risk_report(mint, network).await.map_err(|e| e.to_string()) },
+    );
+
+    view! {
+        <Suspense fallback=|| view! { <p class="status-message">"Running checks..."</p> }>
+            {move || report.get().map(|result| match result {
+                Ok(report) => view! { <RiskSummary report=report/> }.into_view(),
+                Err(err) => view! { <div class="error-message">{err}</div> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn RiskSummary(report: RiskReport) -> impl IntoView {
+    let rating = format!("{} risk: {}/100", report.rating.label(), report.score);
+    let share = |share: Option<f64>| share.map_or_else(|| "Unknown".to_string(), |share| format!("{:.1}%", share * 100.0));
+    let flags = report
+        .flags
+        .into_iter()
+        .map(|flag| {
+            let class = format!("risk-flag {}", flag.severity.label());
+            view! {
+                <li class=class>
+                    <strong>{flag.severity.label()}</strong>
+                    " " {flag.message}
+                    {(flag.weight > 0).then(|| format!(" (+{})", flag.weight))}
+                </li>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="risk-report">
+            <h3>{rating}</h3>
+            <table class="token-table">
+                <tbody>
+                    <tr><th>"Largest holder"</th><td>{share(report.top_holder_share)}</td></tr>
+                    <tr><th>"Ten largest holders"</th><td>{share(report.top_ten_share)}</td></tr>
+                </tbody>
+            </table>
+            <ul class="risk-flags">{flags}</ul>
+        </div>
+    }
+}
+
+#[component]
+fn HolderTable(snapshot: HolderSnapshot) -> impl IntoView {
+    let taken_at = chrono::DateTime::from_timestamp(snapshot.taken_at, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    let summary = format!("{} holders at slot {} ({})", snapshot.holders.len(), snapshot.slot, taken_at);
+    let excluded = snapshot.programs_excluded.then(|| {
+        format!("{} program-owned holders left out", snapshot.excluded_holders)
+    });
+    let rows = snapshot
+        .holders
+        .into_iter()
+        .enumerate()
+        .map(|(index, holder)| view! {
+            <tr>
+                <td>{index + 1}</td>
+                <td>{holder.owner}</td>
+                <td>{holder.ui_amount}</td>
+                <td>{format!("{:.2}%", holder.share * 100.0)}</td>
+            </tr>
+        })
+        .collect_view();
+
+    view! {
+        <p class="status-message">{summary}</p>
+        {excluded.map(|excluded| view! { <p class="status-message">{excluded}</p> })}
+        <table class="token-table">
+            <thead>
+                <tr>
+                    <th>"#"</th>
+                    <th>"Owner"</th>
+                    <th>"Amount"</th>
+                    <th>"Share"</th>
+                </tr>
+            </thead>
+            <tbody>{rows}</tbody>
+        </table>
+    }
+}
+
+#[component]
+fn JobProgress(job: JobStatus) -> impl IntoView {
+    let current = JobStage::PIPELINE.iter().position(|stage| *stage == job.stage);
+    let steps = JobStage::PIPELINE
+        .iter()
+        .enumerate()
+        .map(|(index, stage)| {
+            let class = match current {
+                Some(current) if index < current => "job-step done",
+                Some(current) if index == current => "job-step active",
+                None if job.stage == JobStage::Completed => "job-step done",
+                _ => "job-step",
+            };
+            view! { <li class=class>{stage.label()}</li> }
+        })
+        .collect_view();
+
+    view! {
+        <div id="creation-status" class="status-message">
+            <ol class="job-steps">{steps}</ol>
+            {job.result.map(|result| view! {
+                <div class="success-message">
+                    "Token created: "
+                    <a href=result.explorer_url.clone() target="_blank" rel="noopener noreferrer">
+                        {result.mint}
+                    </a>
+                </div>
+            })}
+            {job.error.map(|err| view! {
+                <div class="error-message">
+                    "Token creation failed: " {err}
+                </div>
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn Erc20TokenForm() -> impl IntoView {
+    let wallet_ctx = use_context::<WalletContext>().expect("WalletContext not found");
+    let (token_name, set_token_name) = create_signal(String::new());
+    let (token_symbol, set_token_symbol) = create_signal(String::new());
+    let (decimals, set_decimals) = create_signal(18u8);
+    let (initial_supply, set_initial_supply) = create_signal(1_000_000u64);
+    let (cap, set_cap) = create_signal(Option::<u64>::None);
+    let (mintable, set_mintable) = create_signal(true);
+    let (burnable, set_burnable) = create_signal(true);
+    let (loading, set_loading) = create_signal(false);
+    let (status, set_status) = create_signal(String::new());
+    let (deployed, set_deployed) = create_signal(Option::<Erc20DeployResult>::None);
+
+    let handle_submit = move |ev: SubmitEvent| {
+        ev.prevent_default();
+
+        let params = Erc20Params {
+            name: token_name.get_untracked(),
+            symbol: token_symbol.get_untracked(),
+            decimals: decimals.get_untracked(),
+            initial_supply: initial_supply.get_untracked(),
+            cap: cap.get_untracked(),
+            mintable: mintable.get_untracked(),
+            burnable: burnable.get_untracked(),
+        };
+
+        if let Err(e) = params.validate() {
+            set_status.set(e);
+            return;
+        }
+
+        let wallet_ctx = wallet_ctx.clone();
+        set_loading.set(true);
+        set_deployed.set(None);
+        set_status.set("Confirm the deployment in MetaMask...".to_string());
+
+        spawn_local(async move {
+            match wallet_ctx.deploy_erc20(&params).await {
+                Ok(result) => {
+                    set_status.set(format!("Token deployed at {}", result.contract_address));
+                    set_deployed.set(Some(result));
+                }
+                Err(e) => {
+                    set_status.set(format!("Token deployment failed: {}", e));
+                }
+            }
+            set_loading.set(false);
+        });
+    };
+
+    view! {
+        <div class="token-forms">
+            <form class="token-form" on:submit=handle_submit>
+                <div class="form-group">
+                    <label for="erc20_name">"Token Name"</label>
+                    <input
+                        type="text"
+                        id="erc20_name"
+                        required
+                        maxlength="31"
+                        placeholder="Enter token name"
+                        on:input=move |ev| {
+                            set_token_name.set(event_target_value(&ev));
+                        }
+                    />
+                </div>
+
+                <div class="form-group">
+                    <label for="erc20_symbol">"Token Symbol"</label>
+                    <input
+                        type="text"
+                        id="erc20_symbol"
+                        required
+                        maxlength="31"
+                        placeholder="Enter token symbol"
+                        on:input=move |ev| {
+                            set_token_symbol.set(event_target_value(&ev));
+                        }
+                    />
+                </div>
+
+                <div class="form-group">
+                    <label for="erc20_decimals">"Decimals (0-18)"</label>
+                    <input
+                        type="number"
+                        id="erc20_decimals"
+                        min="0"
+                        max="18"
+                        required
+                        value="18"
+                        on:input=move |ev| {
+                            if let Ok(value) = event_target_value(&ev).parse() {
+                                set_decimals.set(value);
+                            }
+                        }
+                    />
+                </div>
+
+                <div class="form-group">
+                    <label for="erc20_supply">"Initial Supply"</label>
+                    <input
+                        type="number"
+                        id="erc20_supply"
+                        min="0"
+                        required
+                        value="1000000"
+                        on:input=move |ev| {
+                            if let Ok(value) = event_target_value(&ev).parse() {
+                                set_initial_supply.set(value);
+                            }
+                        }
+                    />
+                </div>
+
+                <div class="form-group">
+                    <label for="erc20_cap">"Supply Cap"</label>
+                    <input
+                        type="number"
+                        id="erc20_cap"
+                        min="1"
+                        placeholder="Optional: Leave empty for no cap"
+                        on:input=move |ev| {
+                            let value = event_target_value(&ev).parse::<u64>().ok();
+                            set_cap.set(value);
+                        }
+                    />
+                </div>
+
+                <div class="form-group checkbox-group">
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked=true
+                            on:change=move |ev| {
+                                set_mintable.set(event_target_checked(&ev));
+                            }
+                        />
+                        "Owner can mint new tokens"
+                    </label>
+                </div>
+
+                <div class="form-group checkbox-group">
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked=true
+                            on:change=move |ev| {
+                                set_burnable.set(event_target_checked(&ev));
+                            }
+                        />
+                        "Holders can burn their tokens"
+                    </label>
+                </div>
+
+                <div class="status-message">
+                    {move || status.get()}
+                </div>
+
+                {move || deployed.get().map(|result| view! {
+                    <div class="success-message">
+                        <div>"Contract: " {result.contract_address.clone()}</div>
+                        <div>"Transaction: " {result.transaction_hash.clone()}</div>
+                        {result.explorer_url.clone().map(|url| view! {
+                            <a href=url target="_blank" rel="noopener">"View on Explorer"</a>
+                        })}
+                    </div>
+                })}
+
+                <button
+                    type="submit"
+                    class="button"
+                    disabled=move || loading.get()
+                >
+                    {move || if loading.get() { "Deploying Token..." } else { "Deploy ERC-20 Token" }}
+                </button>
+            </form>
         </div>
     }
 }
@@ -428,6 +1203,7 @@ fn WalletConnect() -> impl IntoView {
             if wallet_ctx.state.get().connected {
                 let wallet_ctx = wallet_ctx.clone();
                 spawn_local(async move {
+                    wallet_ctx.restore_session().await;
                     if let Ok(balance) = wallet_ctx.get_balance().await {
                         wallet_ctx.state.update(|state| {
                             state.sol_balance = balance;
@@ -463,12 +1239,24 @@ fn WalletConnect() -> impl IntoView {
         }
     });
 
+    let sign_in = create_action({
+        let wallet_ctx = wallet_ctx.clone();
+        move |_: &()| {
+            let wallet_ctx = wallet_ctx.clone();
+            async move {
+                if let Err(e) = wallet_ctx.sign_in().await {
+                    wallet_ctx.set_error(&e);
+                }
+            }
+        }
+    });
+
     let disconnect = create_action({
         let wallet_ctx = wallet_ctx.clone();
         move |_: &()| {
             let wallet_ctx = wallet_ctx.clone();
             async move {
-                wallet_ctx.disconnect();
+                wallet_ctx.sign_out().await;
             }
         }
     });
@@ -483,6 +1271,12 @@ fn WalletConnect() -> impl IntoView {
                             <div class="wallet-address">
                                 {wallet_ctx.state.get().address.clone().unwrap_or_default()}
                             </div>
+                            {wallet_ctx.state.get().session.is_none().then(|| view! {
+                                <button class="connect-button"
+                                    on:click=move |_| sign_in.dispatch(())>
+                                    "Sign In"
+                                </button>
+                            })}
                             <button class="disconnect-button"
                                 on:click=move |_| disconnect.dispatch(())>
                                 "Disconnect"
@@ -0,0 +1,89 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{require_admin, require_session, AuthSession, AuthStore};
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use server::random_token;
+
+pub const SESSION_COOKIE: &str = "sol_tools_session";
+
+/// Message the wallet must sign to prove it controls `address`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignInChallenge {
+    pub nonce: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SessionInfo {
+    pub address: String,
+    /// Unix timestamp (seconds) after which the session is rejected.
+    pub expires_at: i64,
+}
+
+#[server(RequestSignIn, "/api")]
+pub async fn request_sign_in(address: String) -> Result<SignInChallenge, ServerFnError> {
+    use actix_web::{web, HttpRequest};
+
+    let req = leptos_actix::extract::<HttpRequest>().await?;
+    let store = leptos_actix::extract::<web::Data<AuthStore>>().await?;
+    let info = req.connection_info();
+    let uri = format!("{}://{}", info.scheme(), info.host());
+
+    store
+        .issue_challenge(&address, info.host(), &uri)
+        .map_err(ServerFnError::new)
+}
+
+#[server(VerifySignIn, "/api")]
+pub async fn verify_sign_in(nonce: String, signature: String) -> Result<SessionInfo, ServerFnError> {
+    use actix_web::{cookie::{time::Duration, Cookie, SameSite}, http::header, web, HttpRequest};
+    use leptos_actix::ResponseOptions;
+
+    let req = leptos_actix::extract::<HttpRequest>().await?;
+    let store = leptos_actix::extract::<web::Data<AuthStore>>().await?;
+    let (token, session) = store.verify(&nonce, &signature).map_err(ServerFnError::new)?;
+
+    let cookie = Cookie::build(SESSION_COOKIE, token)
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .secure(req.connection_info().scheme() == "https")
+        .max_age(Duration::seconds(store.session_ttl().as_secs() as i64))
+        .finish();
+
+    let response = expect_context::<ResponseOptions>();
+    let value = header::HeaderValue::from_str(&cookie.to_string()).map_err(ServerFnError::new)?;
+    response.append_header(header::SET_COOKIE, value);
+
+    Ok(session)
+}
+
+#[server(CurrentSession, "/api")]
+pub async fn current_session() -> Result<Option<SessionInfo>, ServerFnError> {
+    Ok(leptos_actix::extract::<Option<AuthSession>>().await?.map(|session| session.0))
+}
+
+#[server(SignOut, "/api")]
+pub async fn sign_out() -> Result<(), ServerFnError> {
+    use actix_web::{cookie::Cookie, http::header, web, HttpRequest};
+    use leptos_actix::ResponseOptions;
+
+    let req = leptos_actix::extract::<HttpRequest>().await?;
+    let store = leptos_actix::extract::<web::Data<AuthStore>>().await?;
+    if let Some(cookie) = req.cookie(SESSION_COOKIE) {
+        store.revoke(cookie.value());
+    }
+
+    let mut removal = Cookie::build(SESSION_COOKIE, "").path("/").finish();
+    removal.make_removal();
+    let response = expect_context::<ResponseOptions>();
+    let value = header::HeaderValue::from_str(&removal.to_string()).map_err(ServerFnError::new)?;
+    response.append_header(header::SET_COOKIE, value);
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use std::{
+    collections::HashMap,
+    future::{ready, Ready},
+    str::FromStr,
+    sync::Mutex,
+    time::Duration,
+};
+
+use actix_web::{dev::Payload, error::ErrorUnauthorized, web, FromRequest, HttpRequest};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use leptos::ServerFnError;
+use rand::{rngs::OsRng, RngCore};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use super::{SessionInfo, SignInChallenge, SESSION_COOKIE};
+use crate::config::ServerConfig;
+
+const CHALLENGE_TTL: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+struct PendingChallenge {
+    address: Pubkey,
+    message: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Outstanding sign-in nonces and active sessions, shared by every worker.
+pub struct AuthStore {
+    challenges: Mutex<HashMap<String, PendingChallenge>>,
+    sessions: Mutex<HashMap<String, SessionInfo>>,
+    session_ttl: Duration,
+}
+
+impl Default for AuthStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_SESSION_TTL)
+    }
+}
+
+impl AuthStore {
+    pub fn new(session_ttl: Duration) -> Self {
+        Self {
+            challenges: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+            session_ttl,
+        }
+    }
+
+    pub fn session_ttl(&self) -> Duration {
+        self.session_ttl
+    }
+
+    pub fn issue_challenge(&self, address: &str, domain: &str, uri: &str) -> Result<SignInChallenge> {
+        let address = Pubkey::from_str(address)
+            .map_err(|_| anyhow!("Invalid Solana address: {}", address))?;
+        let nonce = random_token(16);
+        let issued_at = Utc::now();
+        let expires_at = issued_at + chrono::Duration::from_std(CHALLENGE_TTL)?;
+
+        let message = format!(
+            "{domain} wants you to sign in with your Solana account:\n\
+             {address}\n\
+             \n\
+             Sign in to Token Tools to create and manage tokens.\n\
+             \n\
+             URI: {uri}\n\
+             Version: 1\n\
+             Nonce: {nonce}\n\
+             Issued At: {issued_at}\n\
+             Expiration Time: {expires_at}",
+            issued_at = issued_at.to_rfc3339(),
+            expires_at = expires_at.to_rfc3339(),
+        );
+
+        let mut challenges = self.challenges.lock().unwrap();
+        let now = Utc::now();
+        challenges.retain(|_, pending| pending.expires_at > now);
+        challenges.insert(nonce.clone(), PendingChallenge {
+            address,
+            message: message.clone(),
+            expires_at,
+        });
+
+        Ok(SignInChallenge { nonce, message })
+    }
+
+    /// Consumes the challenge for `nonce` and, if `signature` is a valid
+    /// ed25519 signature of its message, opens a session. Returns the
+    /// session token to store in the cookie.
+    pub fn verify(&self, nonce: &str, signature: &str) -> Result<(String, SessionInfo)> {
+        let pending = self
+            .challenges
+            .lock()
+            .unwrap()
+            .remove(nonce)
+            .ok_or_else(|| anyhow!("Unknown or already used sign-in nonce"))?;
+
+        if pending.expires_at <= Utc::now() {
+            return Err(anyhow!("Sign-in challenge expired"));
+        }
+
+        let signature = Signature::from_str(signature)
+            .map_err(|_| anyhow!("Malformed signature"))?;
+        if !signature.verify(pending.address.as_ref(), pending.message.as_bytes()) {
+            return Err(anyhow!("Signature does not match {}", pending.address));
+        }
+
+        let session = SessionInfo {
+            address: pending.address.to_string(),
+            expires_at: (Utc::now() + chrono::Duration::from_std(self.session_ttl)?).timestamp(),
+        };
+        let token = random_token(32);
+
+        let mut sessions = self.sessions.lock().unwrap();
+        let now = Utc::now().timestamp();
+        sessions.retain(|_, existing| existing.expires_at > now);
+        sessions.insert(token.clone(), session.clone());
+
+        Ok((token, session))
+    }
+
+    pub fn session(&self, token: &str) -> Option<SessionInfo> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(token)
+            .filter(|session| session.expires_at > Utc::now().timestamp())
+            .cloned()
+    }
+
+    pub fn revoke(&self, token: &str) {
+        self.sessions.lock().unwrap().remove(token);
+    }
+}
+
+/// Extractor for endpoints that require a signed-in wallet. Rejects the
+/// request with 401 when the session cookie is missing, unknown or expired.
+pub struct AuthSession(pub SessionInfo);
+
+impl FromRequest for AuthSession {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let session = req
+            .app_data::<web::Data<AuthStore>>()
+            .zip(req.cookie(SESSION_COOKIE))
+            .and_then(|(store, cookie)| store.session(cookie.value()));
+
+        ready(session.map(AuthSession).ok_or_else(|| ErrorUnauthorized("Sign in required")))
+    }
+}
+
+/// Server function guard; fails unless the caller has signed in.
+pub async fn require_session() -> Result<AuthSession, ServerFnError> {
+    leptos_actix::extract::<AuthSession>().await
+}
+
+/// Server function guard; fails unless the caller has signed in with one of
+/// the configured admin wallets.
+pub async fn require_admin() -> Result<AuthSession, ServerFnError> {
+    let session = require_session().await?;
+    let config = leptos_actix::extract::<web::Data<ServerConfig>>().await?;
+    if !config.is_admin(&session.0.address) {
+        return Err(ServerFnError::new("Admin access required"));
+    }
+    Ok(session)
+}
+
+pub(crate) fn random_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    bs58::encode(bytes).into_string()
+}
@@ -0,0 +1,117 @@
+//! `create_spl airdrop`: pay every wallet in a CSV.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::{Args, ValueEnum};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use crate::{
+    output::{emit, AirdropPreview},
+    Context,
+};
+use sol_tools::token::{
+    airdrop::{
+        plan_airdrop, read_recipients, run_airdrop, settle_pending, AirdropState, DistributionMode, LookupTablePlan,
+        LookupTableUse, DEFAULT_CONCURRENCY,
+    },
+    ops::{self, format_amount},
+};
+
+#[derive(Args)]
+pub struct AirdropArgs {
+    mint: Pubkey,
+    /// `recipient,amount` rows, amounts in whole tokens.
+    csv: PathBuf,
+    #[arg(long, value_enum, default_value_t = Mode::Transfer)]
+    mode: Mode,
+    /// Per-row payment state; rows it records as paid are skipped. Defaults
+    /// to the CSV path with a `.state.jsonl` extension. It is tied to the
+    /// mint and cluster of its first run.
+    #[arg(long)]
+    state: Option<PathBuf>,
+    /// Write the reconciliation report here: CSV for `.csv`, JSON
+    /// otherwise. Repeatable.
+    #[arg(long)]
+    report: Vec<PathBuf>,
+    /// Transactions in flight at once.
+    #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+    concurrency: usize,
+    /// Load repeated accounts from this lookup table instead of creating
+    /// one when it would save transactions.
+    #[arg(long, conflicts_with = "no_lookup_table")]
+    lookup_table: Option<Pubkey>,
+    /// Send without a lookup table.
+    #[arg(long)]
+    no_lookup_table: bool,
+    /// Show what would be sent without sending it.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Mode {
+    /// Transfer from the signer's token account.
+    Transfer,
+    /// Mint new tokens; the signer must be the mint authority.
+    Mint,
+}
+
+pub async fn run(context: &Context, args: AirdropArgs, json: bool) -> Result<()> {
+    let mint = ops::load_mint(&context.client, &args.mint).await?;
+    let recipients = read_recipients(&args.csv, mint.decimals)?;
+    let state_path = args.state.unwrap_or_else(|| AirdropState::path_for(&args.csv));
+    let state = AirdropState::open(&state_path, &args.mint, &context.network_key)?;
+    settle_pending(&context.client, &state).await?;
+    let mode = match args.mode {
+        Mode::Transfer => DistributionMode::Transfer,
+        Mode::Mint => DistributionMode::Mint,
+    };
+    let lookup_table = match (args.lookup_table, args.no_lookup_table) {
+        (Some(table), _) => LookupTableUse::Existing(table),
+        (None, true) => LookupTableUse::Never,
+        (None, false) => LookupTableUse::Auto,
+    };
+    let plan = plan_airdrop(&context.client, &mint, &context.signer.pubkey(), mode, recipients, &state, lookup_table).await?;
+
+    if args.dry_run {
+        let total = plan
+            .unpaid()
+            .try_fold(0u64, |total, row| total.checked_add(row.amount))
+            .ok_or_else(|| anyhow!("Total amount overflows"))?;
+        let preview = AirdropPreview {
+            mint: mint.address.to_string(),
+            mode,
+            recipients: plan.unpaid().count(),
+            already_paid: plan.already_paid.len(),
+            transactions: plan.transactions.len(),
+            accounts_to_create: plan.accounts_to_create,
+            total: format_amount(total, mint.decimals),
+            lookup_table: plan.lookup_table.as_ref().map(|table| match table {
+                LookupTablePlan::Existing(table) => table.key.to_string(),
+                LookupTablePlan::Create(_) => "new".to_string(),
+            }),
+        };
+        return emit(&preview, json);
+    }
+
+    let report = run_airdrop(&context.client, &mint, &context.signer, &plan, &state, args.concurrency, |transaction, outcome| {
+        if json {
+            return;
+        }
+        let lines: Vec<String> = transaction.recipients.iter().map(|row| row.line.to_string()).collect();
+        match outcome {
+            Ok(signature) => eprintln!("lines {}: paid in {}", lines.join(","), signature),
+            Err(e) => eprintln!("lines {}: failed: {:#}", lines.join(","), e),
+        }
+    })
+    .await?;
+    for path in &args.report {
+        report.write(path)?;
+    }
+    emit(&report, json)?;
+    if report.failed > 0 {
+        return Err(anyhow!("{} of {} rows were not paid; run again to retry them", report.failed, report.rows.len()));
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! `create_spl metadata attach`: brand a mint that was created without
+//! metadata.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context as _, Result};
+use clap::Args;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use crate::{output::emit, Context};
+use sol_tools::{
+    config::ServerConfig,
+    metadata::{validate_name, validate_symbol, OffchainMetadata},
+    storage::{self, UploadQuota},
+    registry::TokenRegistry,
+    token::{attach_metadata, check_can_attach_metadata, ops::load_mint, CreateTokenParams, TokenCreationResult},
+    upload::store_image,
+};
+
+#[derive(Args)]
+pub struct AttachArgs {
+    mint: Pubkey,
+    #[arg(long)]
+    name: String,
+    #[arg(long)]
+    symbol: String,
+    /// URI of metadata JSON that is already hosted, instead of uploading
+    /// one.
+    #[arg(long, conflicts_with_all = ["description", "image"])]
+    uri: Option<String>,
+    #[arg(long, default_value = "")]
+    description: String,
+    /// Logo to crop, resize and upload with the metadata.
+    #[arg(long)]
+    image: Option<PathBuf>,
+    /// Lock the metadata at creation.
+    #[arg(long)]
+    immutable: bool,
+}
+
+/// The mint is recorded in the server's registry afterwards, like tokens the
+/// server creates, so storage GC keeps the uploaded metadata.
+pub async fn run(context: &Context, args: AttachArgs, json: bool) -> Result<()> {
+    validate_name(&args.name)?;
+    validate_symbol(&args.symbol)?;
+    check_can_attach_metadata(&context.client, &context.signer.pubkey(), &args.mint).await?;
+    let config = ServerConfig::load()?;
+    let registry = TokenRegistry::open(&config.registry.database_path)?;
+
+    let uri = match &args.uri {
+        Some(uri) => uri.clone(),
+        None => upload(context, &config, &args, json).await?,
+    };
+    let signature = attach_metadata(
+        &context.client,
+        &context.signer,
+        &args.mint,
+        args.name.clone(),
+        args.symbol.clone(),
+        uri.clone(),
+        !args.immutable,
+    )
+    .await?;
+
+    let mint = load_mint(&context.client, &args.mint).await?;
+    let params = CreateTokenParams {
+        name: args.name,
+        symbol: args.symbol,
+        description: args.description,
+        metadata_uri: uri.clone(),
+        decimals: mint.decimals,
+        initial_supply: 0,
+        is_mutable: !args.immutable,
+        freeze_authority: mint.freeze_authority.is_some(),
+        rate_limit: None,
+        transfer_fee: None,
+        max_transfer_amount: None,
+        network: context.network.clone(),
+        payer: None,
+    };
+    let result = TokenCreationResult {
+        status: "Attached".to_string(),
+        mint: args.mint.to_string(),
+        explorer_url: context.network.explorer_url().replace("{}", &args.mint.to_string()),
+        signature: signature.to_string(),
+        signatures: vec![signature.to_string()],
+    };
+    registry.record_creation(params, &context.signer.pubkey().to_string(), &result)?;
+    emit(&context.report("Attached metadata", &args.mint, signature.to_string()).with("uri", uri), json)
+}
+
+/// Uploads the logo and the metadata JSON through the storage backend in
+/// the server config, returning the JSON's URI.
+async fn upload(context: &Context, config: &ServerConfig, args: &AttachArgs, json: bool) -> Result<String> {
+    let store = storage::from_config(config)?;
+    let mut document = OffchainMetadata {
+        name: args.name.clone(),
+        symbol: args.symbol.clone(),
+        description: args.description.clone(),
+        ..OffchainMetadata::default()
+    };
+    if let Some(path) = &args.image {
+        let bytes = fs::read(path).with_context(|| format!("Failed to read image {}", path.display()))?;
+        // The quota guards the server against its users; here it only
+        // bounds this one upload.
+        let quota = UploadQuota::new(config.storage.quota.clone());
+        let owner = context.signer.pubkey().to_string();
+        let uploaded = store_image(store.as_ref(), &quota, &owner, bytes, config.images.clone()).await?;
+        if !json {
+            eprintln!("Uploaded logo {}", uploaded.image);
+        }
+        document.image = uploaded.image;
+        document = document.with_files(uploaded.files);
+    }
+    document.validate()?;
+    let uri = store.put_json(&serde_json::to_value(&document)?).await?.uri;
+    if !json {
+        eprintln!("Uploaded metadata {}", uri);
+    }
+    Ok(uri)
+}
@@ -0,0 +1,52 @@
+//! `create_spl batch`: create every token in a manifest.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use clap::Args;
+
+use crate::{output::emit, Context};
+use sol_tools::token::batch::{run_batch, EntryStatus, Ledger, Manifest, DEFAULT_CONCURRENCY};
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// Manifest of tokens to create: JSON, YAML or TOML.
+    manifest: PathBuf,
+    /// Ledger of created entries; entries already in it are skipped.
+    /// Defaults to the manifest path with a `.ledger.jsonl` extension. It is
+    /// tied to the cluster of its first run.
+    #[arg(long)]
+    ledger: Option<PathBuf>,
+    /// Write the summary here: CSV for `.csv`, JSON otherwise. Repeatable.
+    #[arg(long)]
+    summary: Vec<PathBuf>,
+    /// Transactions in flight at once.
+    #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+    concurrency: usize,
+}
+
+pub async fn run(context: &Context, args: BatchArgs, json: bool) -> Result<()> {
+    let manifest = Manifest::load(&args.manifest)?;
+    manifest.validate(&context.network)?;
+    let ledger = Ledger::open(&args.ledger.unwrap_or_else(|| Ledger::path_for(&args.manifest)), &context.network_key)?;
+    let total = manifest.tokens.len();
+    let payer = Arc::new(context.signer.insecure_clone());
+    let summary = run_batch(&manifest, &ledger, &context.rpc_url, &context.network, payer, args.concurrency, |entry, outcome| {
+        if json {
+            return;
+        }
+        match outcome.status {
+            EntryStatus::Failed => eprintln!("{}: failed: {}", entry.id, outcome.error.as_deref().unwrap_or_default()),
+            status => eprintln!("{}: {} {}", entry.id, status.label(), outcome.mint.as_deref().unwrap_or_default()),
+        }
+    })
+    .await;
+    for path in &args.summary {
+        summary.write(path)?;
+    }
+    emit(&summary, json)?;
+    if summary.failed > 0 {
+        return Err(anyhow!("{} of {} entries failed; run again to retry them", summary.failed, total));
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! `create_spl check-metadata`: compare on-chain metadata with the JSON and
+//! image it points to.
+
+use anyhow::{anyhow, Result};
+use clap::Args;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    output::{emit, MetadataCheckReport},
+    Context,
+};
+use sol_tools::{
+    config::ServerConfig,
+    registry::TokenRegistry,
+    token::consistency::{check_consistency, check_registry, ConsistencyReport},
+};
+
+#[derive(Args)]
+pub struct CheckMetadataArgs {
+    #[arg(required_unless_present = "registry", conflicts_with = "registry")]
+    mints: Vec<Pubkey>,
+    /// Check every token in the server's registry instead, each through the
+    /// RPC endpoint the server config sets for its network.
+    #[arg(long)]
+    registry: bool,
+    /// IPFS gateway to try, in order. Repeatable; defaults to the server
+    /// config's gateway and its fallbacks.
+    #[arg(long = "gateway")]
+    gateways: Vec<String>,
+}
+
+pub async fn run(context: &Context, args: CheckMetadataArgs, json: bool) -> Result<()> {
+    let mut config = ServerConfig::load()?;
+    if let Some((first, rest)) = args.gateways.split_first() {
+        config.storage.ipfs_gateway = first.clone();
+        config.storage.fallback_gateways = rest.to_vec();
+    }
+
+    let reports = if args.registry {
+        let registry = TokenRegistry::open(&config.registry.database_path)?;
+        check_registry(&registry, &config).await?
+    } else {
+        let gateways = config.storage.ipfs_gateways();
+        let mut reports = Vec::with_capacity(args.mints.len());
+        for mint in &args.mints {
+            let report = check_consistency(&context.client, mint, context.network.clone(), &gateways)
+                .await
+                .unwrap_or_else(|e| ConsistencyReport::failed(mint.to_string(), context.network.clone(), &e));
+            reports.push(report);
+        }
+        reports
+    };
+
+    let inconsistent = reports.iter().filter(|report| !report.is_consistent()).count();
+    let total = reports.len();
+    emit(&MetadataCheckReport(reports), json)?;
+    if inconsistent > 0 {
+        return Err(anyhow!("{} of {} token(s) have metadata issues", inconsistent, total));
+    }
+    Ok(())
+}
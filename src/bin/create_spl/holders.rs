@@ -0,0 +1,37 @@
+//! `create_spl holders`: snapshot who holds a mint.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    output::{emit, HoldersReport},
+    Context,
+};
+use sol_tools::token::holders::snapshot_holders;
+
+#[derive(Args)]
+pub struct HoldersArgs {
+    mint: Pubkey,
+    /// Leave out owners that are PDAs or program-owned accounts, such as
+    /// pools and vaults.
+    #[arg(long)]
+    exclude_programs: bool,
+    /// Write the full snapshot here: CSV for `.csv`, JSON otherwise.
+    /// Repeatable.
+    #[arg(long)]
+    output: Vec<PathBuf>,
+    /// Holders to list in the summary.
+    #[arg(long, default_value_t = 20)]
+    top: usize,
+}
+
+pub async fn run(context: &Context, args: HoldersArgs, json: bool) -> Result<()> {
+    let snapshot = snapshot_holders(&context.client, &args.mint, args.exclude_programs).await?;
+    for path in &args.output {
+        snapshot.write(path)?;
+    }
+    emit(&HoldersReport { snapshot: &snapshot, top: args.top }, json)
+}
@@ -0,0 +1,128 @@
+//! `create_spl keystore`: manage the encrypted keystore the server unlocks its
+//! payer from.
+
+use std::{fs, io::Write, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use clap::Subcommand;
+use solana_sdk::signature::Keypair;
+use zeroize::Zeroizing;
+
+use crate::output::{emit, KeystoreList, KeystoreListing, KeystoreReport};
+use sol_tools::utils::{
+    keypair::resolve_keypair,
+    keystore::{read_passphrase, Keystore},
+};
+
+const PASSPHRASE_ENV: &str = "SOL_TOOLS_KEYSTORE_PASSPHRASE";
+const NEW_PASSPHRASE_ENV: &str = "SOL_TOOLS_KEYSTORE_NEW_PASSPHRASE";
+
+#[derive(Subcommand)]
+pub enum KeystoreCommand {
+    /// Generate a new keypair and store it.
+    Create { name: String },
+    /// Store an existing keypair: a file, `env://NAME` or `prompt://`.
+    Import { name: String, source: String },
+    /// Decrypt an entry and write it as a Solana CLI keypair file, or to
+    /// stdout.
+    Export {
+        name: String,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Re-encrypt an entry under a new passphrase.
+    Rotate { name: String },
+    /// List entries and their addresses.
+    List,
+    /// Delete an entry.
+    Remove { name: String },
+}
+
+/// Runs a keystore command. Passphrases come from
+/// `SOL_TOOLS_KEYSTORE_PASSPHRASE` (and `SOL_TOOLS_KEYSTORE_NEW_PASSPHRASE`
+/// for `rotate`), a terminal prompt, or stdin.
+pub fn run(path: PathBuf, command: KeystoreCommand, json: bool) -> Result<()> {
+    let mut keystore = Keystore::open(&path)?;
+    let report = |action: &'static str, name: &str, address: &str| KeystoreReport {
+        action,
+        name: name.to_string(),
+        address: address.to_string(),
+        keystore: path.display().to_string(),
+    };
+
+    match command {
+        KeystoreCommand::Create { name } => {
+            let passphrase = new_passphrase(PASSPHRASE_ENV)?;
+            let address = keystore.insert(&name, &Keypair::new(), &passphrase)?.address.clone();
+            keystore.save(&path)?;
+            emit(&report("Created keystore entry", &name, &address), json)
+        }
+        KeystoreCommand::Import { name, source } => {
+            let keypair = resolve_keypair(&source)?;
+            let passphrase = new_passphrase(PASSPHRASE_ENV)?;
+            let address = keystore.insert(&name, &keypair, &passphrase)?.address.clone();
+            keystore.save(&path)?;
+            emit(&report("Imported keystore entry", &name, &address), json)
+        }
+        KeystoreCommand::Export { name, out } => {
+            let passphrase = read_passphrase(PASSPHRASE_ENV, &format!("Passphrase for {}: ", name))?;
+            let keypair = keystore.export(&name, &passphrase)?;
+            let secret = Zeroizing::new(serde_json::to_string(&keypair.to_bytes().to_vec())?);
+            let Some(out) = out else {
+                println!("{}", *secret);
+                return Ok(());
+            };
+            write_private(&out, secret.as_bytes())?;
+            emit(&report("Exported keystore entry", &name, &keystore.entry(&name)?.address), json)
+        }
+        KeystoreCommand::Rotate { name } => {
+            let passphrase = read_passphrase(PASSPHRASE_ENV, &format!("Current passphrase for {}: ", name))?;
+            keystore.entry(&name)?.unlock(&passphrase)?;
+            let new_passphrase = new_passphrase(NEW_PASSPHRASE_ENV)?;
+            let address = keystore.rotate(&name, &passphrase, &new_passphrase)?.address.clone();
+            keystore.save(&path)?;
+            emit(&report("Rotated keystore entry", &name, &address), json)
+        }
+        KeystoreCommand::List => {
+            let entries = keystore
+                .entries
+                .iter()
+                .map(|(name, entry)| KeystoreListing {
+                    name: name.clone(),
+                    address: entry.address.clone(),
+                    updated_at: entry.updated_at,
+                })
+                .collect();
+            emit(&KeystoreList { keystore: path.display().to_string(), entries }, json)
+        }
+        KeystoreCommand::Remove { name } => {
+            let entry = keystore.remove(&name)?;
+            keystore.save(&path)?;
+            emit(&report("Removed keystore entry", &name, &entry.address), json)
+        }
+    }
+}
+
+/// Reads a passphrase for new encryption, asking twice on a terminal.
+fn new_passphrase(env_var: &str) -> Result<Zeroizing<String>> {
+    let prompting = std::env::var_os(env_var).is_none() && std::io::IsTerminal::is_terminal(&std::io::stdin());
+    let passphrase = read_passphrase(env_var, "New passphrase: ")?;
+    if passphrase.is_empty() {
+        return Err(anyhow!("The keystore passphrase cannot be empty"));
+    }
+    if prompting && *read_passphrase(env_var, "Repeat passphrase: ")? != *passphrase {
+        return Err(anyhow!("Passphrases do not match"));
+    }
+    Ok(passphrase)
+}
+
+/// Creates `path` readable only by its owner; never overwrites.
+fn write_private(path: &PathBuf, contents: &[u8]) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(contents)?;
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! `create_spl lookup-table`: manage address lookup tables.
+
+use anyhow::Result;
+use clap::Subcommand;
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+
+use crate::{
+    output::{emit, LookupTableReport},
+    Context,
+};
+use sol_tools::token::lookup_table::{
+    close_lookup_table, create_lookup_table, deactivate_lookup_table, extend_lookup_table, lookup_table_info,
+};
+
+#[derive(Subcommand)]
+pub enum LookupTableCommand {
+    /// Create a table owned by the signer, optionally filled with addresses.
+    Create { addresses: Vec<Pubkey> },
+    /// Append addresses to a table; ones it already holds are skipped.
+    Extend {
+        table: Pubkey,
+        #[arg(required = true)]
+        addresses: Vec<Pubkey>,
+    },
+    /// Stop a table from being used so it can be closed after a cool-down.
+    Deactivate { table: Pubkey },
+    /// Close a deactivated table and reclaim its rent.
+    Close {
+        table: Pubkey,
+        /// Receives the rent; defaults to the signer.
+        #[arg(long)]
+        recipient: Option<Pubkey>,
+    },
+    /// Show a table's authority, state and addresses.
+    Show { table: Pubkey },
+}
+
+pub async fn run(context: &Context, command: LookupTableCommand, json: bool) -> Result<()> {
+    let client = &context.client;
+    let signer = &context.signer;
+    let (action, table, signatures) = match command {
+        LookupTableCommand::Create { addresses } => {
+            let (table, signatures) = create_lookup_table(client, signer, &addresses).await?;
+            ("Created lookup table", table, signatures)
+        }
+        LookupTableCommand::Extend { table, addresses } => {
+            ("Extended lookup table", table, extend_lookup_table(client, signer, &table, &addresses).await?)
+        }
+        LookupTableCommand::Deactivate { table } => {
+            ("Deactivated lookup table", table, vec![deactivate_lookup_table(client, signer, &table).await?])
+        }
+        LookupTableCommand::Close { table, recipient } => {
+            let recipient = recipient.unwrap_or_else(|| signer.pubkey());
+            ("Closed lookup table", table, vec![close_lookup_table(client, signer, &table, &recipient).await?])
+        }
+        LookupTableCommand::Show { table } => return emit(&lookup_table_info(client, &table).await?, json),
+    };
+    let report = LookupTableReport {
+        action,
+        table: table.to_string(),
+        explorer_url: context.network.explorer_url().replace("{}", &table.to_string()),
+        signatures: signatures.iter().map(ToString::to_string).collect(),
+    };
+    emit(&report, json)
+}
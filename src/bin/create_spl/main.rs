@@ -0,0 +1,357 @@
+//! Command-line management of SPL tokens: create a mint with metadata, then
+//! mint, burn, move, freeze and administer it. Every command prints a human
+//! summary, or a single JSON document with `--json`.
+
+mod airdrop;
+mod attach;
+mod batch;
+mod check_metadata;
+mod holders;
+mod keystore;
+mod lookup_table;
+mod output;
+mod spec;
+
+use std::{collections::BTreeMap, path::PathBuf, process::ExitCode, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair, signer::Signer};
+
+use output::{emit, emit_error, TxReport};
+use sol_tools::token::{
+    create_token_with_rpc,
+    ops::{self, MetadataUpdate, MintAuthority},
+    CreateTokenParams, NetworkType,
+};
+use sol_tools::utils::keypair::{default_keypair, resolve_keypair, SolanaCliConfig};
+
+#[derive(Parser)]
+#[command(name = "create_spl", version, about = "Create and manage SPL tokens")]
+struct Cli {
+    /// RPC endpoint: `devnet`, `mainnet`, `localhost` or a URL. Defaults to
+    /// the Solana CLI config, then devnet.
+    #[arg(short = 'u', long = "url", global = true, env = "SOL_TOOLS_RPC_URL")]
+    url: Option<String>,
+
+    /// Keypair that signs and pays: a file, `env://NAME` or `prompt://`.
+    /// Defaults to the Solana CLI keypair.
+    #[arg(short = 'k', long, global = true, env = "SOLANA_KEYPAIR_PATH")]
+    keypair: Option<String>,
+
+    /// Print one JSON document instead of a human summary.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a mint with Metaplex metadata and an optional initial supply.
+    Create(CreateArgs),
+    /// Mint tokens to a wallet.
+    Mint {
+        mint: Pubkey,
+        /// Amount in whole tokens, e.g. `1.5`.
+        amount: String,
+        /// Receiving wallet; defaults to the signer.
+        #[arg(long)]
+        to: Option<Pubkey>,
+    },
+    /// Burn tokens from the signer's account.
+    Burn { mint: Pubkey, amount: String },
+    /// Send tokens from the signer to a wallet.
+    Transfer { mint: Pubkey, amount: String, recipient: Pubkey },
+    /// Freeze a wallet's token account.
+    Freeze { mint: Pubkey, holder: Pubkey },
+    /// Thaw a wallet's token account.
+    Thaw { mint: Pubkey, holder: Pubkey },
+    /// Hand over or revoke the mint, freeze or metadata update authority.
+    Authority(AuthorityArgs),
+    /// Manage the token's metadata account.
+    #[command(subcommand)]
+    Metadata(MetadataCommand),
+    /// Show the mint's supply, authorities and metadata.
+    Info { mint: Pubkey },
+    /// Check that on-chain names and symbols match the metadata JSON, and
+    /// that the JSON and image resolve.
+    CheckMetadata(check_metadata::CheckMetadataArgs),
+    /// Snapshot the wallets holding a mint, largest first.
+    Holders(holders::HoldersArgs),
+    /// Pay every wallet in a `recipient,amount` CSV, resuming from its
+    /// state file.
+    Airdrop(airdrop::AirdropArgs),
+    /// Manage address lookup tables for v0 transactions.
+    #[command(subcommand)]
+    LookupTable(lookup_table::LookupTableCommand),
+    /// Create every token in a manifest, resuming from its ledger.
+    Batch(batch::BatchArgs),
+    /// Show what `apply` would change to make the chain match a token spec.
+    Plan(spec::SpecArgs),
+    /// Create or update tokens to match a token spec.
+    Apply(spec::SpecArgs),
+    /// Manage the encrypted keystore the server's payer is unlocked from.
+    Keystore {
+        #[arg(long = "path", env = "SOL_TOOLS_KEYSTORE", default_value = "sol-tools.keystore.json")]
+        path: PathBuf,
+        #[command(subcommand)]
+        command: keystore::KeystoreCommand,
+    },
+}
+
+#[derive(Args)]
+struct CreateArgs {
+    #[arg(long)]
+    name: String,
+    #[arg(long)]
+    symbol: String,
+    /// URI of the off-chain metadata JSON.
+    #[arg(long)]
+    uri: String,
+    #[arg(long, default_value_t = 9)]
+    decimals: u8,
+    /// Initial supply in whole tokens, minted to the signer.
+    #[arg(long, default_value = "0")]
+    supply: String,
+    /// Lock the metadata at creation.
+    #[arg(long)]
+    immutable: bool,
+    /// Create the mint without a freeze authority.
+    #[arg(long)]
+    no_freeze_authority: bool,
+}
+
+#[derive(Args)]
+struct AuthorityArgs {
+    mint: Pubkey,
+    #[arg(long, value_enum)]
+    kind: AuthorityKind,
+    /// New authority.
+    #[arg(long, conflicts_with = "revoke", required_unless_present = "revoke")]
+    new: Option<Pubkey>,
+    /// Give the authority up for good. For `metadata` this makes the
+    /// metadata immutable.
+    #[arg(long)]
+    revoke: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AuthorityKind {
+    Mint,
+    Freeze,
+    Metadata,
+}
+
+#[derive(Subcommand)]
+enum MetadataCommand {
+    /// Change the name, symbol or URI, or lock the metadata.
+    Update {
+        mint: Pubkey,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        symbol: Option<String>,
+        #[arg(long)]
+        uri: Option<String>,
+        /// Lock the metadata after this update.
+        #[arg(long)]
+        immutable: bool,
+    },
+    /// Upload metadata for a mint created without it and create its
+    /// metadata account. Needs the mint authority.
+    Attach(attach::AttachArgs),
+}
+
+/// Names the cluster for lockfiles: the canonical moniker, whether the
+/// cluster was given by moniker or by its public RPC URL, or else the URL.
+fn network_key(url: &str) -> String {
+    let host = url.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/');
+    match host {
+        "devnet" | "d" | "api.devnet.solana.com" => "devnet".to_string(),
+        "mainnet" | "mainnet-beta" | "m" | "api.mainnet-beta.solana.com" => "mainnet".to_string(),
+        "api.testnet.solana.com" => "testnet".to_string(),
+        "localhost" | "l" | "localhost:8899" | "127.0.0.1:8899" => "localhost".to_string(),
+        _ => url.to_string(),
+    }
+}
+
+/// Resolves a cluster moniker to an RPC URL and the explorer network.
+fn cluster(url: &str) -> (String, NetworkType) {
+    match url {
+        "devnet" | "d" => (NetworkType::Devnet.rpc_url().to_string(), NetworkType::Devnet),
+        "mainnet" | "mainnet-beta" | "m" => (NetworkType::Mainnet.rpc_url().to_string(), NetworkType::Mainnet),
+        "localhost" | "l" => ("http://127.0.0.1:8899".to_string(), NetworkType::Devnet),
+        url if url.contains("mainnet") => (url.to_string(), NetworkType::Mainnet),
+        url => (url.to_string(), NetworkType::Devnet),
+    }
+}
+
+struct Context {
+    client: RpcClient,
+    rpc_url: String,
+    network: NetworkType,
+    network_key: String,
+    signer: Keypair,
+}
+
+impl Context {
+    fn report(&self, action: &'static str, mint: &Pubkey, signature: String) -> TxReport {
+        TxReport {
+            action,
+            mint: mint.to_string(),
+            explorer_url: self.network.tx_explorer_url().replace("{}", &signature),
+            signature,
+            details: BTreeMap::new(),
+        }
+    }
+
+    async fn amount(&self, mint: &Pubkey, amount: &str) -> Result<u64> {
+        let account = ops::load_mint(&self.client, mint).await?;
+        ops::parse_amount(amount, account.decimals)
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    if let Command::Keystore { path, command } = cli.command {
+        return keystore::run(path, command, cli.json);
+    }
+
+    let solana_config = SolanaCliConfig::load()?;
+    let url = cli
+        .url
+        .or(solana_config.json_rpc_url)
+        .unwrap_or_else(|| "devnet".to_string());
+    let (rpc_url, network) = cluster(&url);
+    let signer = match &cli.keypair {
+        Some(source) => resolve_keypair(source)?,
+        None => default_keypair()?,
+    };
+    let context = Context {
+        client: RpcClient::new_with_commitment(rpc_url.clone(), CommitmentConfig::confirmed()),
+        rpc_url,
+        network,
+        network_key: network_key(&url),
+        signer,
+    };
+    let json = cli.json;
+    let signer = &context.signer;
+    let client = &context.client;
+
+    match cli.command {
+        Command::Create(args) => {
+            let params = CreateTokenParams {
+                description: String::new(),
+                metadata_uri: args.uri,
+                decimals: args.decimals,
+                initial_supply: ops::parse_amount(&args.supply, args.decimals)?,
+                is_mutable: !args.immutable,
+                freeze_authority: !args.no_freeze_authority,
+                rate_limit: None,
+                transfer_fee: None,
+                max_transfer_amount: None,
+                network: context.network.clone(),
+                payer: Some(Arc::new(signer.insecure_clone())),
+                name: args.name,
+                symbol: args.symbol,
+            };
+            let result = create_token_with_rpc(params, &context.rpc_url).await?;
+            emit(&result, json)
+        }
+        Command::Mint { mint, amount, to } => {
+            let recipient = to.unwrap_or_else(|| signer.pubkey());
+            let signature = ops::mint_tokens(client, signer, &mint, &recipient, context.amount(&mint, &amount).await?).await?;
+            let report = context
+                .report("Minted", &mint, signature.to_string())
+                .with("amount", amount)
+                .with("recipient", recipient.to_string());
+            emit(&report, json)
+        }
+        Command::Burn { mint, amount } => {
+            let signature = ops::burn_tokens(client, signer, &mint, context.amount(&mint, &amount).await?).await?;
+            emit(&context.report("Burned", &mint, signature.to_string()).with("amount", amount), json)
+        }
+        Command::Transfer { mint, amount, recipient } => {
+            let signature =
+                ops::transfer_tokens(client, signer, &mint, &recipient, context.amount(&mint, &amount).await?).await?;
+            let report = context
+                .report("Transferred", &mint, signature.to_string())
+                .with("amount", amount)
+                .with("recipient", recipient.to_string());
+            emit(&report, json)
+        }
+        Command::Freeze { mint, holder } => {
+            let signature = ops::freeze_account(client, signer, &mint, &holder).await?;
+            emit(&context.report("Froze", &mint, signature.to_string()).with("holder", holder.to_string()), json)
+        }
+        Command::Thaw { mint, holder } => {
+            let signature = ops::thaw_account(client, signer, &mint, &holder).await?;
+            emit(&context.report("Thawed", &mint, signature.to_string()).with("holder", holder.to_string()), json)
+        }
+        Command::Authority(args) => {
+            let signature = match args.kind {
+                AuthorityKind::Mint | AuthorityKind::Freeze => {
+                    let kind = match args.kind {
+                        AuthorityKind::Mint => MintAuthority::Mint,
+                        _ => MintAuthority::Freeze,
+                    };
+                    ops::set_authority(client, signer, &args.mint, kind, args.new.as_ref()).await?
+                }
+                AuthorityKind::Metadata => {
+                    let update = MetadataUpdate {
+                        new_update_authority: args.new,
+                        make_immutable: args.revoke,
+                        ..MetadataUpdate::default()
+                    };
+                    ops::update_metadata(client, signer, &args.mint, update).await?
+                }
+            };
+            let kind = args.kind.to_possible_value().expect("no skipped variants");
+            let new = args.new.map_or_else(|| "none".to_string(), |key| key.to_string());
+            let report = context
+                .report("Updated authority", &args.mint, signature.to_string())
+                .with("kind", kind.get_name().to_string())
+                .with("new_authority", new);
+            emit(&report, json)
+        }
+        Command::Metadata(MetadataCommand::Update { mint, name, symbol, uri, immutable }) => {
+            if name.is_none() && symbol.is_none() && uri.is_none() && !immutable {
+                return Err(anyhow!("Nothing to update; pass --name, --symbol, --uri or --immutable"));
+            }
+            let update = MetadataUpdate {
+                name,
+                symbol,
+                uri,
+                new_update_authority: None,
+                make_immutable: immutable,
+            };
+            let signature = ops::update_metadata(client, signer, &mint, update).await?;
+            emit(&context.report("Updated metadata", &mint, signature.to_string()), json)
+        }
+        Command::Metadata(MetadataCommand::Attach(args)) => attach::run(&context, args, json).await,
+        Command::Info { mint } => emit(&ops::token_info(client, &mint).await?, json),
+        Command::CheckMetadata(args) => check_metadata::run(&context, args, json).await,
+        Command::Holders(args) => holders::run(&context, args, json).await,
+        Command::Airdrop(args) => airdrop::run(&context, args, json).await,
+        Command::LookupTable(command) => lookup_table::run(&context, command, json).await,
+        Command::Batch(args) => batch::run(&context, args, json).await,
+        Command::Plan(args) => spec::plan(&context, args, json).await,
+        Command::Apply(args) => spec::apply(&context, args, json).await,
+        Command::Keystore { .. } => unreachable!("handled before connecting"),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json = cli.json;
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            emit_error(&e, json);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sol_tools::token::{
+    airdrop::{AirdropReport, DistributionMode, RowStatus},
+    batch::BatchSummary,
+    consistency::ConsistencyReport,
+    holders::HolderSnapshot,
+    lookup_table::LookupTableInfo,
+    ops::{format_amount, MintAuthority, OnchainMetadata, TokenInfo},
+    spec::{AppliedStep, Plan, TokenPlan},
+    TokenCreationResult,
+};
+
+/// A command result printable for people or as JSON.
+pub trait Report: Serialize {
+    fn human(&self) -> String;
+}
+
+pub fn emit<R: Report>(report: &R, json: bool) -> anyhow::Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+    } else {
+        println!("{}", report.human());
+    }
+    Ok(())
+}
+
+pub fn emit_error(error: &anyhow::Error, json: bool) {
+    if json {
+        println!("{}", serde_json::json!({ "error": format!("{:#}", error) }));
+    } else {
+        eprintln!("Error: {:#}", error);
+    }
+}
+
+/// Outcome of a confirmed transaction.
+#[derive(Serialize)]
+pub struct TxReport {
+    pub action: &'static str,
+    pub mint: String,
+    pub signature: String,
+    pub explorer_url: String,
+    #[serde(flatten)]
+    pub details: BTreeMap<&'static str, String>,
+}
+
+impl TxReport {
+    pub fn with(mut self, key: &'static str, value: String) -> Self {
+        self.details.insert(key, value);
+        self
+    }
+}
+
+impl Report for TxReport {
+    fn human(&self) -> String {
+        let mut lines = vec![self.action.to_string(), format!("  Mint:      {}", self.mint)];
+        lines.extend(self.details.iter().map(|(key, value)| format!("  {:<10} {}", format!("{}:", label(key)), value)));
+        lines.push(format!("  Signature: {}", self.signature));
+        lines.push(format!("  Explorer:  {}", self.explorer_url));
+        lines.join("\n")
+    }
+}
+
+impl Report for TokenCreationResult {
+    fn human(&self) -> String {
+        let mut lines = vec!["Created token".to_string(), format!("  Mint:      {}", self.mint)];
+        if self.signatures.len() > 1 {
+            lines.extend(self.signatures.iter().map(|signature| format!("  Signature: {}", signature)));
+        } else {
+            lines.push(format!("  Signature: {}", self.signature));
+        }
+        lines.push(format!("  Explorer:  {}", self.explorer_url));
+        lines.join("\n")
+    }
+}
+
+impl Report for TokenInfo {
+    fn human(&self) -> String {
+        let none = || "none".to_string();
+        let mut lines = vec![
+            format!("Mint:             {}", self.mint),
+            format!("Program:          {}", self.program),
+            format!("Decimals:         {}", self.decimals),
+            format!("Supply:           {}", self.ui_supply),
+            format!("Mint authority:   {}", self.mint_authority.clone().unwrap_or_else(none)),
+            format!("Freeze authority: {}", self.freeze_authority.clone().unwrap_or_else(none)),
+        ];
+        match &self.metadata {
+            Some(metadata) => lines.extend([
+                format!("Name:             {}", metadata.name),
+                format!("Symbol:           {}", metadata.symbol),
+                format!("URI:              {}", metadata.uri),
+                format!("Update authority: {}", metadata.update_authority),
+                format!("Mutable:          {}", metadata.is_mutable),
+            ]),
+            None => lines.push("Metadata:         none".to_string()),
+        }
+        lines.join("\n")
+    }
+}
+
+impl Report for Plan {
+    fn human(&self) -> String {
+        let mut lines = vec![format!("Network: {}", self.network)];
+        let (mut create, mut update, mut conflicted) = (0, 0, 0);
+        for token in &self.tokens {
+            let mint = token.mint.as_deref().unwrap_or_default();
+            if !token.conflicts.is_empty() {
+                conflicted += 1;
+                lines.push(format!("! {} ({})", token.name, mint));
+                lines.extend(token.conflicts.iter().map(|conflict| format!("    {}", conflict)));
+            } else if token.create {
+                create += 1;
+                lines.push(format!("+ {}: create", token.name));
+            } else if token.finish {
+                update += 1;
+                lines.push(format!("~ {} ({}): finish creating (metadata and initial supply)", token.name, mint));
+            } else if token.is_noop() {
+                lines.push(format!("= {} ({}): up to date", token.name, mint));
+            } else {
+                update += 1;
+                lines.push(format!("~ {} ({})", token.name, mint));
+                lines.extend(token_changes(token).into_iter().map(|change| format!("    {}", change)));
+            }
+        }
+        lines.extend(self.untracked.iter().map(|name| format!("? {}: in the lockfile but not in the spec", name)));
+        lines.push(format!("Plan: {} to create, {} to update, {} with conflicts", create, update, conflicted));
+        lines.join("\n")
+    }
+}
+
+fn token_changes(token: &TokenPlan) -> Vec<String> {
+    let Some(current) = &token.current else {
+        return Vec::new();
+    };
+    let metadata = current.metadata.as_ref();
+    let current_field = |field: fn(&OnchainMetadata) -> &String| {
+        metadata.map(field).cloned().unwrap_or_default()
+    };
+    let mut changes = Vec::new();
+    if token.mint_amount > 0 {
+        let amount = format_amount(token.mint_amount, current.decimals);
+        changes.push(format!("mint {} (supply {})", amount, current.ui_supply));
+    }
+    if let Some(name) = &token.metadata.name {
+        changes.push(format!("name {:?} -> {:?}", current_field(|m| &m.name), name));
+    }
+    if let Some(symbol) = &token.metadata.symbol {
+        changes.push(format!("symbol {:?} -> {:?}", current_field(|m| &m.symbol), symbol));
+    }
+    if let Some(uri) = &token.metadata.uri {
+        changes.push(format!("uri {:?} -> {:?}", current_field(|m| &m.uri), uri));
+    }
+    if token.upload_metadata {
+        changes.push(format!("upload metadata and replace uri {:?}", current_field(|m| &m.uri)));
+    }
+    if let Some(authority) = &token.metadata.update_authority {
+        changes.push(format!("update authority {} -> {}", current_field(|m| &m.update_authority), authority));
+    }
+    if token.metadata.make_immutable {
+        changes.push("make metadata immutable".to_string());
+    }
+    for change in &token.authorities {
+        let from = match change.kind {
+            MintAuthority::Mint => &current.mint_authority,
+            MintAuthority::Freeze => &current.freeze_authority,
+        };
+        let none = || "none".to_string();
+        changes.push(format!(
+            "{} authority {} -> {}",
+            change.kind.label(),
+            from.clone().unwrap_or_else(none),
+            change.to.clone().unwrap_or_else(none)
+        ));
+    }
+    changes
+}
+
+impl Report for BatchSummary {
+    fn human(&self) -> String {
+        format!(
+            "Created {}, skipped {}, failed {}; spent {} SOL",
+            self.created,
+            self.skipped,
+            self.failed,
+            format_amount(self.total_cost_lamports, 9)
+        )
+    }
+}
+
+/// What `airdrop --dry-run` would send.
+#[derive(Serialize)]
+pub struct AirdropPreview {
+    pub mint: String,
+    pub mode: DistributionMode,
+    pub recipients: usize,
+    pub already_paid: usize,
+    pub transactions: usize,
+    pub accounts_to_create: usize,
+    /// Whole tokens.
+    pub total: String,
+    /// Lookup table the transactions would load accounts from: an address,
+    /// or `new` for one the run creates.
+    pub lookup_table: Option<String>,
+}
+
+impl Report for AirdropPreview {
+    fn human(&self) -> String {
+        [
+            format!("Would pay {} recipient(s) {} in {} transaction(s)", self.recipients, self.total, self.transactions),
+            format!("  Token accounts to create: {}", self.accounts_to_create),
+            format!("  Already paid:             {}", self.already_paid),
+            format!("  Lookup table:             {}", self.lookup_table.as_deref().unwrap_or("none")),
+        ]
+        .join("\n")
+    }
+}
+
+impl Report for AirdropReport {
+    fn human(&self) -> String {
+        let mut lines = vec![
+            format!("Paid {}, skipped {}, failed {}", self.paid, self.skipped, self.failed),
+            format!("  Transactions:     {}", self.transactions),
+            format!("  Accounts created: {}", self.accounts_created),
+            format!("  Requested:        {}", self.total_requested),
+            format!("  Distributed:      {}", self.total_distributed),
+            format!("  Outstanding:      {}", self.total_outstanding),
+        ];
+        if let Some(table) = &self.lookup_table {
+            lines.push(format!("  Lookup table:     {}", table));
+            if self.lookup_table_created {
+                lines.push(format!("  Reclaim its rent with `lookup-table deactivate {0}`, then `lookup-table close {0}`", table));
+            }
+        }
+        for row in self.rows.iter().filter(|row| row.status == RowStatus::Failed) {
+            lines.push(format!("  ! line {} {} {}: {}", row.line, row.recipient, row.amount, row.error.as_deref().unwrap_or("not paid")));
+        }
+        lines.join("\n")
+    }
+}
+
+#[derive(Serialize)]
+pub struct LookupTableReport {
+    pub action: &'static str,
+    pub table: String,
+    pub explorer_url: String,
+    pub signatures: Vec<String>,
+}
+
+impl Report for LookupTableReport {
+    fn human(&self) -> String {
+        let mut lines = vec![self.action.to_string(), format!("  Table:     {}", self.table)];
+        lines.extend(self.signatures.iter().map(|signature| format!("  Signature: {}", signature)));
+        lines.push(format!("  Explorer:  {}", self.explorer_url));
+        lines.join("\n")
+    }
+}
+
+impl Report for LookupTableInfo {
+    fn human(&self) -> String {
+        let state = match self.deactivation_slot {
+            Some(slot) => format!("deactivated in slot {}", slot),
+            None => "active".to_string(),
+        };
+        let mut lines = vec![
+            format!("Lookup table {}", self.address),
+            format!("  Authority:     {}", self.authority.as_deref().unwrap_or("none (frozen)")),
+            format!("  State:         {}", state),
+            format!("  Last extended: slot {}", self.last_extended_slot),
+            format!("  Addresses:     {}", self.addresses.len()),
+        ];
+        lines.extend(self.addresses.iter().enumerate().map(|(i, address)| format!("    {:>3} {}", i, address)));
+        lines.join("\n")
+    }
+}
+
+#[derive(Serialize)]
+pub struct ApplyReport {
+    pub lockfile: String,
+    pub steps: Vec<AppliedStep>,
+}
+
+impl Report for ApplyReport {
+    fn human(&self) -> String {
+        if self.steps.is_empty() {
+            return "No changes; the chain matches the spec".to_string();
+        }
+        format!("Applied {} change(s); lockfile {}", self.steps.len(), self.lockfile)
+    }
+}
+
+#[derive(Serialize)]
+pub struct KeystoreReport {
+    pub action: &'static str,
+    pub name: String,
+    pub address: String,
+    pub keystore: String,
+}
+
+impl Report for KeystoreReport {
+    fn human(&self) -> String {
+        format!(
+            "{}\n  Name:      {}\n  Address:   {}\n  Keystore:  {}",
+            self.action, self.name, self.address, self.keystore
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct KeystoreList {
+    pub keystore: String,
+    pub entries: Vec<KeystoreListing>,
+}
+
+#[derive(Serialize)]
+pub struct KeystoreListing {
+    pub name: String,
+    pub address: String,
+    pub updated_at: i64,
+}
+
+impl Report for KeystoreList {
+    fn human(&self) -> String {
+        if self.entries.is_empty() {
+            return format!("No entries in {}", self.keystore);
+        }
+        let width = self.entries.iter().map(|entry| entry.name.len()).max().unwrap_or(0);
+        self.entries
+            .iter()
+            .map(|entry| format!("{:<width$}  {}", entry.name, entry.address, width = width))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `new_authority` -> `New authority`.
+fn label(key: &str) -> String {
+    let text = key.replace('_', " ");
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// A holder snapshot; the summary lists the `top` largest holders while
+/// JSON carries all of them.
+pub struct HoldersReport<'a> {
+    pub snapshot: &'a HolderSnapshot,
+    pub top: usize,
+}
+
+impl Serialize for HoldersReport<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.snapshot.serialize(serializer)
+    }
+}
+
+impl Report for HoldersReport<'_> {
+    fn human(&self) -> String {
+        let snapshot = self.snapshot;
+        let taken_at = chrono::DateTime::from_timestamp(snapshot.taken_at, 0)
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default();
+        let mut lines = vec![
+            format!("{} holder(s) of {} at slot {} ({})", snapshot.holders.len(), snapshot.mint, snapshot.slot, taken_at),
+            format!("  Supply:   {}", format_amount(snapshot.supply, snapshot.decimals)),
+        ];
+        if snapshot.programs_excluded {
+            lines.push(format!(
+                "  Excluded: {} program-owned holder(s) with {}",
+                snapshot.excluded_holders,
+                format_amount(snapshot.excluded_amount, snapshot.decimals)
+            ));
+        }
+        for (index, holder) in snapshot.holders.iter().take(self.top).enumerate() {
+            lines.push(format!("  {:>4}. {:<44} {:>24} {:>7.2}%", index + 1, holder.owner, holder.ui_amount, holder.share * 100.0));
+        }
+        if snapshot.holders.len() > self.top {
+            lines.push(format!("  ... and {} more", snapshot.holders.len() - self.top));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Results of `check-metadata`, one per token.
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct MetadataCheckReport(pub Vec<ConsistencyReport>);
+
+impl Report for MetadataCheckReport {
+    fn human(&self) -> String {
+        let mut lines = Vec::new();
+        for report in &self.0 {
+            if report.is_consistent() {
+                lines.push(format!("{}: consistent", report.mint));
+                continue;
+            }
+            lines.push(format!("{}: {} issue(s)", report.mint, report.issues.len()));
+            for issue in &report.issues {
+                lines.push(format!("  {}: {}", issue.code, issue.message));
+            }
+        }
+        let consistent = self.0.iter().filter(|report| report.is_consistent()).count();
+        lines.push(format!("{} of {} token(s) consistent", consistent, self.0.len()));
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,101 @@
+//! `create_spl plan` and `create_spl apply`: manage tokens declared in a spec
+//! file.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use clap::Args;
+use solana_sdk::signer::Signer;
+
+use crate::{
+    output::{emit, ApplyReport},
+    Context,
+};
+use sol_tools::{
+    config::ServerConfig,
+    registry::TokenRegistry,
+    storage,
+    token::spec::{self, ApplyContext, Lockfile, SpecFile},
+};
+
+#[derive(Args)]
+pub struct SpecArgs {
+    /// Token spec, TOML or YAML.
+    spec: PathBuf,
+    /// Lockfile mapping spec names to mints; defaults to the spec path with
+    /// a `.lock` extension.
+    #[arg(long)]
+    lockfile: Option<PathBuf>,
+}
+
+struct Loaded {
+    spec: SpecFile,
+    lockfile: Lockfile,
+    lockfile_path: PathBuf,
+    config: ServerConfig,
+}
+
+/// The server config supplies the IPFS gateway, the registry applied tokens
+/// are recorded in and, for `metadata` documents, the storage backend.
+fn load(args: SpecArgs) -> Result<Loaded> {
+    let lockfile_path = args.lockfile.unwrap_or_else(|| Lockfile::path_for(&args.spec));
+    Ok(Loaded {
+        spec: SpecFile::load(&args.spec)?,
+        lockfile: Lockfile::load(&lockfile_path)?,
+        lockfile_path,
+        config: ServerConfig::load()?,
+    })
+}
+
+pub async fn plan(context: &Context, args: SpecArgs, json: bool) -> Result<()> {
+    let loaded = load(args)?;
+    let plan = spec::plan(
+        &context.client,
+        &loaded.spec,
+        &loaded.lockfile,
+        &context.network_key,
+        &context.signer.pubkey(),
+        &loaded.config.storage.ipfs_gateway,
+    )
+    .await?;
+    emit(&plan, json)
+}
+
+pub async fn apply(context: &Context, args: SpecArgs, json: bool) -> Result<()> {
+    let Loaded { spec, mut lockfile, lockfile_path, config } = load(args)?;
+    let plan = spec::plan(
+        &context.client,
+        &spec,
+        &lockfile,
+        &context.network_key,
+        &context.signer.pubkey(),
+        &config.storage.ipfs_gateway,
+    )
+    .await?;
+    if let Some((token, conflict)) = plan.conflicts().next() {
+        return Err(anyhow!("{}: {}", token, conflict));
+    }
+    let store = if spec.tokens.values().any(|token| token.metadata.is_some()) {
+        Some(storage::from_config(&config)?)
+    } else {
+        None
+    };
+    let registry = TokenRegistry::open(&config.registry.database_path)?;
+    let apply_context = ApplyContext {
+        client: &context.client,
+        rpc_url: &context.rpc_url,
+        network: context.network.clone(),
+        network_key: &context.network_key,
+        signer: Arc::new(context.signer.insecure_clone()),
+        ipfs_gateway: &config.storage.ipfs_gateway,
+        store,
+        registry: &registry,
+    };
+    let steps = spec::apply(&apply_context, &spec, plan, &mut lockfile, &lockfile_path, |step| {
+        if !json {
+            eprintln!("{}: {}", step.token, step.action);
+        }
+    })
+    .await?;
+    emit(&ApplyReport { lockfile: lockfile_path.display().to_string(), steps }, json)
+}
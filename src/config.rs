@@ -0,0 +1,582 @@
+use std::{env, fs, io::ErrorKind, path::Path, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use crate::{
+    token::{
+        signer::{RemoteSigner, TransactionSigner},
+        NetworkType,
+    },
+    utils::keystore::{read_passphrase, Keystore, UnlockedEntry},
+};
+
+pub const DEFAULT_CONFIG_PATH: &str = "sol-tools.toml";
+
+/// Server configuration, read from a TOML file (`SOL_TOOLS_CONFIG`, falling
+/// back to `sol-tools.toml`) and then overridden by environment variables.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Address to bind; defaults to the Leptos `site-addr`.
+    pub bind_address: Option<String>,
+    pub rpc: RpcConfig,
+    pub payer: PayerConfig,
+    pub storage: StorageConfig,
+    pub images: ImageConfig,
+    pub cors: CorsConfig,
+    pub auth: AuthConfig,
+    pub registry: RegistryConfig,
+    pub batch: BatchConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RpcConfig {
+    pub devnet_url: String,
+    pub mainnet_url: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct PayerConfig {
+    /// Keypair file or other source accepted by
+    /// [`crate::utils::keypair::resolve_keypair`] other than `prompt://`.
+    /// Defaults to the Solana CLI keypair.
+    pub keypair_path: Option<String>,
+    /// Encrypted keystore holding the payer; takes precedence over
+    /// `keypair_path` and is unlocked at startup.
+    pub keystore: Option<String>,
+    /// Name of the payer's entry in the keystore.
+    pub keystore_entry: String,
+    /// Signing service holding the payer key; takes precedence over both
+    /// local options.
+    pub remote_signer: Option<RemoteSignerConfig>,
+    #[serde(skip)]
+    unlocked: Option<Arc<UnlockedEntry>>,
+    /// The `keypair_path` keypair, once the server has loaded it.
+    #[serde(skip)]
+    loaded: Option<Arc<Keypair>>,
+}
+
+impl Default for PayerConfig {
+    fn default() -> Self {
+        Self {
+            keypair_path: None,
+            keystore: None,
+            keystore_entry: "payer".to_string(),
+            remote_signer: None,
+            unlocked: None,
+            loaded: None,
+        }
+    }
+}
+
+/// See [`crate::token::signer::RemoteSigner`] for the protocol.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RemoteSignerConfig {
+    pub url: String,
+    /// Address of the key the service signs with.
+    pub pubkey: String,
+    /// Sent as a bearer token when set.
+    pub auth_token: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Where metadata and images are stored.
+    pub backend: StorageBackend,
+    /// Gateway used to build fetchable URLs for `ipfs://` URIs.
+    pub ipfs_gateway: String,
+    /// Gateways tried in order when `ipfs_gateway` cannot serve a document,
+    /// and for `http(s)` URIs pinned to a gateway that stopped answering.
+    pub fallback_gateways: Vec<String>,
+    pub pinata: PinataConfig,
+    pub kubo: KuboConfig,
+    pub irys: IrysConfig,
+    pub s3: S3Config,
+    pub filesystem: FilesystemConfig,
+    pub quota: QuotaConfig,
+    pub gc: GcConfig,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Pinata,
+    Kubo,
+    Irys,
+    S3,
+    Filesystem,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PinataConfig {
+    /// Scoped API JWT; preferred over the key pair when set.
+    pub jwt: String,
+    pub api_key: String,
+    pub secret_api_key: String,
+}
+
+/// Processing applied to uploaded token logos.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ImageConfig {
+    /// Largest upload accepted before decoding.
+    pub max_input_bytes: usize,
+    /// Largest width or height accepted when decoding.
+    pub max_dimension: u32,
+    /// Square sizes to produce; the largest becomes the metadata `image`.
+    pub sizes: Vec<u32>,
+    pub format: ImageFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Webp,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct KuboConfig {
+    /// Base URL of the Kubo RPC API.
+    pub api_url: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct IrysConfig {
+    pub node_url: String,
+    pub gateway_url: String,
+    /// Solana keypair that signs and pays for uploads; defaults to the payer.
+    pub keypair_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct S3Config {
+    /// Service endpoint, e.g. `https://s3.us-east-1.amazonaws.com` or a MinIO URL.
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Key prefix for stored objects.
+    pub prefix: String,
+    /// Public base URL for stored objects; defaults to the path-style bucket URL.
+    pub public_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct FilesystemConfig {
+    pub directory: String,
+    /// URL the directory is reachable at; the server mounts it under `/storage`.
+    pub public_url: String,
+}
+
+/// Per-wallet upload limits, applied to everything pinned on a user's behalf.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct QuotaConfig {
+    pub max_upload_bytes: usize,
+    pub max_uploads_per_window: usize,
+    pub max_bytes_per_window: usize,
+    pub window_secs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Origins allowed to call the REST API; empty disables cross-origin access.
+    pub allowed_origins: Vec<String>,
+    pub max_age_secs: usize,
+}
+
+/// Periodic unpinning of metadata no recorded token points to.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct GcConfig {
+    /// Off by default: with an empty or replaced registry every pin looks
+    /// orphaned.
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Pins younger than this are kept, so in-flight creations are safe.
+    pub min_age_secs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    pub session_ttl_secs: u64,
+    /// Wallets allowed to manage storage pins.
+    pub admin_addresses: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct RegistryConfig {
+    /// SQLite database recording every token created through the server.
+    pub database_path: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BatchConfig {
+    /// Directory for batch ledgers, one per wallet and batch name.
+    pub ledger_directory: String,
+    /// Largest manifest the API accepts.
+    pub max_tokens: usize,
+    /// Creation transactions in flight at once per batch.
+    pub concurrency: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            ledger_directory: "batches".to_string(),
+            max_tokens: 100,
+            concurrency: crate::token::batch::DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            devnet_url: NetworkType::Devnet.rpc_url().to_string(),
+            mainnet_url: NetworkType::Mainnet.rpc_url().to_string(),
+        }
+    }
+}
+
+impl StorageConfig {
+    /// `ipfs_gateway` followed by the fallbacks, the order documents are
+    /// read in.
+    pub fn ipfs_gateways(&self) -> Vec<String> {
+        let mut gateways = vec![self.ipfs_gateway.clone()];
+        for gateway in &self.fallback_gateways {
+            if !gateways.contains(gateway) {
+                gateways.push(gateway.clone());
+            }
+        }
+        gateways
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::default(),
+            ipfs_gateway: "https://gateway.pinata.cloud".to_string(),
+            fallback_gateways: vec!["https://ipfs.io".to_string(), "https://dweb.link".to_string()],
+            pinata: PinataConfig::default(),
+            kubo: KuboConfig::default(),
+            irys: IrysConfig::default(),
+            s3: S3Config::default(),
+            filesystem: FilesystemConfig::default(),
+            quota: QuotaConfig::default(),
+            gc: GcConfig::default(),
+        }
+    }
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            max_upload_bytes: 5 * 1024 * 1024,
+            max_uploads_per_window: 30,
+            max_bytes_per_window: 50 * 1024 * 1024,
+            window_secs: 60 * 60,
+        }
+    }
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 24 * 60 * 60,
+            min_age_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: 10 * 1024 * 1024,
+            max_dimension: 8192,
+            sizes: vec![512, 256, 128],
+            format: ImageFormat::default(),
+        }
+    }
+}
+
+impl Default for KuboConfig {
+    fn default() -> Self {
+        Self { api_url: "http://127.0.0.1:5001".to_string() }
+    }
+}
+
+impl Default for IrysConfig {
+    fn default() -> Self {
+        Self {
+            node_url: "https://node1.irys.xyz".to_string(),
+            gateway_url: "https://gateway.irys.xyz".to_string(),
+            keypair_path: None,
+        }
+    }
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            prefix: String::new(),
+            public_url: None,
+        }
+    }
+}
+
+impl Default for FilesystemConfig {
+    fn default() -> Self {
+        Self {
+            directory: "storage".to_string(),
+            public_url: "http://127.0.0.1:3000/storage".to_string(),
+        }
+    }
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            max_age_secs: 3600,
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            session_ttl_secs: 24 * 60 * 60,
+            admin_addresses: Vec::new(),
+        }
+    }
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self { database_path: "sol-tools.db".to_string() }
+    }
+}
+
+impl ServerConfig {
+    pub fn load() -> Result<Self> {
+        let explicit = env::var("SOL_TOOLS_CONFIG").ok();
+        let path = explicit.as_deref().unwrap_or(DEFAULT_CONFIG_PATH);
+
+        let mut config = match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse config file {}", path))?,
+            Err(e) if e.kind() == ErrorKind::NotFound && explicit.is_none() => Self::default(),
+            Err(e) => return Err(anyhow!("Failed to read config file {}: {}", path, e)),
+        };
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        if let Ok(value) = env::var("SOL_TOOLS_BIND_ADDRESS") {
+            self.bind_address = Some(value);
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_DEVNET_RPC_URL") {
+            self.rpc.devnet_url = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_MAINNET_RPC_URL") {
+            self.rpc.mainnet_url = value;
+        }
+        if let Ok(value) = env::var("SOLANA_KEYPAIR_PATH") {
+            self.payer.keypair_path = Some(value);
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_KEYSTORE") {
+            self.payer.keystore = Some(value);
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_KEYSTORE_ENTRY") {
+            self.payer.keystore_entry = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_REMOTE_SIGNER_URL") {
+            self.payer.remote_signer.get_or_insert_with(RemoteSignerConfig::default).url = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_REMOTE_SIGNER_PUBKEY") {
+            self.payer.remote_signer.get_or_insert_with(RemoteSignerConfig::default).pubkey = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_REMOTE_SIGNER_TOKEN") {
+            self.payer.remote_signer.get_or_insert_with(RemoteSignerConfig::default).auth_token = Some(value);
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_STORAGE_BACKEND") {
+            self.storage.backend = toml::Value::String(value.clone()).try_into().map_err(|_| {
+                anyhow!("Unknown SOL_TOOLS_STORAGE_BACKEND {:?}; expected pinata, kubo, irys, s3 or filesystem", value)
+            })?;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_IPFS_FALLBACK_GATEWAYS") {
+            self.storage.fallback_gateways = split_list(&value);
+        }
+        if let Ok(value) = env::var("PINATA_JWT") {
+            self.storage.pinata.jwt = value;
+        }
+        if let Ok(value) = env::var("PINATA_API_KEY") {
+            self.storage.pinata.api_key = value;
+        }
+        if let Ok(value) = env::var("PINATA_SECRET_API_KEY") {
+            self.storage.pinata.secret_api_key = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_S3_ACCESS_KEY_ID") {
+            self.storage.s3.access_key_id = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_S3_SECRET_ACCESS_KEY") {
+            self.storage.s3.secret_access_key = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_DATABASE_PATH") {
+            self.registry.database_path = value;
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_CORS_ORIGINS") {
+            self.cors.allowed_origins = split_list(&value);
+        }
+        if let Ok(value) = env::var("SOL_TOOLS_ADMIN_ADDRESSES") {
+            self.auth.admin_addresses = split_list(&value);
+        }
+        Ok(())
+    }
+
+    pub fn rpc_url(&self, network: &NetworkType) -> &str {
+        match network {
+            NetworkType::Devnet => &self.rpc.devnet_url,
+            NetworkType::Mainnet => &self.rpc.mainnet_url,
+        }
+    }
+
+    /// Gets the server's payer ready at startup: unlocks the keystore, if one
+    /// is configured, with the passphrase from `SOL_TOOLS_KEYSTORE_PASSPHRASE`
+    /// or stdin, or else loads the keypair once. Fails for `prompt://`, which
+    /// the server cannot answer.
+    pub fn prepare_payer(&mut self) -> Result<()> {
+        if self.payer.remote_signer.is_some() {
+            return Ok(());
+        }
+        let Some(path) = self.payer.keystore.as_deref() else {
+            if self.payer.keypair_path.as_deref().is_some_and(|path| path.starts_with("prompt:")) {
+                return Err(anyhow!(
+                    "payer.keypair_path cannot be prompt:// for the server; use a keypair file, env://NAME or a keystore"
+                ));
+            }
+            let payer = self.load_payer()?;
+            println!("Loaded payer {}", Signer::pubkey(&payer));
+            self.payer.loaded = Some(Arc::new(payer));
+            return Ok(());
+        };
+        let keystore = Keystore::open(Path::new(path))?;
+        let entry = keystore
+            .entry(&self.payer.keystore_entry)
+            .with_context(|| format!("Payer not found in keystore {}", path))?;
+        let passphrase = read_passphrase(
+            "SOL_TOOLS_KEYSTORE_PASSPHRASE",
+            &format!("Passphrase for {} in {}: ", self.payer.keystore_entry, path),
+        )?;
+        let unlocked = entry.unlock(&passphrase)?;
+        println!("Unlocked payer {} from keystore {}", unlocked.address(), path);
+        self.payer.unlocked = Some(Arc::new(unlocked));
+        Ok(())
+    }
+
+    /// The signer for server-side transactions: the remote signer, the
+    /// unlocked keystore entry or the payer keypair, in that order.
+    pub fn payer_signer(&self) -> Result<Arc<dyn TransactionSigner>> {
+        if let Some(remote) = &self.payer.remote_signer {
+            let pubkey = remote
+                .pubkey
+                .parse()
+                .map_err(|e| anyhow!("Invalid remote signer pubkey {:?}: {}", remote.pubkey, e))?;
+            return Ok(Arc::new(RemoteSigner::new(&remote.url, pubkey, remote.auth_token.clone())));
+        }
+        if let Some(unlocked) = &self.payer.unlocked {
+            return Ok(unlocked.clone());
+        }
+        if let Some(loaded) = &self.payer.loaded {
+            return Ok(loaded.clone());
+        }
+        Ok(Arc::new(self.load_payer()?))
+    }
+
+    /// Loads the payer keypair for one use; drop it as soon as the
+    /// transaction is signed so the secret is wiped. Fails when the payer is
+    /// held by a remote signer.
+    pub fn load_payer(&self) -> Result<Keypair> {
+        if self.payer.remote_signer.is_some() {
+            return Err(anyhow!("The payer key is held by the remote signer"));
+        }
+        if let Some(unlocked) = &self.payer.unlocked {
+            return unlocked.keypair();
+        }
+        if let Some(path) = &self.payer.keystore {
+            return Err(anyhow!("Payer keystore {} is locked", path));
+        }
+        if let Some(loaded) = &self.payer.loaded {
+            return Ok(loaded.insecure_clone());
+        }
+        match self.payer.keypair_path.as_deref() {
+            Some(path) => crate::utils::load_keypair_from_path(path),
+            None => crate::utils::keypair::default_keypair()
+                .context("No payer keypair configured and no Solana CLI keypair found"),
+        }
+    }
+
+    pub fn session_ttl(&self) -> Duration {
+        Duration::from_secs(self.auth.session_ttl_secs)
+    }
+
+    pub fn is_admin(&self, address: &str) -> bool {
+        self.auth.admin_addresses.iter().any(|admin| admin == address)
+    }
+}
+
+/// Splits a comma-separated environment value, dropping empty entries.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_an_unknown_storage_backend() {
+        env::set_var("SOL_TOOLS_STORAGE_BACKEND", "s3");
+        let mut config = ServerConfig::default();
+        config.apply_env().unwrap();
+        assert_eq!(config.storage.backend, StorageBackend::S3);
+
+        env::set_var("SOL_TOOLS_STORAGE_BACKEND", "pinnata");
+        let err = ServerConfig::default().apply_env().unwrap_err();
+        env::remove_var("SOL_TOOLS_STORAGE_BACKEND");
+        assert!(err.to_string().contains("\"pinnata\""), "{}", err);
+    }
+}
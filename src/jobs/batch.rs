@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::random_token,
+    config::ServerConfig,
+    registry::TokenRegistry,
+    token::{
+        batch::{run_batch, BatchEntry, BatchSummary, EntryOutcome, EntryStatus, Ledger, Manifest},
+        NetworkType, TokenCreationResult,
+    },
+};
+
+/// Batch creation submitted through the API. Resubmitting a `name` resumes
+/// that batch: entries it already created are skipped.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BatchRequest {
+    pub name: String,
+    pub network: NetworkType,
+    pub tokens: Vec<BatchEntry>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchStatus {
+    pub id: String,
+    pub name: String,
+    pub total: usize,
+    pub finished: bool,
+    /// Outcomes so far, in completion order until the batch finishes.
+    pub summary: BatchSummary,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+struct BatchRun {
+    owner: String,
+    status: BatchStatus,
+}
+
+/// Batches running or finished since startup, each on its own task.
+pub struct BatchRuns {
+    runs: Mutex<HashMap<String, BatchRun>>,
+    registry: Arc<TokenRegistry>,
+}
+
+impl BatchRuns {
+    pub fn new(registry: Arc<TokenRegistry>) -> Self {
+        Self { runs: Mutex::new(HashMap::new()), registry }
+    }
+
+    /// Validates `request` and starts it on behalf of wallet `owner`. Fails
+    /// while a batch of the same name is still running for that wallet.
+    pub fn submit(self: Arc<Self>, owner: &str, request: BatchRequest, config: Arc<ServerConfig>) -> Result<BatchStatus> {
+        if request.tokens.len() > config.batch.max_tokens {
+            return Err(anyhow!("A batch may hold at most {} tokens", config.batch.max_tokens));
+        }
+        if request.name.is_empty() || !request.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(anyhow!("Batch names may only contain letters, digits, '-' and '_'"));
+        }
+        let manifest = Manifest { tokens: request.tokens };
+        manifest.validate(&request.network)?;
+
+        let now = Utc::now().timestamp();
+        let status = BatchStatus {
+            id: random_token(16),
+            name: request.name,
+            total: manifest.tokens.len(),
+            finished: false,
+            summary: BatchSummary::default(),
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        {
+            let mut runs = self.runs.lock().unwrap();
+            let running = runs
+                .values()
+                .any(|run| run.owner == owner && run.status.name == status.name && !run.status.finished);
+            if running {
+                return Err(anyhow!("Batch {} is already running", status.name));
+            }
+            runs.insert(status.id.clone(), BatchRun { owner: owner.to_string(), status: status.clone() });
+        }
+
+        let id = status.id.clone();
+        let owner = owner.to_string();
+        let ledger_path = PathBuf::from(&config.batch.ledger_directory).join(format!("{}-{}.jsonl", owner, status.name));
+        actix_web::rt::spawn(async move {
+            let outcome = self.execute(&id, &owner, &manifest, request.network, &ledger_path, &config).await;
+            self.update(&id, |status| {
+                match outcome {
+                    Ok(summary) => status.summary = summary,
+                    Err(e) => status.error = Some(e.to_string()),
+                }
+                status.finished = true;
+            });
+        });
+        Ok(status)
+    }
+
+    /// Current status of batch `id`, if it exists and belongs to `owner`.
+    pub fn status(&self, id: &str, owner: &str) -> Option<BatchStatus> {
+        let runs = self.runs.lock().unwrap();
+        runs.get(id).filter(|run| run.owner == owner).map(|run| run.status.clone())
+    }
+
+    async fn execute(
+        &self,
+        id: &str,
+        owner: &str,
+        manifest: &Manifest,
+        network: NetworkType,
+        ledger_path: &Path,
+        config: &ServerConfig,
+    ) -> Result<BatchSummary> {
+        std::fs::create_dir_all(&config.batch.ledger_directory)?;
+        let ledger = Ledger::open(ledger_path, network.key())?;
+        let payer = config.payer_signer()?;
+        let rpc_url = config.rpc_url(&network).to_string();
+        let summary = run_batch(manifest, &ledger, &rpc_url, &network, payer, config.batch.concurrency, |entry, outcome| {
+            if outcome.status == EntryStatus::Created {
+                self.record(entry, outcome, owner, &network);
+            }
+            self.update(id, |status| status.summary.push(outcome.clone()));
+        })
+        .await;
+        Ok(summary)
+    }
+
+    fn record(&self, entry: &BatchEntry, outcome: &EntryOutcome, owner: &str, network: &NetworkType) {
+        let (Some(mint), Some(signature), Ok(params)) = (&outcome.mint, &outcome.signature, entry.params(network)) else {
+            return;
+        };
+        let result = TokenCreationResult {
+            status: "Created".to_string(),
+            mint: mint.clone(),
+            explorer_url: network.explorer_url().replace("{}", mint),
+            signature: signature.clone(),
+            signatures: outcome.signatures.clone(),
+        };
+        if let Err(e) = self.registry.record_creation(params, owner, &result) {
+            eprintln!("Error recording token {}: {}", mint, e);
+        }
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut BatchStatus)) {
+        let mut runs = self.runs.lock().unwrap();
+        if let Some(run) = runs.get_mut(id) {
+            apply(&mut run.status);
+            run.status.updated_at = Utc::now().timestamp();
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use leptos::{server_fn::codec::Json, *};
+use serde::{Deserialize, Serialize};
+
+use crate::token::{CreateTokenParams, CreationStage, TokenCreationResult};
+use crate::metadata::{MetadataError, OffchainMetadata};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use batch::{BatchRequest, BatchRuns, BatchStatus};
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::JobQueue;
+
+/// LocalStorage key holding the job the create page is following, so a
+/// reload can pick it back up.
+#[cfg(target_arch = "wasm32")]
+const ACTIVE_JOB_KEY: &str = "sol_tools_active_job";
+#[cfg(target_arch = "wasm32")]
+const POLL_INTERVAL_MS: u32 = 2_000;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum JobStage {
+    Queued,
+    UploadingMetadata,
+    Simulating,
+    Sending,
+    Confirming,
+    Completed,
+    Failed,
+}
+
+impl JobStage {
+    /// Stages a successful job passes through, in order.
+    pub const PIPELINE: [JobStage; 5] = [
+        JobStage::Queued,
+        JobStage::UploadingMetadata,
+        JobStage::Simulating,
+        JobStage::Sending,
+        JobStage::Confirming,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStage::Queued => "Queued",
+            JobStage::UploadingMetadata => "Uploading metadata",
+            JobStage::Simulating => "Simulating transaction",
+            JobStage::Sending => "Sending transaction",
+            JobStage::Confirming => "Waiting for confirmation",
+            JobStage::Completed => "Completed",
+            JobStage::Failed => "Failed",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStage::Completed | JobStage::Failed)
+    }
+}
+
+impl From<CreationStage> for JobStage {
+    fn from(stage: CreationStage) -> Self {
+        match stage {
+            CreationStage::Simulating => JobStage::Simulating,
+            CreationStage::Sending => JobStage::Sending,
+            CreationStage::Confirming => JobStage::Confirming,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub stage: JobStage,
+    pub result: Option<TokenCreationResult>,
+    pub error: Option<String>,
+    /// Unix timestamps (seconds).
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Token creation submitted to the job queue. The job pins `metadata`, so
+/// `params.metadata_uri` is ignored.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TokenJobRequest {
+    pub params: CreateTokenParams,
+    pub metadata: OffchainMetadata,
+}
+
+impl TokenJobRequest {
+    pub fn validate(&self) -> Result<(), MetadataError> {
+        self.params.validate()?;
+        self.metadata.validate()?;
+        self.metadata.check_matches(&self.params.name, &self.params.symbol)
+    }
+}
+
+#[server(name = SubmitTokenJob, prefix = "/api", input = Json)]
+pub async fn submit_token_job(
+    params: CreateTokenParams,
+    metadata: OffchainMetadata,
+) -> Result<JobStatus, ServerFnError> {
+    use crate::{auth::require_session, config::ServerConfig};
+    use actix_web::web;
+
+    let session = require_session().await?;
+    let queue = leptos_actix::extract::<web::Data<JobQueue>>().await?;
+    let config = leptos_actix::extract::<web::Data<ServerConfig>>().await?;
+
+    let request = TokenJobRequest { params, metadata };
+    request.validate().map_err(ServerFnError::new)?;
+    Ok(queue.into_inner().submit(
+        &session.0.address,
+        request,
+        config.into_inner(),
+    ))
+}
+
+#[server(TokenJobStatus, "/api")]
+pub async fn token_job_status(id: String) -> Result<JobStatus, ServerFnError> {
+    use crate::auth::require_session;
+    use actix_web::web;
+
+    let session = require_session().await?;
+    let queue = leptos_actix::extract::<web::Data<JobQueue>>().await?;
+    queue
+        .status(&id, &session.0.address)
+        .ok_or_else(|| ServerFnError::new(format!("Unknown job {}", id)))
+}
+
+/// Streams updates for job `id` from the SSE endpoint. `on_update` gets the
+/// current status followed by every change until the job finishes;
+/// `on_closed` runs if the stream is refused or drops for good, so the
+/// caller can fall back to polling.
+#[cfg(target_arch = "wasm32")]
+pub fn watch_job(
+    id: &str,
+    on_update: impl Fn(JobStatus) + 'static,
+    on_closed: impl Fn() + 'static,
+) -> Result<(), String> {
+    use wasm_bindgen::{closure::Closure, JsCast};
+    use web_sys::{EventSource, MessageEvent};
+
+    let source = EventSource::new(&format!("/v1/jobs/{}/events", id))
+        .map_err(|e| format!("Failed to open job stream: {:?}", e))?;
+
+    let handle = source.clone();
+    let on_message = Closure::<dyn Fn(MessageEvent)>::new(move |event: MessageEvent| {
+        let Some(data) = event.data().as_string() else {
+            return;
+        };
+        match serde_json::from_str::<JobStatus>(&data) {
+            Ok(status) => {
+                if status.stage.is_finished() {
+                    handle.close();
+                }
+                on_update(status);
+            }
+            Err(e) => log::warn!("Ignoring malformed job event: {}", e),
+        }
+    });
+    source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+
+    let handle = source.clone();
+    let on_error = Closure::<dyn Fn()>::new(move || {
+        // The browser retries dropped streams by itself; only give up once
+        // it has marked the source closed.
+        if handle.ready_state() == EventSource::CLOSED {
+            on_closed();
+        }
+    });
+    source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+    on_error.forget();
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn watch_job(
+    _id: &str,
+    _on_update: impl Fn(JobStatus) + 'static,
+    _on_closed: impl Fn() + 'static,
+) -> Result<(), String> {
+    Err("Job streaming not supported in server environment".to_string())
+}
+
+/// Polls the status endpoint until job `id` finishes, passing each status
+/// to `on_update`.
+#[cfg(target_arch = "wasm32")]
+pub async fn poll_job(id: String, on_update: impl Fn(JobStatus)) -> Result<(), String> {
+    use gloo::timers::future::TimeoutFuture;
+
+    loop {
+        TimeoutFuture::new(POLL_INTERVAL_MS).await;
+        let status = token_job_status(id.clone()).await.map_err(|e| e.to_string())?;
+        let finished = status.stage.is_finished();
+        on_update(status);
+        if finished {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn poll_job(_id: String, _on_update: impl Fn(JobStatus)) -> Result<(), String> {
+    Err("Job polling not supported in server environment".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remember_job(id: &str) {
+    use gloo::storage::{LocalStorage, Storage};
+    let _ = LocalStorage::set(ACTIVE_JOB_KEY, id);
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remembered_job() -> Option<String> {
+    use gloo::storage::{LocalStorage, Storage};
+    LocalStorage::get(ACTIVE_JOB_KEY).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn forget_job() {
+    use gloo::storage::{LocalStorage, Storage};
+    LocalStorage::delete(ACTIVE_JOB_KEY);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remember_job(_id: &str) {}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remembered_job() -> Option<String> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn forget_job() {}
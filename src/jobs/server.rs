@@ -0,0 +1,150 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Result;
+use chrono::Utc;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tokio::sync::broadcast;
+
+use super::{JobStage, JobStatus, TokenJobRequest};
+use crate::{
+    auth::random_token,
+    config::ServerConfig,
+    registry::TokenRegistry,
+    storage::{MetadataStore, UploadQuota},
+    token::{create_token_reporting_mint, TokenCreationResult},
+};
+
+/// How long a finished job stays queryable before it is dropped.
+const JOB_RETENTION: Duration = Duration::from_secs(60 * 60);
+const EVENT_BUFFER: usize = 16;
+
+struct JobEntry {
+    owner: String,
+    status: JobStatus,
+    events: broadcast::Sender<JobStatus>,
+}
+
+/// In-memory token creation jobs. Each job runs on its own task and
+/// publishes every status change to its subscribers.
+pub struct JobQueue {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    registry: Arc<TokenRegistry>,
+    store: Arc<dyn MetadataStore>,
+    quota: Arc<UploadQuota>,
+}
+
+impl JobQueue {
+    pub fn new(registry: Arc<TokenRegistry>, store: Arc<dyn MetadataStore>, quota: Arc<UploadQuota>) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            registry,
+            store,
+            quota,
+        }
+    }
+
+    /// Queues `request` on behalf of wallet `owner` and starts it in the
+    /// background. Returns the initial status carrying the job id.
+    pub fn submit(self: Arc<Self>, owner: &str, request: TokenJobRequest, config: Arc<ServerConfig>) -> JobStatus {
+        let now = Utc::now().timestamp();
+        let status = JobStatus {
+            id: random_token(16),
+            stage: JobStage::Queued,
+            result: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            let cutoff = now - JOB_RETENTION.as_secs() as i64;
+            jobs.retain(|_, job| !job.status.stage.is_finished() || job.status.updated_at > cutoff);
+            let (events, _) = broadcast::channel(EVENT_BUFFER);
+            jobs.insert(status.id.clone(), JobEntry {
+                owner: owner.to_string(),
+                status: status.clone(),
+                events,
+            });
+        }
+
+        let id = status.id.clone();
+        let owner = owner.to_string();
+        actix_web::rt::spawn(async move {
+            let outcome = self.execute(&id, &owner, request, &config).await;
+            self.update(&id, |status| match outcome {
+                Ok(result) => {
+                    status.stage = JobStage::Completed;
+                    status.result = Some(result);
+                }
+                Err(e) => {
+                    status.stage = JobStage::Failed;
+                    status.error = Some(e.to_string());
+                }
+            });
+        });
+
+        status
+    }
+
+    /// Current status of job `id`, if it exists and belongs to `owner`.
+    pub fn status(&self, id: &str, owner: &str) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(id).filter(|job| job.owner == owner).map(|job| job.status.clone())
+    }
+
+    /// Current status of job `id` plus a receiver for every later change.
+    pub fn subscribe(&self, id: &str, owner: &str) -> Option<(JobStatus, broadcast::Receiver<JobStatus>)> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(id)
+            .filter(|job| job.owner == owner)
+            .map(|job| (job.status.clone(), job.events.subscribe()))
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut JobStatus)) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(job) = jobs.get_mut(id) {
+            apply(&mut job.status);
+            job.status.updated_at = Utc::now().timestamp();
+            // No subscribers is fine; pollers read the stored status.
+            let _ = job.events.send(job.status.clone());
+        }
+    }
+
+    fn set_stage(&self, id: &str, stage: JobStage) {
+        self.update(id, |status| status.stage = stage);
+    }
+
+    async fn execute(
+        &self,
+        id: &str,
+        owner: &str,
+        request: TokenJobRequest,
+        config: &ServerConfig,
+    ) -> Result<TokenCreationResult> {
+        let TokenJobRequest { mut params, metadata } = request;
+
+        self.set_stage(id, JobStage::UploadingMetadata);
+        let metadata = serde_json::to_vec(&metadata)?;
+        self.quota.charge(owner, metadata.len())?;
+        params.metadata_uri = self.store.put("metadata.json", "application/json", metadata).await?.uri;
+        let recorded = params.clone();
+        params.payer = Some(config.payer_signer()?);
+
+        let rpc_url = config.rpc_url(&params.network).to_string();
+        // Recorded as soon as the mint lands, so the metadata stays referenced
+        // even if a later transaction fails. The mint exists on chain by then,
+        // so a registry failure must not fail the job.
+        let record_mint = |mint: &Pubkey, signature: &Signature| {
+            if let Err(e) = self.registry.record_mint(recorded, owner, &mint.to_string(), &signature.to_string()) {
+                eprintln!("Error recording token {}: {}", mint, e);
+            }
+            Ok(())
+        };
+        create_token_reporting_mint(params, &rpc_url, |stage| self.set_stage(id, stage.into()), record_mint).await
+    }
+}
@@ -1,8 +1,19 @@
 pub mod app;
+pub mod auth;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config;
+pub mod jobs;
+pub mod metadata;
+pub mod registry;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod storage;
 pub mod token;
+pub mod upload;
 pub mod utils;
 pub mod wallet;
 pub mod routes;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod testing;
 
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen::prelude::wasm_bindgen(start)]
@@ -1,20 +1,106 @@
+use actix_cors::Cors;
 use actix_files::Files;
 use actix_web::*;
 use leptos::*;
 use leptos_actix::{generate_route_list, LeptosRoutes};
 use sol_tools::app::*;
+use sol_tools::auth::AuthStore;
+use sol_tools::config::{CorsConfig, ServerConfig, StorageBackend};
+use sol_tools::jobs::{BatchRuns, JobQueue};
+use sol_tools::registry::TokenRegistry;
+use sol_tools::storage::{self, MetadataStore, UploadQuota};
+use sol_tools::token::holders::HolderCache;
+use sol_tools::routes::{
+    batch::{batch_status, batch_summary_csv, submit_batch},
+    consistency::{metadata_check_route, registry_metadata_check_route},
+    contract::create_token_route,
+    holders::{holders_csv_route, holders_route},
+    jobs::{job_events, job_status, submit_job},
+    metadata::{upload_image, upload_metadata},
+    registry::{get_token_route, list_tokens_route},
+    risk::risk_route,
+};
+
+fn cors(config: &CorsConfig) -> Cors {
+    config
+        .allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(vec!["GET", "POST"])
+        .allowed_header(http::header::CONTENT_TYPE)
+        .supports_credentials()
+        .max_age(config.max_age_secs)
+}
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     let conf = get_configuration(None).await.unwrap();
-    let addr = conf.leptos_options.site_addr;
+    let mut server_config = ServerConfig::load().expect("Failed to load server configuration");
+    server_config.prepare_payer().expect("Failed to load payer");
+    let addr = server_config
+        .bind_address
+        .clone()
+        .unwrap_or_else(|| conf.leptos_options.site_addr.to_string());
     let routes = generate_route_list(App);
+    let auth_store = web::Data::new(AuthStore::new(server_config.session_ttl()));
+    let registry = web::Data::new(
+        TokenRegistry::open(&server_config.registry.database_path).expect("Failed to open token registry"),
+    );
+    let store = storage::from_config(&server_config).expect("Failed to set up metadata storage");
+    let quota = web::Data::new(UploadQuota::new(server_config.storage.quota.clone()));
+    let job_queue = web::Data::new(JobQueue::new(
+        registry.clone().into_inner(),
+        store.clone(),
+        quota.clone().into_inner(),
+    ));
+    let batch_runs = web::Data::new(BatchRuns::new(registry.clone().into_inner()));
+    let holders = web::Data::new(HolderCache::new());
+    storage::spawn_gc(store.clone(), registry.clone().into_inner(), server_config.storage.gc.clone());
+    let store: web::Data<dyn MetadataStore> = web::Data::from(store);
+    let server_config = web::Data::new(server_config);
 
     HttpServer::new(move || {
         let leptos_options = &conf.leptos_options;
         let site_root = &leptos_options.site_root;
+        let cors_config = &server_config.cors;
 
         App::new()
+            .app_data(auth_store.clone())
+            .app_data(server_config.clone())
+            .app_data(job_queue.clone())
+            .app_data(batch_runs.clone())
+            .app_data(registry.clone())
+            .app_data(store.clone())
+            .app_data(quota.clone())
+            .app_data(holders.clone())
+            .service(
+                web::scope("/v1")
+                    .wrap(middleware::Condition::new(
+                        !cors_config.allowed_origins.is_empty(),
+                        cors(cors_config),
+                    ))
+                    .service(create_token_route)
+                    .service(upload_metadata)
+                    .service(upload_image)
+                    .service(submit_job)
+                    .service(job_status)
+                    .service(job_events)
+                    .service(submit_batch)
+                    .service(batch_status)
+                    .service(batch_summary_csv)
+                    .service(list_tokens_route)
+                    .service(holders_csv_route)
+                    .service(holders_route)
+                    .service(risk_route)
+                    .service(metadata_check_route)
+                    .service(registry_metadata_check_route)
+                    .service(get_token_route),
+            )
+            .configure(|cfg| {
+                if server_config.storage.backend == StorageBackend::Filesystem {
+                    cfg.service(Files::new("/storage", &server_config.storage.filesystem.directory));
+                }
+            })
             .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
             .leptos_routes(leptos_options.clone(), routes.clone(), App)
             .service(Files::new("/", site_root))
@@ -0,0 +1,162 @@
+//! Reads metadata documents and images through a list of gateways, moving
+//! on to the next one when a gateway fails.
+//!
+//! URIs come from on-chain data anyone can write, so a URL that is not on a
+//! configured gateway must resolve to public addresses only, and the
+//! connection is pinned to the addresses checked. Redirects are followed by
+//! hand so every hop gets the same check, and bodies are cut off at
+//! [`MAX_FETCH_BYTES`].
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use reqwest::{
+    header::{CONTENT_TYPE, LOCATION},
+    redirect::Policy,
+    Url,
+};
+
+use super::gateway_urls;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_REDIRECTS: usize = 5;
+/// The default upload size limit; nothing this tool stores is larger.
+pub const MAX_FETCH_BYTES: usize = 5 * 1024 * 1024;
+
+/// A document read by [`fetch_with_fallback`].
+pub struct Fetched {
+    /// URL that answered.
+    pub url: String,
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+    /// URLs tried before `url`, each with why it failed.
+    pub failures: Vec<(String, String)>,
+}
+
+/// Reads `uri` from the first of its [`gateway_urls`] that answers with a
+/// success status. Fails with every URL tried when none does.
+pub async fn fetch_with_fallback(uri: &str, ipfs_gateways: &[String]) -> Result<Fetched> {
+    let mut failures = Vec::new();
+    for url in gateway_urls(uri, ipfs_gateways) {
+        match fetch(&url, ipfs_gateways).await {
+            Ok((content_type, bytes)) => return Ok(Fetched { url, content_type, bytes, failures }),
+            Err(e) => failures.push((url, e.root_cause().to_string())),
+        }
+    }
+    let tried: Vec<String> = failures.iter().map(|(url, error)| format!("{} ({})", url, error)).collect();
+    Err(anyhow!("Failed to fetch {}: {}", uri, tried.join(", ")))
+}
+
+async fn fetch(url: &str, ipfs_gateways: &[String]) -> Result<(Option<String>, Vec<u8>)> {
+    let mut url = Url::parse(url)?;
+    for _ in 0..=MAX_REDIRECTS {
+        let http = client_for(&url, ipfs_gateways).await?;
+        let mut response = http.get(url.clone()).send().await?;
+        if response.status().is_redirection() {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| anyhow!("HTTP {} without a location", response.status()))?;
+            url = url.join(location)?;
+            continue;
+        }
+        if !response.status().is_success() {
+            bail!("HTTP {}", response.status());
+        }
+        if response.content_length().is_some_and(|length| length > MAX_FETCH_BYTES as u64) {
+            bail!("Larger than {} bytes", MAX_FETCH_BYTES);
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if bytes.len() + chunk.len() > MAX_FETCH_BYTES {
+                bail!("Larger than {} bytes", MAX_FETCH_BYTES);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        return Ok((content_type, bytes));
+    }
+    bail!("More than {} redirects", MAX_REDIRECTS)
+}
+
+/// A client for one request to `url`. URLs on a configured gateway are
+/// trusted, so a local IPFS node can serve as one; any other host is
+/// resolved here, refused unless every address is public, and pinned so
+/// the request cannot be pointed elsewhere by a second lookup.
+async fn client_for(url: &Url, ipfs_gateways: &[String]) -> Result<reqwest::Client> {
+    let builder = reqwest::Client::builder()
+        .redirect(Policy::none())
+        .timeout(FETCH_TIMEOUT)
+        .connect_timeout(CONNECT_TIMEOUT);
+    let on_gateway = ipfs_gateways
+        .iter()
+        .any(|gateway| url.as_str().starts_with(&format!("{}/ipfs/", gateway.trim_end_matches('/'))));
+    if on_gateway {
+        return Ok(builder.build()?);
+    }
+
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("Unsupported scheme {}", url.scheme());
+    }
+    let host = url.host_str().ok_or_else(|| anyhow!("{} has no host", url))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.trim_matches(['[', ']']), port)).await?.collect();
+    if addresses.is_empty() {
+        bail!("{} did not resolve", host);
+    }
+    if let Some(address) = addresses.iter().find(|address| !is_public(address.ip())) {
+        bail!("{} resolves to the non-public address {}", host, address.ip());
+    }
+    Ok(builder.resolve_to_addrs(host, &addresses).build()?)
+}
+
+/// False for loopback, private, link-local, shared, unspecified and other
+/// addresses that do not belong to a host on the public internet.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // 100.64.0.0/10, carrier-grade NAT
+        || (a == 100 && (64..128).contains(&b))
+        // 198.18.0.0/15, benchmarking
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // fc00::/7, unique local
+        || (first & 0xfe00) == 0xfc00
+        // fe80::/10, link-local
+        || (first & 0xffc0) == 0xfe80
+        // 2001:db8::/32, documentation
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
@@ -0,0 +1,338 @@
+//! Off-chain token metadata following the Metaplex JSON standard, shared by
+//! the browser and the server so both reject the same inputs.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod fetch;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use fetch::{fetch_with_fallback, Fetched};
+
+/// Longest name the token metadata program accepts, in bytes.
+pub const MAX_NAME_LENGTH: usize = 32;
+/// Longest symbol the token metadata program accepts, in bytes.
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+/// Longest metadata URI the token metadata program accepts, in bytes.
+pub const MAX_URI_LENGTH: usize = 200;
+
+const URI_SCHEMES: [&str; 4] = ["https://", "http://", "ipfs://", "ar://"];
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum MetadataError {
+    #[error("{0} is required")]
+    Missing(String),
+    #[error("{field} is {len} bytes, longer than the on-chain limit of {max}")]
+    TooLong { field: String, len: usize, max: usize },
+    #[error("{field} must be an http(s)://, ipfs:// or ar:// URI, got {value:?}")]
+    InvalidUri { field: String, value: String },
+    #[error("{field} must be a MIME type such as image/png, got {value:?}")]
+    InvalidMimeType { field: String, value: String },
+    #[error("{field} must be a hex SHA-256 digest, got {value:?}")]
+    InvalidDigest { field: String, value: String },
+    #[error("{0} does not match the token's {1}")]
+    Mismatch(String, &'static str),
+    #[error("content of {uri} has SHA-256 {actual}, expected {expected}")]
+    DigestMismatch { uri: String, expected: String, actual: String },
+}
+
+/// The JSON document a token's on-chain `uri` points to.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct OffchainMetadata {
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub image: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Properties>,
+    #[serde(default, skip_serializing_if = "Extensions::is_empty")]
+    pub extensions: Extensions,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Attribute {
+    pub trait_type: String,
+    pub value: AttributeValue,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Properties {
+    #[serde(default)]
+    pub files: Vec<MetadataFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<Category>,
+}
+
+/// One entry of the `properties.files` list.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MetadataFile {
+    pub uri: String,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+    /// Hex SHA-256 of the file, so consumers can detect a swapped asset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl MetadataFile {
+    /// Checks fetched `bytes` against the recorded digest. Files without a
+    /// digest cannot be checked and pass.
+    pub fn verify(&self, bytes: &[u8]) -> Result<(), MetadataError> {
+        let Some(expected) = &self.sha256 else {
+            return Ok(());
+        };
+        let actual = sha256_hex(bytes);
+        if actual.eq_ignore_ascii_case(expected) {
+            Ok(())
+        } else {
+            Err(MetadataError::DigestMismatch {
+                uri: self.uri.clone(),
+                expected: expected.clone(),
+                actual,
+            })
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Image,
+    Video,
+    Audio,
+    Vr,
+    Html,
+}
+
+/// Project links that wallets and explorers show next to the token.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Extensions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discord: Option<String>,
+}
+
+impl Extensions {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl OffchainMetadata {
+    /// Attaches processed image files, which also marks the token as an
+    /// image. Does nothing when `files` is empty.
+    pub fn with_files(mut self, files: Vec<MetadataFile>) -> Self {
+        if !files.is_empty() {
+            self.properties = Some(Properties {
+                files,
+                category: Some(Category::Image),
+            });
+        }
+        self
+    }
+
+    /// Checks the document against the standard and against the on-chain
+    /// limits for the name and symbol it repeats.
+    pub fn validate(&self) -> Result<(), MetadataError> {
+        validate_name(&self.name)?;
+        validate_symbol(&self.symbol)?;
+        if !self.image.is_empty() {
+            validate_uri("image", &self.image)?;
+        }
+        if let Some(url) = &self.animation_url {
+            validate_uri("animation_url", url)?;
+        }
+        if let Some(url) = &self.external_url {
+            validate_uri("external_url", url)?;
+        }
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            if attribute.trait_type.trim().is_empty() {
+                return Err(MetadataError::Missing(format!("attributes[{}].trait_type", i)));
+            }
+        }
+        if let Some(properties) = &self.properties {
+            for (i, file) in properties.files.iter().enumerate() {
+                validate_uri(&format!("properties.files[{}].uri", i), &file.uri)?;
+                if !is_mime_type(&file.mime_type) {
+                    return Err(MetadataError::InvalidMimeType {
+                        field: format!("properties.files[{}].type", i),
+                        value: file.mime_type.clone(),
+                    });
+                }
+                if let Some(digest) = file.sha256.as_ref().filter(|digest| !is_sha256_hex(digest)) {
+                    return Err(MetadataError::InvalidDigest {
+                        field: format!("properties.files[{}].sha256", i),
+                        value: digest.clone(),
+                    });
+                }
+            }
+        }
+        if let Some(website) = &self.extensions.website {
+            validate_uri("extensions.website", website)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that this document describes the token with `name` and
+    /// `symbol`, as wallets show a mismatch as a warning.
+    pub fn check_matches(&self, name: &str, symbol: &str) -> Result<(), MetadataError> {
+        if self.name != name {
+            return Err(MetadataError::Mismatch("name".to_string(), "on-chain name"));
+        }
+        if self.symbol != symbol {
+            return Err(MetadataError::Mismatch("symbol".to_string(), "on-chain symbol"));
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`, as recorded in `properties.files`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Fetchable URL for a metadata or image URI: `ipfs://` URIs are read
+/// through `ipfs_gateway` and `ar://` URIs through arweave.net.
+pub fn gateway_url(uri: &str, ipfs_gateway: &str) -> String {
+    if let Some(cid) = uri.strip_prefix("ipfs://") {
+        format!("{}/ipfs/{}", ipfs_gateway.trim_end_matches('/'), cid)
+    } else if let Some(id) = uri.strip_prefix("ar://") {
+        format!("https://arweave.net/{}", id)
+    } else {
+        uri.to_string()
+    }
+}
+
+/// URI schemes a page may link to or load an image from; on-chain URIs with
+/// any other, such as `javascript:` or `data:`, are shown as text only.
+const LINKABLE_SCHEMES: [&str; 4] = ["http://", "https://", "ipfs://", "ar://"];
+
+/// A URL for `uri` that is safe to render as a link or image source, with
+/// `ipfs://` and `ar://` mapped through their gateways. `None` for any other
+/// scheme.
+pub fn link_url(uri: &str, ipfs_gateway: &str) -> Option<String> {
+    LINKABLE_SCHEMES
+        .iter()
+        .any(|scheme| uri.starts_with(scheme))
+        .then(|| gateway_url(uri, ipfs_gateway))
+}
+
+/// Every URL a metadata or image URI can be read from, in the order to try
+/// them: `ipfs://` URIs through each gateway in turn, and `http(s)` URIs
+/// pinned to a gateway's `/ipfs/` path as written, then through the others.
+pub fn gateway_urls(uri: &str, ipfs_gateways: &[String]) -> Vec<String> {
+    let (mut urls, path) = match uri.strip_prefix("ipfs://") {
+        Some(cid) => (Vec::new(), Some(cid)),
+        None if uri.starts_with("http://") || uri.starts_with("https://") => {
+            (vec![uri.to_string()], uri.split_once("/ipfs/").map(|(_, path)| path))
+        }
+        None => (vec![gateway_url(uri, "")], None),
+    };
+    if let Some(path) = path {
+        for gateway in ipfs_gateways {
+            let url = format!("{}/ipfs/{}", gateway.trim_end_matches('/'), path);
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+pub fn validate_name(name: &str) -> Result<(), MetadataError> {
+    validate_required("name", name, MAX_NAME_LENGTH)
+}
+
+pub fn validate_symbol(symbol: &str) -> Result<(), MetadataError> {
+    validate_required("symbol", symbol, MAX_SYMBOL_LENGTH)
+}
+
+/// Checks a metadata URI before it is written on chain.
+pub fn validate_metadata_uri(uri: &str) -> Result<(), MetadataError> {
+    if uri.len() > MAX_URI_LENGTH {
+        return Err(MetadataError::TooLong {
+            field: "uri".to_string(),
+            len: uri.len(),
+            max: MAX_URI_LENGTH,
+        });
+    }
+    validate_uri("uri", uri)
+}
+
+fn validate_required(field: &str, value: &str, max: usize) -> Result<(), MetadataError> {
+    if value.trim().is_empty() {
+        return Err(MetadataError::Missing(field.to_string()));
+    }
+    if value.len() > max {
+        return Err(MetadataError::TooLong {
+            field: field.to_string(),
+            len: value.len(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+fn validate_uri(field: &str, value: &str) -> Result<(), MetadataError> {
+    let valid = URI_SCHEMES.iter().any(|scheme| {
+        value
+            .strip_prefix(scheme)
+            .is_some_and(|rest| !rest.is_empty() && !rest.contains(char::is_whitespace))
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(MetadataError::InvalidUri {
+            field: field.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+fn is_mime_type(value: &str) -> bool {
+    value
+        .split_once('/')
+        .is_some_and(|(kind, subtype)| !kind.is_empty() && !subtype.is_empty() && !subtype.contains('/'))
+}
+
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_only_to_safe_schemes() {
+        let gateway = "https://gateway.example/";
+        assert_eq!(link_url("https://example.com/a.json", gateway).as_deref(), Some("https://example.com/a.json"));
+        assert_eq!(link_url("ipfs://bafy/a.json", gateway).as_deref(), Some("https://gateway.example/ipfs/bafy/a.json"));
+        assert_eq!(link_url("ar://abc", gateway).as_deref(), Some("https://arweave.net/abc"));
+        for uri in ["javascript:alert(1)", " javascript:alert(1)", "JAVASCRIPT:alert(1)", "data:text/html,<script>", "", "example.com"] {
+            assert_eq!(link_url(uri, gateway), None, "{}", uri);
+        }
+    }
+}
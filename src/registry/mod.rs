@@ -0,0 +1,47 @@
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::token::{CreateTokenParams, NetworkType};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::TokenRegistry;
+
+/// A token created through this server, as stored in the registry.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TokenRecord {
+    pub mint: String,
+    pub network: NetworkType,
+    pub signature: String,
+    /// Parameters the token was created with; the payer is never stored.
+    pub params: CreateTokenParams,
+    pub metadata_uri: String,
+    /// Wallet that requested the creation.
+    pub creator: String,
+    /// Unix timestamp (seconds).
+    pub created_at: i64,
+}
+
+impl TokenRecord {
+    pub fn explorer_url(&self) -> String {
+        self.network.explorer_url().replace("{}", &self.mint)
+    }
+}
+
+#[server(ListTokens, "/api")]
+pub async fn list_tokens(creator: String) -> Result<Vec<TokenRecord>, ServerFnError> {
+    use actix_web::web;
+
+    let registry = leptos_actix::extract::<web::Data<TokenRegistry>>().await?;
+    registry.list(Some(&creator)).map_err(ServerFnError::new)
+}
+
+#[server(GetToken, "/api")]
+pub async fn get_token(mint: String) -> Result<Option<TokenRecord>, ServerFnError> {
+    use actix_web::web;
+
+    let registry = leptos_actix::extract::<web::Data<TokenRegistry>>().await?;
+    registry.get(&mint).map_err(ServerFnError::new)
+}
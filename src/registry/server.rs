@@ -0,0 +1,118 @@
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, types::Type, Connection, OptionalExtension, Row};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::TokenRecord;
+use crate::token::{CreateTokenParams, TokenCreationResult};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tokens (
+        mint         TEXT PRIMARY KEY,
+        network      TEXT NOT NULL,
+        signature    TEXT NOT NULL,
+        params       TEXT NOT NULL,
+        metadata_uri TEXT NOT NULL,
+        creator      TEXT NOT NULL,
+        created_at   INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS tokens_by_creator ON tokens (creator, created_at DESC);
+";
+
+const COLUMNS: &str = "mint, network, signature, params, metadata_uri, creator, created_at";
+
+/// SQLite-backed record of every token created through the server.
+pub struct TokenRegistry {
+    conn: Mutex<Connection>,
+}
+
+impl TokenRegistry {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open token registry {}", path))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    pub fn record(&self, record: &TokenRecord) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            &format!("INSERT OR REPLACE INTO tokens ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)", COLUMNS),
+            params![
+                record.mint,
+                network_key(record)?,
+                record.signature,
+                serde_json::to_value(&record.params)?,
+                record.metadata_uri,
+                record.creator,
+                record.created_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Records a token that `creator` just created.
+    pub fn record_creation(&self, params: CreateTokenParams, creator: &str, result: &TokenCreationResult) -> Result<()> {
+        self.record_mint(params, creator, &result.mint, &result.signature)
+    }
+
+    /// Records a token as soon as its mint lands in the transaction
+    /// `signature`, before its metadata and supply are sent, so a creation
+    /// that fails part way is still known.
+    pub fn record_mint(&self, params: CreateTokenParams, creator: &str, mint: &str, signature: &str) -> Result<()> {
+        self.record(&TokenRecord {
+            mint: mint.to_string(),
+            network: params.network.clone(),
+            signature: signature.to_string(),
+            metadata_uri: params.metadata_uri.clone(),
+            params,
+            creator: creator.to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Newest first, optionally limited to tokens created by `creator`.
+    pub fn list(&self, creator: Option<&str>) -> Result<Vec<TokenRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tokens WHERE ?1 IS NULL OR creator = ?1 ORDER BY created_at DESC",
+            COLUMNS
+        ))?;
+        let rows = stmt.query_map([creator], read_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn get(&self, mint: &str) -> Result<Option<TokenRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let record = conn
+            .query_row(&format!("SELECT {} FROM tokens WHERE mint = ?1", COLUMNS), [mint], read_row)
+            .optional()?;
+        Ok(record)
+    }
+}
+
+fn network_key(record: &TokenRecord) -> Result<String> {
+    match serde_json::to_value(&record.network)? {
+        Value::String(key) => Ok(key),
+        other => Err(anyhow::anyhow!("Unexpected network encoding {}", other)),
+    }
+}
+
+fn read_row(row: &Row) -> rusqlite::Result<TokenRecord> {
+    Ok(TokenRecord {
+        mint: row.get(0)?,
+        network: decode(1, Value::String(row.get(1)?))?,
+        signature: row.get(2)?,
+        params: decode(3, row.get(3)?)?,
+        metadata_uri: row.get(4)?,
+        creator: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+fn decode<T: DeserializeOwned>(column: usize, value: Value) -> rusqlite::Result<T> {
+    serde_json::from_value(value)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(column, Type::Text, Box::new(e)))
+}
@@ -0,0 +1,43 @@
+#[cfg(not(target_arch = "wasm32"))]
+use actix_web::{get, post, web, HttpResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    auth::AuthSession,
+    config::ServerConfig,
+    jobs::{BatchRequest, BatchRuns},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+#[post("/batches")]
+pub async fn submit_batch(
+    session: AuthSession,
+    runs: web::Data<BatchRuns>,
+    config: web::Data<ServerConfig>,
+    req: web::Json<BatchRequest>,
+) -> HttpResponse {
+    match runs.into_inner().submit(&session.0.address, req.into_inner(), config.into_inner()) {
+        Ok(status) => HttpResponse::Accepted().json(status),
+        Err(e) => HttpResponse::UnprocessableEntity().body(format!("{:#}", e)),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/batches/{id}")]
+pub async fn batch_status(session: AuthSession, runs: web::Data<BatchRuns>, id: web::Path<String>) -> HttpResponse {
+    match runs.status(&id, &session.0.address) {
+        Some(status) => HttpResponse::Ok().json(status),
+        None => HttpResponse::NotFound().body("Unknown batch"),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/batches/{id}/summary.csv")]
+pub async fn batch_summary_csv(session: AuthSession, runs: web::Data<BatchRuns>, id: web::Path<String>) -> HttpResponse {
+    match runs.status(&id, &session.0.address) {
+        Some(status) => HttpResponse::Ok()
+            .content_type("text/csv")
+            .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.csv\"", status.name)))
+            .body(status.summary.to_csv()),
+        None => HttpResponse::NotFound().body("Unknown batch"),
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(not(target_arch = "wasm32"))]
+use actix_web::{get, web, HttpResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Deserialize;
+#[cfg(not(target_arch = "wasm32"))]
+use solana_client::nonblocking::rpc_client::RpcClient;
+#[cfg(not(target_arch = "wasm32"))]
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    auth::AuthSession,
+    config::ServerConfig,
+    registry::TokenRegistry,
+    token::{
+        consistency::{check_consistency, check_registry},
+        NetworkType,
+    },
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deserialize)]
+pub struct MetadataCheckQuery {
+    pub network: NetworkType,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/tokens/{mint}/metadata-check")]
+pub async fn metadata_check_route(
+    config: web::Data<ServerConfig>,
+    mint: web::Path<String>,
+    query: web::Query<MetadataCheckQuery>,
+) -> HttpResponse {
+    let Ok(mint) = mint.parse::<Pubkey>() else {
+        return HttpResponse::BadRequest().body(format!("Invalid mint address {}", mint));
+    };
+    let client = RpcClient::new_with_commitment(config.rpc_url(&query.network).to_string(), CommitmentConfig::confirmed());
+    match check_consistency(&client, &mint, query.network.clone(), &config.storage.ipfs_gateways()).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::BadGateway().body(format!("{:#}", e)),
+    }
+}
+
+/// Checks every token in the registry; one report per token. Admins only,
+/// since each check makes outbound requests for every registered token.
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/metadata-check")]
+pub async fn registry_metadata_check_route(
+    session: AuthSession,
+    config: web::Data<ServerConfig>,
+    registry: web::Data<TokenRegistry>,
+) -> HttpResponse {
+    if !config.is_admin(&session.0.address) {
+        return HttpResponse::Forbidden().body("Admin access required");
+    }
+    match check_registry(&registry, &config).await {
+        Ok(reports) => HttpResponse::Ok().json(reports),
+        Err(e) => {
+            eprintln!("Error checking registry metadata: {:#}", e);
+            HttpResponse::InternalServerError().body("Failed to check registry metadata")
+        }
+    }
+}
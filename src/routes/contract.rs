@@ -1,49 +1,44 @@
 #[cfg(not(target_arch = "wasm32"))]
 use actix_web::{post, web, HttpResponse, Responder};
-
 #[cfg(not(target_arch = "wasm32"))]
-#[derive(serde::Deserialize)]
-pub struct CreateTokenRequest {
-    pub metadata_uri: String,
-}
+use crate::{auth::AuthSession, config::ServerConfig, registry::TokenRegistry, token::CreateTokenParams};
 
 #[cfg(not(target_arch = "wasm32"))]
 #[post("/create-token")]
-pub async fn create_token_route(req: web::Json<CreateTokenRequest>) -> impl Responder {
-    use crate::token::create_token;
-    use crate::token::NetworkType;
-    use solana_sdk::signer::keypair::Keypair;
+pub async fn create_token_route(
+    session: AuthSession,
+    config: web::Data<ServerConfig>,
+    registry: web::Data<TokenRegistry>,
+    req: web::Json<CreateTokenParams>,
+) -> impl Responder {
+    use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+    use crate::token::create_token_reporting_mint;
 
-    let _payer = match crate::utils::load_env_keypair("SOLANA_KEYPAIR_PATH") {
-        Ok(keypair) => Some(keypair),
+    let mut params = req.into_inner();
+    let recorded = params.clone();
+    params.payer = match config.payer_signer() {
+        Ok(signer) => Some(signer),
         Err(e) => {
-            eprintln!("Error loading keypair: {}", e);
-            return HttpResponse::InternalServerError().body("Failed to load keypair");
+            eprintln!("Error loading payer signer: {}", e);
+            return HttpResponse::InternalServerError().body("Failed to load payer signer");
         }
     };
+    let rpc_url = config.rpc_url(&params.network).to_string();
 
-    let params = crate::token::CreateTokenParams {
-        name: "TokenName".to_string(),
-        symbol: "SYMBOL".to_string(),
-        description: "Description".to_string(),
-        metadata_uri: req.metadata_uri.clone(),
-        decimals: 9,
-        initial_supply: 1000,
-        is_mutable: true,
-        freeze_authority: false,
-        rate_limit: None,
-        transfer_fee: None,
-        max_transfer_amount: None,
-        network: NetworkType::Devnet,
-        #[cfg(not(target_arch = "wasm32"))]
-        payer: None::<Keypair>,
+    // Recorded as soon as the mint lands, so the metadata stays referenced
+    // even if a later transaction fails.
+    let record_mint = |mint: &Pubkey, signature: &Signature| {
+        if let Err(e) = registry.record_mint(recorded, &session.0.address, &mint.to_string(), &signature.to_string()) {
+            eprintln!("Error recording token {}: {}", mint, e);
+        }
+        Ok(())
     };
-
-    match create_token(params).await {
+    match create_token_reporting_mint(params, &rpc_url, |_| {}, record_mint).await {
         Ok(result) => HttpResponse::Ok().json(&serde_json::json!(result)),
         Err(e) => {
             eprintln!("Error creating token: {}", e);
             HttpResponse::InternalServerError().body("Failed to create token")
         }
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,75 @@
+#[cfg(not(target_arch = "wasm32"))]
+use actix_web::{get, web, HttpResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Deserialize;
+#[cfg(not(target_arch = "wasm32"))]
+use solana_client::nonblocking::rpc_client::RpcClient;
+#[cfg(not(target_arch = "wasm32"))]
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    config::ServerConfig,
+    token::{
+        holders::{HolderCache, HolderSnapshot},
+        NetworkType,
+    },
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deserialize)]
+pub struct HoldersQuery {
+    pub network: NetworkType,
+    #[serde(default)]
+    pub exclude_programs: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn snapshot(
+    config: &ServerConfig,
+    holders: &HolderCache,
+    mint: &str,
+    query: &HoldersQuery,
+) -> Result<HolderSnapshot, HttpResponse> {
+    let mint: Pubkey = mint
+        .parse()
+        .map_err(|_| HttpResponse::BadRequest().body(format!("Invalid mint address {}", mint)))?;
+    let client = RpcClient::new_with_commitment(config.rpc_url(&query.network).to_string(), CommitmentConfig::confirmed());
+    holders
+        .snapshot(&client, &mint, query.exclude_programs)
+        .await
+        .map_err(|e| HttpResponse::BadGateway().body(format!("{:#}", e)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/tokens/{mint}/holders")]
+pub async fn holders_route(
+    config: web::Data<ServerConfig>,
+    holders: web::Data<HolderCache>,
+    mint: web::Path<String>,
+    query: web::Query<HoldersQuery>,
+) -> HttpResponse {
+    match snapshot(&config, &holders, &mint, &query).await {
+        Ok(snapshot) => HttpResponse::Ok().json(snapshot),
+        Err(response) => response,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/tokens/{mint}/holders.csv")]
+pub async fn holders_csv_route(
+    config: web::Data<ServerConfig>,
+    holders: web::Data<HolderCache>,
+    mint: web::Path<String>,
+    query: web::Query<HoldersQuery>,
+) -> HttpResponse {
+    match snapshot(&config, &holders, &mint, &query).await {
+        Ok(snapshot) => HttpResponse::Ok()
+            .content_type("text/csv")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{}-holders-{}.csv\"", snapshot.mint, snapshot.slot),
+            ))
+            .body(snapshot.to_csv()),
+        Err(response) => response,
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(not(target_arch = "wasm32"))]
+use actix_web::{get, post, web, HttpResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    auth::AuthSession,
+    config::ServerConfig,
+    jobs::{JobQueue, TokenJobRequest},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+#[post("/jobs")]
+pub async fn submit_job(
+    session: AuthSession,
+    queue: web::Data<JobQueue>,
+    config: web::Data<ServerConfig>,
+    req: web::Json<TokenJobRequest>,
+) -> HttpResponse {
+    if let Err(err) = req.validate() {
+        return HttpResponse::UnprocessableEntity().body(err.to_string());
+    }
+    let status = queue.into_inner().submit(&session.0.address, req.into_inner(), config.into_inner());
+    HttpResponse::Accepted().json(status)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/jobs/{id}")]
+pub async fn job_status(
+    session: AuthSession,
+    queue: web::Data<JobQueue>,
+    id: web::Path<String>,
+) -> HttpResponse {
+    match queue.status(&id, &session.0.address) {
+        Some(status) => HttpResponse::Ok().json(status),
+        None => HttpResponse::NotFound().body("Unknown job"),
+    }
+}
+
+/// Server-sent events: the current status, then one event per change until
+/// the job completes or fails.
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/jobs/{id}/events")]
+pub async fn job_events(
+    session: AuthSession,
+    queue: web::Data<JobQueue>,
+    id: web::Path<String>,
+) -> HttpResponse {
+    use futures::stream;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let Some((current, receiver)) = queue.subscribe(&id, &session.0.address) else {
+        return HttpResponse::NotFound().body("Unknown job");
+    };
+
+    // The receiver is dropped after the final event, which ends the stream.
+    let events = stream::unfold((Some(current), Some(receiver)), |(pending, receiver)| async move {
+        let mut receiver = receiver?;
+        let status = match pending {
+            Some(status) => status,
+            None => loop {
+                match receiver.recv().await {
+                    Ok(status) => break status,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            },
+        };
+        let next = (!status.stage.is_finished()).then_some(receiver);
+        Some((sse_event(&status), (None, next)))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn sse_event(status: &crate::jobs::JobStatus) -> Result<web::Bytes, actix_web::Error> {
+    let data = serde_json::to_string(status)?;
+    Ok(web::Bytes::from(format!("data: {}\n\n", data)))
+}
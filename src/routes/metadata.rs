@@ -1,16 +1,82 @@
 #[cfg(not(target_arch = "wasm32"))]
 use actix_web::{post, web, HttpResponse};
 #[cfg(not(target_arch = "wasm32"))]
-use crate::utils::pinata::server::upload_metadata_to_pinata;
+use crate::{
+    auth::AuthSession,
+    config::ServerConfig,
+    metadata::OffchainMetadata,
+    storage::{MetadataStore, UploadQuota},
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 #[post("/upload-metadata")]
 pub async fn upload_metadata(
-    metadata: web::Json<serde_json::Value>,
-    api_keys: web::Data<(String, String)>,
+    session: AuthSession,
+    metadata: web::Json<OffchainMetadata>,
+    store: web::Data<dyn MetadataStore>,
+    quota: web::Data<UploadQuota>,
 ) -> HttpResponse {
-    match upload_metadata_to_pinata(&api_keys.0, &api_keys.1, &metadata.0).await {
-        Ok(url) => HttpResponse::Ok().json(serde_json::json!({ "url": url })),
+    if let Err(err) = metadata.validate() {
+        return HttpResponse::UnprocessableEntity().body(err.to_string());
+    }
+    let bytes = match serde_json::to_vec(&metadata.0) {
+        Ok(bytes) => bytes,
+        Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+    };
+    if let Err(err) = quota.charge(&session.0.address, bytes.len()) {
+        return HttpResponse::TooManyRequests().body(err.to_string());
+    }
+    match store.put("metadata.json", "application/json", bytes).await {
+        Ok(object) => HttpResponse::Ok().json(serde_json::json!({
+            "url": object.uri,
+            "gatewayUrl": object.gateway_url,
+        })),
         Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
     }
-} 
\ No newline at end of file
+}
+
+/// Multipart logo upload; the image is read from the `file` field.
+#[cfg(not(target_arch = "wasm32"))]
+#[post("/images")]
+pub async fn upload_image(
+    session: AuthSession,
+    mut payload: actix_multipart::Multipart,
+    store: web::Data<dyn MetadataStore>,
+    quota: web::Data<UploadQuota>,
+    config: web::Data<ServerConfig>,
+) -> HttpResponse {
+    use futures::TryStreamExt;
+
+    let limit = config.images.max_input_bytes;
+    let mut image = None;
+    loop {
+        let mut field = match payload.try_next().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+        };
+        if field.name() != "file" {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        loop {
+            match field.try_next().await {
+                Ok(Some(chunk)) if bytes.len() + chunk.len() > limit => {
+                    return HttpResponse::PayloadTooLarge().body(format!("Image exceeds {} bytes", limit));
+                }
+                Ok(Some(chunk)) => bytes.extend_from_slice(&chunk),
+                Ok(None) => break,
+                Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+            }
+        }
+        image = Some(bytes);
+    }
+
+    let Some(bytes) = image else {
+        return HttpResponse::BadRequest().body("Missing file field");
+    };
+    match crate::upload::store_image(store.as_ref(), &quota, &session.0.address, bytes, config.images.clone()).await {
+        Ok(uploaded) => HttpResponse::Ok().json(uploaded),
+        Err(err) => HttpResponse::UnprocessableEntity().body(err.to_string()),
+    }
+}
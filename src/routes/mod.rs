@@ -1,2 +1,8 @@
 pub mod metadata;
-pub mod contract; 
\ No newline at end of file
+pub mod contract;
+pub mod jobs;
+pub mod batch;
+pub mod consistency;
+pub mod holders;
+pub mod registry;
+pub mod risk; 
\ No newline at end of file
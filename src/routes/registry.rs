@@ -0,0 +1,40 @@
+#[cfg(not(target_arch = "wasm32"))]
+use actix_web::{get, web, HttpResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Deserialize;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::registry::TokenRegistry;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deserialize)]
+pub struct ListTokensQuery {
+    pub creator: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/tokens")]
+pub async fn list_tokens_route(
+    registry: web::Data<TokenRegistry>,
+    query: web::Query<ListTokensQuery>,
+) -> HttpResponse {
+    match registry.list(query.creator.as_deref()) {
+        Ok(records) => HttpResponse::Ok().json(records),
+        Err(e) => {
+            eprintln!("Error listing tokens: {}", e);
+            HttpResponse::InternalServerError().body("Failed to list tokens")
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/tokens/{mint}")]
+pub async fn get_token_route(registry: web::Data<TokenRegistry>, mint: web::Path<String>) -> HttpResponse {
+    match registry.get(&mint) {
+        Ok(Some(record)) => HttpResponse::Ok().json(record),
+        Ok(None) => HttpResponse::NotFound().body("Unknown token"),
+        Err(e) => {
+            eprintln!("Error reading token {}: {}", mint, e);
+            HttpResponse::InternalServerError().body("Failed to read token")
+        }
+    }
+}
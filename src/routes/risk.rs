@@ -0,0 +1,37 @@
+#[cfg(not(target_arch = "wasm32"))]
+use actix_web::{get, web, HttpResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Deserialize;
+#[cfg(not(target_arch = "wasm32"))]
+use solana_client::nonblocking::rpc_client::RpcClient;
+#[cfg(not(target_arch = "wasm32"))]
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    config::ServerConfig,
+    token::{holders::HolderCache, risk::assess_risk, NetworkType},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deserialize)]
+pub struct RiskQuery {
+    pub network: NetworkType,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[get("/tokens/{mint}/risk")]
+pub async fn risk_route(
+    config: web::Data<ServerConfig>,
+    holders: web::Data<HolderCache>,
+    mint: web::Path<String>,
+    query: web::Query<RiskQuery>,
+) -> HttpResponse {
+    let Ok(mint) = mint.parse::<Pubkey>() else {
+        return HttpResponse::BadRequest().body(format!("Invalid mint address {}", mint));
+    };
+    let client = RpcClient::new_with_commitment(config.rpc_url(&query.network).to_string(), CommitmentConfig::confirmed());
+    match assess_risk(&client, &mint, query.network.clone(), &config.storage.ipfs_gateways(), &holders).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::BadGateway().body(format!("{:#}", e)),
+    }
+}
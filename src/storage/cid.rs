@@ -0,0 +1,243 @@
+//! Local computation of the CIDv1 an IPFS node assigns to added content, so
+//! what a pinning service reports can be checked against what was sent.
+//!
+//! Matches `ipfs add --cid-version=1`: 256 KiB fixed-size chunks stored as raw
+//! leaves under a balanced UnixFS DAG of at most 174 links per node. Content
+//! that fits in one chunk is a single raw block.
+
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+
+const CHUNK_SIZE: usize = 262_144;
+const MAX_LINKS: usize = 174;
+
+const CODEC_RAW: u64 = 0x55;
+const CODEC_DAG_PB: u64 = 0x70;
+const MULTIHASH_SHA2_256: u64 = 0x12;
+/// UnixFS `Data.Type` of a file node.
+const UNIXFS_FILE: u64 = 2;
+
+/// A node of the DAG being built: its CID bytes, the file bytes under it and
+/// its cumulative encoded size (the link `Tsize`).
+struct Node {
+    cid: Vec<u8>,
+    file_size: u64,
+    tree_size: u64,
+}
+
+/// The base32 CIDv1 of `bytes` as added to IPFS with the defaults above.
+pub fn cid_v1(bytes: &[u8]) -> String {
+    let mut level: Vec<Node> = if bytes.is_empty() {
+        vec![raw_leaf(bytes)]
+    } else {
+        bytes.chunks(CHUNK_SIZE).map(raw_leaf).collect()
+    };
+    while level.len() > 1 {
+        level = level.chunks(MAX_LINKS).map(file_node).collect();
+    }
+    let root = level.pop().expect("at least one node");
+    format!("b{}", base32(&root.cid))
+}
+
+/// Fails unless `returned`, the CID a backend reported, is the CID of the
+/// content that was sent.
+pub fn verify_cid(backend: &str, bytes: &[u8], returned: &str) -> Result<()> {
+    let expected = cid_v1(bytes);
+    if returned == expected {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{} returned CID {} but the uploaded content hashes to {}",
+            backend,
+            returned,
+            expected
+        ))
+    }
+}
+
+/// The CID at the start of an IPFS reference, in the base32 CIDv1 form
+/// [`cid_v1`] produces, so different spellings of one pin compare equal.
+/// Accepts `ipfs://<cid>[/path]`, path gateway URLs (`.../ipfs/<cid>[/path]`),
+/// subdomain gateway URLs (`https://<cid>.ipfs.<host>/...`) and bare CIDs.
+/// CIDv0 (`Qm...`) is converted to the CIDv1 of the same DAG. Anything else
+/// is `None`.
+pub fn normalize_cid(reference: &str) -> Option<String> {
+    let cid = if let Some(rest) = reference.strip_prefix("ipfs://") {
+        rest.split('/').next()?
+    } else if let Some(rest) = reference.strip_prefix("https://").or_else(|| reference.strip_prefix("http://")) {
+        match rest.split_once("/ipfs/") {
+            Some((_, path)) => path.split(['/', '?', '#']).next()?,
+            None => {
+                let host = rest.split(['/', '?', '#']).next()?;
+                match host.split_once('.') {
+                    Some((cid, domain)) if domain.starts_with("ipfs.") => cid,
+                    _ => return None,
+                }
+            }
+        }
+    } else {
+        reference
+    };
+    if cid.len() == 46 && cid.starts_with("Qm") {
+        let multihash = bs58::decode(cid).into_vec().ok()?;
+        let mut bytes = Vec::with_capacity(multihash.len() + 2);
+        put_varint(&mut bytes, 1);
+        put_varint(&mut bytes, CODEC_DAG_PB);
+        bytes.extend_from_slice(&multihash);
+        return Some(format!("b{}", base32(&bytes)));
+    }
+    let lower = cid.to_ascii_lowercase();
+    let is_base32 = lower.len() > 1 && lower.starts_with('b') && lower[1..].bytes().all(|c| matches!(c, b'a'..=b'z' | b'2'..=b'7'));
+    is_base32.then_some(lower)
+}
+
+fn raw_leaf(chunk: &[u8]) -> Node {
+    Node {
+        cid: cid_bytes(CODEC_RAW, chunk),
+        file_size: chunk.len() as u64,
+        tree_size: chunk.len() as u64,
+    }
+}
+
+/// A dag-pb node linking `children`, with UnixFS file data recording each
+/// child's share of the file.
+fn file_node(children: &[Node]) -> Node {
+    let file_size = children.iter().map(|child| child.file_size).sum();
+
+    let mut data = Vec::new();
+    put_varint_field(&mut data, 1, UNIXFS_FILE);
+    put_varint_field(&mut data, 3, file_size);
+    for child in children {
+        put_varint_field(&mut data, 4, child.file_size);
+    }
+
+    // dag-pb orders links before data.
+    let mut node = Vec::new();
+    for child in children {
+        let mut link = Vec::new();
+        put_bytes_field(&mut link, 1, &child.cid);
+        put_bytes_field(&mut link, 2, b"");
+        put_varint_field(&mut link, 3, child.tree_size);
+        put_bytes_field(&mut node, 2, &link);
+    }
+    put_bytes_field(&mut node, 1, &data);
+
+    Node {
+        cid: cid_bytes(CODEC_DAG_PB, &node),
+        file_size,
+        tree_size: node.len() as u64 + children.iter().map(|child| child.tree_size).sum::<u64>(),
+    }
+}
+
+fn cid_bytes(codec: u64, block: &[u8]) -> Vec<u8> {
+    let mut cid = Vec::with_capacity(36);
+    put_varint(&mut cid, 1);
+    put_varint(&mut cid, codec);
+    put_varint(&mut cid, MULTIHASH_SHA2_256);
+    put_varint(&mut cid, 32);
+    cid.extend_from_slice(&Sha256::digest(block));
+    cid
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_varint_field(out: &mut Vec<u8>, field: u64, value: u64) {
+    put_varint(out, field << 3);
+    put_varint(out, value);
+}
+
+fn put_bytes_field(out: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(out, field << 3 | 2);
+    put_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// RFC 4648 base32, lowercase and unpadded, as used by the `b` multibase.
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut out = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = buffer << 8 | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    // The empty and `hello world` CIDs are what `ipfs add --cid-version=1
+    // --raw-leaves` prints for them. The multi-block ones were computed
+    // separately from the dag-pb and UnixFS specs.
+
+    #[test]
+    fn single_blocks_are_raw_leaves() {
+        assert_eq!(cid_v1(b""), "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku");
+        assert_eq!(cid_v1(b"hello world"), "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e");
+        assert_eq!(cid_v1(&pattern(CHUNK_SIZE)), "bafkreibruh455iawsviqslif5c7uurdcfdemh22mtnytyzvnzn75kpejxy");
+    }
+
+    #[test]
+    fn content_past_one_chunk_gets_a_file_node() {
+        assert_eq!(cid_v1(&pattern(CHUNK_SIZE + 1)), "bafybeiexg2oqkfnj56l7fcmawswqbijt5shq4b5rg6a546uwpkqqzwjioi");
+    }
+
+    #[test]
+    fn content_past_174_chunks_gets_a_second_level() {
+        assert_eq!(
+            cid_v1(&pattern(MAX_LINKS * CHUNK_SIZE + 1)),
+            "bafybeib4y7ghw2rq7bracc4xwtxrbzo7cfvagdpte2tmrkgwl6dyard3cm"
+        );
+    }
+
+    #[test]
+    fn verify_cid_names_both_cids() {
+        let expected = cid_v1(b"hello world");
+        assert!(verify_cid("test", b"hello world", &expected).is_ok());
+        let error = verify_cid("test", b"hello world!", &expected).unwrap_err().to_string();
+        assert!(error.contains(&expected) && error.contains(&cid_v1(b"hello world!")), "{}", error);
+    }
+
+    #[test]
+    fn normalizes_every_spelling_of_a_cid() {
+        let cid = "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e";
+        for reference in [
+            cid.to_string(),
+            cid.to_uppercase(),
+            format!("ipfs://{}", cid),
+            format!("ipfs://{}/metadata.json", cid),
+            format!("https://gateway.pinata.cloud/ipfs/{}?filename=a.json", cid),
+            format!("http://localhost:8080/ipfs/{}/a/b", cid),
+            format!("https://{}.ipfs.dweb.link/metadata.json", cid),
+        ] {
+            assert_eq!(normalize_cid(&reference).as_deref(), Some(cid), "{}", reference);
+        }
+        assert_eq!(
+            normalize_cid("ipfs://QmY7Yh4UquoXHLPFo2XbhXkhBvFoPwmQUSa92pxnxjQuPU").as_deref(),
+            Some("bafybeierhgbz4zp2x2u67urqrgfnrnlukciupzenpqpipiz5nwtq7uxpx4")
+        );
+        for reference in ["", "https://example.com/metadata.json", "ar://abc", "ipfs://", "b1nvalid"] {
+            assert_eq!(normalize_cid(reference), None, "{}", reference);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{content_name, content_type_of, is_content_name, MetadataStore, StoredObject};
+use crate::config::FilesystemConfig;
+use crate::utils::pinata::PinnedObject;
+
+/// Writes objects to a local directory. Meant for development; the server
+/// serves the directory under `/storage`.
+pub struct FilesystemStore {
+    directory: PathBuf,
+    public_url: String,
+}
+
+impl FilesystemStore {
+    pub fn new(config: &FilesystemConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.directory)
+            .with_context(|| format!("Failed to create storage directory {}", config.directory))?;
+        Ok(Self {
+            directory: PathBuf::from(&config.directory),
+            public_url: config.public_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn object(&self, name: &str) -> StoredObject {
+        let url = format!("{}/{}", self.public_url, name);
+        StoredObject { uri: url.clone(), gateway_url: url }
+    }
+
+    /// Path of the object at `uri`, which must be one of ours.
+    fn path_of(&self, uri: &str) -> Result<PathBuf> {
+        uri.strip_prefix(&self.public_url)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .filter(|name| !name.is_empty() && !name.contains(['/', '\\']) && *name != "..")
+            .map(|name| self.directory.join(name))
+            .ok_or_else(|| anyhow!("{} is not stored under {}", uri, self.public_url))
+    }
+}
+
+#[async_trait]
+impl MetadataStore for FilesystemStore {
+    fn name(&self) -> &'static str {
+        "filesystem"
+    }
+
+    async fn put(&self, _file_name: &str, content_type: &str, bytes: Vec<u8>) -> Result<StoredObject> {
+        let name = content_name(content_type, &bytes);
+        let path = self.directory.join(&name);
+        tokio::fs::write(&path, bytes)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok(self.object(&name))
+    }
+
+    async fn list(&self) -> Result<Vec<PinnedObject>> {
+        let mut objects = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.directory).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            // Only names `put` writes; anything else was put there by hand.
+            if !metadata.is_file() || !is_content_name(&name) {
+                continue;
+            }
+            objects.push(PinnedObject {
+                uri: self.object(&name).uri,
+                content_type: content_type_of(&name).map(str::to_string),
+                name: Some(name),
+                size: metadata.len(),
+                pinned_at: metadata.modified().ok().map(|at| DateTime::<Utc>::from(at).timestamp()),
+            });
+        }
+        Ok(objects)
+    }
+
+    async fn unpin(&self, uri: &str) -> Result<()> {
+        let path = self.path_of(uri)?;
+        tokio::fs::remove_file(&path)
+            .await
+            .with_context(|| format!("Failed to remove {}", path.display()))
+    }
+
+    async fn repin(&self, uri: &str) -> Result<StoredObject> {
+        // Nothing to fetch from: an object is either still on disk or gone.
+        let path = self.path_of(uri)?;
+        if !tokio::fs::try_exists(&path).await? {
+            return Err(anyhow!("{} is no longer stored", uri));
+        }
+        Ok(StoredObject { uri: uri.to_string(), gateway_url: uri.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(directory: &std::path::Path) -> FilesystemStore {
+        FilesystemStore::new(&FilesystemConfig {
+            directory: directory.to_str().unwrap().to_string(),
+            public_url: "http://localhost:3000/storage/".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trips_objects() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = store(directory.path());
+        let bytes = br#"{"name":"Test"}"#.to_vec();
+
+        let object = store.put("metadata.json", "application/json", bytes.clone()).await.unwrap();
+        let name = content_name("application/json", &bytes);
+        assert_eq!(object.uri, format!("http://localhost:3000/storage/{}", name));
+        assert_eq!(std::fs::read(directory.path().join(&name)).unwrap(), bytes);
+
+        // Same content, same object.
+        let again = store.put("other.json", "application/json", bytes.clone()).await.unwrap();
+        assert_eq!(again.uri, object.uri);
+
+        // Files `put` did not write are left out of the listing.
+        std::fs::write(directory.path().join("notes.txt"), "hand-written").unwrap();
+        let listed = store.list().await.unwrap();
+        let [pinned] = listed.as_slice() else { panic!("expected one object, got {:?}", listed) };
+        assert_eq!(pinned.uri, object.uri);
+        assert_eq!(pinned.size, bytes.len() as u64);
+        assert_eq!(pinned.content_type.as_deref(), Some("application/json"));
+
+        assert_eq!(store.repin(&object.uri).await.unwrap().uri, object.uri);
+        store.unpin(&object.uri).await.unwrap();
+        assert!(!directory.path().join(&name).exists());
+        assert!(store.list().await.unwrap().is_empty());
+        assert!(store.repin(&object.uri).await.is_err());
+        assert!(directory.path().join("notes.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn refuses_uris_outside_the_directory() {
+        let directory = tempfile::tempdir().unwrap();
+        let store = store(directory.path());
+        for uri in [
+            "http://localhost:3000/storage/../secret",
+            "http://localhost:3000/storage/a/b.json",
+            "http://localhost:3000/storage/",
+            "http://elsewhere/storage/a.json",
+        ] {
+            assert!(store.unpin(uri).await.is_err(), "{} was accepted", uri);
+        }
+    }
+}
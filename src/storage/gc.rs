@@ -0,0 +1,78 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use chrono::Utc;
+
+use super::{normalize_cid, MetadataStore};
+use crate::{config::GcConfig, registry::TokenRegistry, utils::pinata::GcReport};
+
+/// Unpins stored metadata documents at least `min_age_secs` old that no token
+/// in the registry points to. Server-side creations are recorded as soon as
+/// their mint lands, before the metadata account is created, so anything
+/// else is left over from an attempt that never reached the chain.
+/// Only objects the backend lists as stored by this tool are considered,
+/// never ones without a timestamp. IPFS references are compared by CID, so
+/// a gateway URL in the registry keeps the `ipfs://` pin it names.
+pub async fn collect_garbage(
+    store: &dyn MetadataStore,
+    registry: &TokenRegistry,
+    min_age_secs: u64,
+    dry_run: bool,
+) -> Result<GcReport> {
+    let referenced: HashSet<String> = registry
+        .list(None)?
+        .into_iter()
+        .map(|record| pin_key(&record.metadata_uri))
+        .collect();
+    let cutoff = Utc::now().timestamp() - min_age_secs as i64;
+
+    let mut report = GcReport { dry_run, ..GcReport::default() };
+    for object in store.list().await? {
+        let is_metadata = object.content_type.as_deref() == Some("application/json");
+        if !is_metadata || !object.pinned_at.is_some_and(|at| at <= cutoff) {
+            continue;
+        }
+        report.scanned += 1;
+        if referenced.contains(&pin_key(&object.uri)) {
+            report.kept += 1;
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = store.unpin(&object.uri).await {
+                report.failed.push(format!("{}: {}", object.uri, e));
+                continue;
+            }
+        }
+        report.unpinned.push(object.uri);
+    }
+    Ok(report)
+}
+
+/// What identifies the stored object `uri` refers to: its CID for IPFS
+/// references, the URI itself otherwise.
+fn pin_key(uri: &str) -> String {
+    normalize_cid(uri).unwrap_or_else(|| uri.trim_end_matches('/').to_string())
+}
+
+/// Runs [`collect_garbage`] every `interval_secs` when enabled.
+pub fn spawn_gc(store: Arc<dyn MetadataStore>, registry: Arc<TokenRegistry>, config: GcConfig) {
+    if !config.enabled {
+        return;
+    }
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs.max(60)));
+        loop {
+            interval.tick().await;
+            match collect_garbage(&*store, &registry, config.min_age_secs, false).await {
+                Ok(report) => println!(
+                    "Storage GC: {} scanned, {} kept, {} unpinned, {} failed",
+                    report.scanned,
+                    report.kept,
+                    report.unpinned.len(),
+                    report.failed.len()
+                ),
+                Err(e) => eprintln!("Storage GC failed: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,299 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::DateTime;
+use reqwest::{
+    multipart::{Form, Part},
+    Client, RequestBuilder,
+};
+use serde_json::{json, Value};
+
+use super::{verify_cid, MetadataStore, StoredObject};
+use crate::config::{KuboConfig, PinataConfig};
+use crate::utils::pinata::PinnedObject;
+
+const PINATA_API_URL: &str = "https://api.pinata.cloud";
+/// Pinata metadata key marking pins made by this server.
+const PIN_TAG_KEY: &str = "app";
+const PIN_TAG_VALUE: &str = "sol-tools";
+const PIN_LIST_PAGE_SIZE: usize = 1000;
+
+fn ipfs_object(gateway: &str, cid: &str) -> StoredObject {
+    StoredObject {
+        uri: format!("ipfs://{}", cid),
+        gateway_url: format!("{}/ipfs/{}", gateway.trim_end_matches('/'), cid),
+    }
+}
+
+/// Accepts `ipfs://<cid>` or a bare CID.
+fn cid_from_uri(uri: &str) -> Result<&str> {
+    let cid = uri.strip_prefix("ipfs://").unwrap_or(uri);
+    if cid.is_empty() || cid.contains(['/', ':']) {
+        return Err(anyhow!("Not an IPFS CID: {}", uri));
+    }
+    Ok(cid)
+}
+
+fn file_form(file_name: &str, content_type: &str, bytes: Vec<u8>) -> Result<Form> {
+    let part = Part::bytes(bytes).file_name(file_name.to_string()).mime_str(content_type)?;
+    Ok(Form::new().part("file", part))
+}
+
+/// Pins through the Pinata API.
+pub struct PinataStore {
+    client: Client,
+    jwt: String,
+    api_key: String,
+    secret_api_key: String,
+    gateway: String,
+}
+
+impl PinataStore {
+    pub fn new(config: &PinataConfig, gateway: &str) -> Self {
+        Self {
+            client: Client::new(),
+            jwt: config.jwt.clone(),
+            api_key: config.api_key.clone(),
+            secret_api_key: config.secret_api_key.clone(),
+            gateway: gateway.to_string(),
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.jwt.is_empty() {
+            request
+                .header("pinata_api_key", &self.api_key)
+                .header("pinata_secret_api_key", &self.secret_api_key)
+        } else {
+            request.bearer_auth(&self.jwt)
+        }
+    }
+
+    /// Pin name and key-values tagging a pin as ours.
+    fn pin_metadata(name: &str, content_type: Option<&str>) -> Value {
+        let mut keyvalues = json!({ PIN_TAG_KEY: PIN_TAG_VALUE });
+        if let Some(content_type) = content_type {
+            keyvalues["contentType"] = json!(content_type);
+        }
+        json!({ "name": name, "keyvalues": keyvalues })
+    }
+
+    fn pinned_object(row: &Value) -> Option<PinnedObject> {
+        let cid = row["ipfs_pin_hash"].as_str()?;
+        Some(PinnedObject {
+            uri: format!("ipfs://{}", cid),
+            name: row["metadata"]["name"].as_str().map(str::to_string),
+            content_type: row["metadata"]["keyvalues"]["contentType"].as_str().map(str::to_string),
+            size: row["size"].as_u64().unwrap_or_default(),
+            pinned_at: row["date_pinned"]
+                .as_str()
+                .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+                .map(|date| date.timestamp()),
+        })
+    }
+}
+
+#[async_trait]
+impl MetadataStore for PinataStore {
+    fn name(&self) -> &'static str {
+        "pinata"
+    }
+
+    async fn put(&self, file_name: &str, content_type: &str, bytes: Vec<u8>) -> Result<StoredObject> {
+        let request = self.authorize(self.client.post(format!("{}/pinning/pinFileToIPFS", PINATA_API_URL)));
+        // Pinata answers with a CIDv0 unless asked otherwise, and only the
+        // CIDv1 layout can be checked locally.
+        let form = file_form(file_name, content_type, bytes.clone())?
+            .text("pinataOptions", r#"{"cidVersion":1}"#)
+            .text("pinataMetadata", Self::pin_metadata(file_name, Some(content_type)).to_string());
+        let result = request
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let cid = result["IpfsHash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Failed to get IPFS hash"))?;
+        verify_cid(self.name(), &bytes, cid)?;
+        Ok(ipfs_object(&self.gateway, cid))
+    }
+
+    async fn list(&self) -> Result<Vec<PinnedObject>> {
+        let filter = json!({ PIN_TAG_KEY: { "value": PIN_TAG_VALUE, "op": "eq" } }).to_string();
+        let mut objects = Vec::new();
+        loop {
+            let offset = objects.len().to_string();
+            let page_size = PIN_LIST_PAGE_SIZE.to_string();
+            let result = self
+                .authorize(self.client.get(format!("{}/data/pinList", PINATA_API_URL)))
+                .query(&[
+                    ("status", "pinned"),
+                    ("pageLimit", &page_size),
+                    ("pageOffset", &offset),
+                    ("metadata[keyvalues]", &filter),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Value>()
+                .await?;
+
+            let rows = result["rows"]
+                .as_array()
+                .ok_or_else(|| anyhow!("Pinata pin list is missing rows"))?;
+            objects.extend(rows.iter().filter_map(Self::pinned_object));
+            if rows.len() < PIN_LIST_PAGE_SIZE {
+                return Ok(objects);
+            }
+        }
+    }
+
+    async fn unpin(&self, uri: &str) -> Result<()> {
+        let cid = cid_from_uri(uri)?;
+        self.authorize(self.client.delete(format!("{}/pinning/unpin/{}", PINATA_API_URL, cid)))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn repin(&self, uri: &str) -> Result<StoredObject> {
+        let cid = cid_from_uri(uri)?;
+        self.authorize(self.client.post(format!("{}/pinning/pinByHash", PINATA_API_URL)))
+            .json(&json!({
+                "hashToPin": cid,
+                "pinataMetadata": Self::pin_metadata(cid, None),
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(ipfs_object(&self.gateway, cid))
+    }
+}
+
+/// Adds and pins through a Kubo (go-ipfs) node's RPC API. Kubo pins carry no
+/// tags telling this server's apart, so they cannot be listed.
+pub struct KuboStore {
+    client: Client,
+    api_url: String,
+    gateway: String,
+}
+
+impl KuboStore {
+    pub fn new(config: &KuboConfig, gateway: &str) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            gateway: gateway.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl MetadataStore for KuboStore {
+    fn name(&self) -> &'static str {
+        "kubo"
+    }
+
+    async fn put(&self, file_name: &str, content_type: &str, bytes: Vec<u8>) -> Result<StoredObject> {
+        let result = self
+            .client
+            .post(format!(
+                "{}/api/v0/add?cid-version=1&raw-leaves=true&chunker=size-262144&pin=true",
+                self.api_url
+            ))
+            .multipart(file_form(file_name, content_type, bytes.clone())?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        let cid = result["Hash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Kubo response is missing the CID"))?;
+        verify_cid(self.name(), &bytes, cid)?;
+        Ok(ipfs_object(&self.gateway, cid))
+    }
+    async fn unpin(&self, uri: &str) -> Result<()> {
+        let cid = cid_from_uri(uri)?;
+        self.client
+            .post(format!("{}/api/v0/pin/rm", self.api_url))
+            .query(&[("arg", cid)])
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn repin(&self, uri: &str) -> Result<StoredObject> {
+        let cid = cid_from_uri(uri)?;
+        self.client
+            .post(format!("{}/api/v0/pin/add", self.api_url))
+            .query(&[("arg", cid)])
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(ipfs_object(&self.gateway, cid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::cid_v1, testing::MockServer};
+
+    fn kubo(server: &MockServer) -> KuboStore {
+        KuboStore::new(&KuboConfig { api_url: format!("{}/", server.url) }, "https://gateway.example")
+    }
+
+    #[tokio::test]
+    async fn kubo_put_adds_and_checks_the_cid() {
+        let bytes = br#"{"name":"Test"}"#.to_vec();
+        let cid = cid_v1(&bytes);
+        let server = MockServer::start(vec![(200, "application/json", json!({ "Hash": cid }).to_string().into_bytes())]);
+
+        let object = kubo(&server).put("metadata.json", "application/json", bytes.clone()).await.unwrap();
+        assert_eq!(object.uri, format!("ipfs://{}", cid));
+        assert_eq!(object.gateway_url, format!("https://gateway.example/ipfs/{}", cid));
+
+        let requests = server.requests();
+        let [request] = requests.as_slice() else { panic!("expected one request, got {:?}", requests) };
+        assert_eq!(request.method, "POST");
+        assert_eq!(
+            request.target,
+            "/api/v0/add?cid-version=1&raw-leaves=true&chunker=size-262144&pin=true"
+        );
+        assert!(request.header("content-type").is_some_and(|value| value.starts_with("multipart/form-data")));
+        assert!(request.body.windows(bytes.len()).any(|window| window == bytes));
+    }
+
+    #[tokio::test]
+    async fn kubo_put_rejects_a_cid_for_other_content() {
+        let server = MockServer::start(vec![(
+            200,
+            "application/json",
+            json!({ "Hash": cid_v1(b"something else") }).to_string().into_bytes(),
+        )]);
+        assert!(kubo(&server).put("logo.png", "image/png", vec![1, 2, 3]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn kubo_unpins_and_repins_by_cid() {
+        let cid = cid_v1(b"pinned");
+        let server = MockServer::start(vec![
+            (200, "application/json", json!({ "Pins": [cid] }).to_string().into_bytes()),
+            (200, "application/json", json!({ "Pins": [cid] }).to_string().into_bytes()),
+        ]);
+        let store = kubo(&server);
+
+        store.unpin(&format!("ipfs://{}", cid)).await.unwrap();
+        assert_eq!(store.repin(&cid).await.unwrap().uri, format!("ipfs://{}", cid));
+        assert!(store.unpin("https://example.com/a.json").await.is_err());
+
+        let targets: Vec<String> = server.requests().into_iter().map(|request| request.target).collect();
+        assert_eq!(targets, [format!("/api/v0/pin/rm?arg={}", cid), format!("/api/v0/pin/add?arg={}", cid)]);
+    }
+}
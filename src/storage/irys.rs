@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::{rngs::OsRng, RngCore};
+use reqwest::{header, Client};
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha384};
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use super::{MetadataStore, StoredObject};
+use crate::config::IrysConfig;
+
+/// ANS-104 signature type for ed25519 (Solana) signers.
+const ED25519_SIGNATURE_TYPE: u16 = 2;
+
+/// Uploads to Arweave through an Irys bundler node, paid for and signed by a
+/// Solana keypair. The node must already hold a balance for that key.
+pub struct IrysStore {
+    client: Client,
+    node_url: String,
+    gateway_url: String,
+    signer: Keypair,
+}
+
+impl IrysStore {
+    pub fn new(config: &IrysConfig, signer: Keypair) -> Self {
+        Self {
+            client: Client::new(),
+            node_url: config.node_url.trim_end_matches('/').to_string(),
+            gateway_url: config.gateway_url.trim_end_matches('/').to_string(),
+            signer,
+        }
+    }
+}
+
+#[async_trait]
+impl MetadataStore for IrysStore {
+    fn name(&self) -> &'static str {
+        "irys"
+    }
+
+    async fn put(&self, _file_name: &str, content_type: &str, bytes: Vec<u8>) -> Result<StoredObject> {
+        let (id, item) = signed_data_item(&self.signer, &[("Content-Type", content_type)], &bytes);
+
+        let result = self
+            .client
+            .post(format!("{}/tx/solana", self.node_url))
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .body(item)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await?;
+
+        if let Some(accepted) = result["id"].as_str() {
+            if accepted != id {
+                return Err(anyhow!("Bundler accepted {} but the data item id is {}", accepted, id));
+            }
+        }
+        Ok(StoredObject {
+            uri: format!("ar://{}", id),
+            gateway_url: format!("{}/{}", self.gateway_url, id),
+        })
+    }
+}
+
+/// Builds a signed ANS-104 data item and returns it with its id.
+fn signed_data_item(signer: &Keypair, tags: &[(&str, &str)], data: &[u8]) -> (String, Vec<u8>) {
+    let owner = signer.pubkey().to_bytes();
+    let mut anchor = [0u8; 32];
+    OsRng.fill_bytes(&mut anchor);
+    let tag_bytes = encode_tags(tags);
+
+    let message = deep_hash_list(&[
+        b"dataitem",
+        b"1",
+        ED25519_SIGNATURE_TYPE.to_string().as_bytes(),
+        &owner,
+        &[],
+        &anchor,
+        &tag_bytes,
+        data,
+    ]);
+    let signature = signer.sign_message(&message);
+    let id = URL_SAFE_NO_PAD.encode(Sha256::digest(signature.as_ref()));
+
+    let mut item = Vec::with_capacity(2 + 64 + 32 + 1 + 33 + 16 + tag_bytes.len() + data.len());
+    item.extend_from_slice(&ED25519_SIGNATURE_TYPE.to_le_bytes());
+    item.extend_from_slice(signature.as_ref());
+    item.extend_from_slice(&owner);
+    item.push(0); // no target
+    item.push(1);
+    item.extend_from_slice(&anchor);
+    item.extend_from_slice(&(tags.len() as u64).to_le_bytes());
+    item.extend_from_slice(&(tag_bytes.len() as u64).to_le_bytes());
+    item.extend_from_slice(&tag_bytes);
+    item.extend_from_slice(data);
+
+    (id, item)
+}
+
+/// Avro encoding of the tag array, as ANS-104 requires.
+fn encode_tags(tags: &[(&str, &str)]) -> Vec<u8> {
+    if tags.is_empty() {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    write_avro_long(&mut out, tags.len() as i64);
+    for (name, value) in tags {
+        for field in [name.as_bytes(), value.as_bytes()] {
+            write_avro_long(&mut out, field.len() as i64);
+            out.extend_from_slice(field);
+        }
+    }
+    write_avro_long(&mut out, 0);
+    out
+}
+
+fn write_avro_long(out: &mut Vec<u8>, value: i64) {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    while zigzag >= 0x80 {
+        out.push((zigzag as u8 & 0x7f) | 0x80);
+        zigzag >>= 7;
+    }
+    out.push(zigzag as u8);
+}
+
+/// Arweave deep hash of a list of blobs.
+fn deep_hash_list(items: &[&[u8]]) -> Vec<u8> {
+    let tag = format!("list{}", items.len());
+    items.iter().fold(Sha384::digest(tag.as_bytes()).to_vec(), |acc, item| {
+        let mut hasher = Sha384::new();
+        hasher.update(&acc);
+        hasher.update(deep_hash_blob(item));
+        hasher.finalize().to_vec()
+    })
+}
+
+fn deep_hash_blob(data: &[u8]) -> Vec<u8> {
+    let tag = format!("blob{}", data.len());
+    let mut hasher = Sha384::new();
+    hasher.update(Sha384::digest(tag.as_bytes()));
+    hasher.update(Sha384::digest(data));
+    hasher.finalize().to_vec()
+}
@@ -0,0 +1,123 @@
+//! Backends that hold off-chain token metadata and images.
+//!
+//! Every backend returns a [`StoredObject`] whose `uri` uses the backend's
+//! native scheme (`ipfs://<cid>`, `ar://<id>`) or, for plain object stores,
+//! the public `https://` URL. `gateway_url` is always fetchable over HTTP.
+
+mod cid;
+mod filesystem;
+mod gc;
+mod ipfs;
+mod irys;
+mod quota;
+mod s3;
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::{ServerConfig, StorageBackend};
+use crate::utils::load_keypair_from_path;
+use crate::utils::pinata::PinnedObject;
+
+pub use cid::{cid_v1, normalize_cid, verify_cid};
+pub use filesystem::FilesystemStore;
+pub use gc::{collect_garbage, spawn_gc};
+pub use ipfs::{KuboStore, PinataStore};
+pub use irys::IrysStore;
+pub use quota::UploadQuota;
+pub use s3::S3Store;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StoredObject {
+    /// URI to reference from on-chain metadata.
+    pub uri: String,
+    /// HTTP URL the object can be fetched from.
+    pub gateway_url: String,
+}
+
+#[async_trait]
+pub trait MetadataStore: Send + Sync {
+    /// Backend name for logs and error messages.
+    fn name(&self) -> &'static str;
+
+    /// Stores `bytes` and returns where they can be found. `file_name` is a
+    /// hint; content-addressed backends ignore it.
+    async fn put(&self, file_name: &str, content_type: &str, bytes: Vec<u8>) -> Result<StoredObject>;
+
+    async fn put_json(&self, value: &serde_json::Value) -> Result<StoredObject> {
+        self.put("metadata.json", "application/json", serde_json::to_vec(value)?).await
+    }
+
+    /// Objects this server stored that the backend still holds.
+    async fn list(&self) -> Result<Vec<PinnedObject>> {
+        Err(anyhow!("{} storage cannot list stored objects", self.name()))
+    }
+
+    /// Releases the object at `uri` so the backend may drop it.
+    async fn unpin(&self, _uri: &str) -> Result<()> {
+        Err(anyhow!("{} storage cannot unpin objects", self.name()))
+    }
+
+    /// Pins content that is already on the network, by URI or bare CID.
+    async fn repin(&self, _uri: &str) -> Result<StoredObject> {
+        Err(anyhow!("{} storage cannot re-pin objects", self.name()))
+    }
+}
+
+/// Builds the backend selected by `storage.backend`.
+pub fn from_config(config: &ServerConfig) -> Result<Arc<dyn MetadataStore>> {
+    let storage = &config.storage;
+    Ok(match storage.backend {
+        StorageBackend::Pinata => Arc::new(PinataStore::new(&storage.pinata, &storage.ipfs_gateway)),
+        StorageBackend::Kubo => Arc::new(KuboStore::new(&storage.kubo, &storage.ipfs_gateway)),
+        StorageBackend::Irys => {
+            let signer = match &storage.irys.keypair_path {
+                Some(path) => load_keypair_from_path(path)?,
+                None => config.load_payer()?,
+            };
+            Arc::new(IrysStore::new(&storage.irys, signer))
+        }
+        StorageBackend::S3 => Arc::new(S3Store::new(&storage.s3)?),
+        StorageBackend::Filesystem => Arc::new(FilesystemStore::new(&storage.filesystem)?),
+    })
+}
+
+/// File extensions used by [`content_name`], by content type.
+const EXTENSIONS: [(&str, &str); 6] = [
+    ("application/json", "json"),
+    ("image/png", "png"),
+    ("image/webp", "webp"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/svg+xml", "svg"),
+];
+
+/// Name for content-addressed object stores: the SHA-256 of the content plus
+/// an extension matching its type, so identical uploads share one object.
+fn content_name(content_type: &str, bytes: &[u8]) -> String {
+    let extension = EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == content_type)
+        .map_or("bin", |(_, extension)| extension);
+    format!("{}.{}", hex::encode(Sha256::digest(bytes)), extension)
+}
+
+/// Content type of an object named by [`content_name`].
+fn content_type_of(name: &str) -> Option<&'static str> {
+    let extension = name.rsplit_once('.')?.1;
+    EXTENSIONS
+        .iter()
+        .find(|(_, known)| *known == extension)
+        .map(|(content_type, _)| *content_type)
+}
+
+/// Whether `name` has the shape [`content_name`] gives: a hex SHA-256 and an
+/// extension.
+fn is_content_name(name: &str) -> bool {
+    name.split_once('.')
+        .is_some_and(|(hash, _)| hash.len() == 64 && hash.bytes().all(|c| c.is_ascii_hexdigit()))
+}
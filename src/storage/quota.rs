@@ -0,0 +1,66 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+
+use crate::config::QuotaConfig;
+
+/// Sliding-window upload limits per wallet address.
+pub struct UploadQuota {
+    limits: QuotaConfig,
+    /// Timestamp and size of each upload inside the current window.
+    usage: Mutex<HashMap<String, VecDeque<(i64, usize)>>>,
+}
+
+impl UploadQuota {
+    pub fn new(limits: QuotaConfig) -> Self {
+        Self {
+            limits,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts an upload of `size` bytes against `owner`, or explains which
+    /// limit it would break.
+    pub fn charge(&self, owner: &str, size: usize) -> Result<()> {
+        let limits = &self.limits;
+        if size > limits.max_upload_bytes {
+            return Err(anyhow!(
+                "Upload of {} bytes exceeds the {} byte limit",
+                size,
+                limits.max_upload_bytes
+            ));
+        }
+
+        let now = Utc::now().timestamp();
+        let window_start = now - limits.window_secs as i64;
+        let mut usage = self.usage.lock().unwrap();
+        usage.retain(|_, uploads| uploads.back().is_some_and(|(at, _)| *at > window_start));
+
+        let uploads = usage.entry(owner.to_string()).or_default();
+        while uploads.front().is_some_and(|(at, _)| *at <= window_start) {
+            uploads.pop_front();
+        }
+        if uploads.len() >= limits.max_uploads_per_window {
+            return Err(anyhow!(
+                "Upload limit reached: at most {} uploads per {} seconds",
+                limits.max_uploads_per_window,
+                limits.window_secs
+            ));
+        }
+        let used: usize = uploads.iter().map(|(_, bytes)| bytes).sum();
+        if used + size > limits.max_bytes_per_window {
+            return Err(anyhow!(
+                "Upload limit reached: at most {} bytes per {} seconds",
+                limits.max_bytes_per_window,
+                limits.window_secs
+            ));
+        }
+
+        uploads.push_back((now, size));
+        Ok(())
+    }
+}
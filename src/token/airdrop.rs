@@ -0,0 +1,873 @@
+//! Distributing a token to many wallets from a CSV of `recipient,amount`
+//! rows. Recipients are packed into as few transactions as fit, creating
+//! their token accounts where missing. Every row's progress is written to a
+//! state file before and after its transaction is sent, so a crashed run can
+//! be started again without paying anyone twice.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    system_program,
+    transaction::TransactionError,
+};
+use solana_transaction_status::TransactionStatus;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+use super::{
+    lookup_table::{create_lookup_table, load_lookup_table, wait_until_usable},
+    ops::{format_amount, parse_amount, MintAccount},
+    signer::{sign_transaction, TransactionSigner},
+    transaction::{compile_message, confirm_signature, transaction_size, CONFIRM_POLL_INTERVAL, MAX_TRANSACTION_SIZE},
+};
+
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Accounts fetched per `getMultipleAccounts` call, the RPC maximum.
+const ACCOUNTS_PER_REQUEST: usize = 100;
+/// Signatures looked up per `getSignatureStatuses` call, the RPC maximum.
+const SIGNATURES_PER_REQUEST: usize = 256;
+
+/// How recipients are paid.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistributionMode {
+    /// `transfer_checked` from the payer's token account.
+    Transfer,
+    /// `mint_to_checked`; the payer must be the mint authority.
+    Mint,
+}
+
+/// One CSV row.
+#[derive(Clone, Debug)]
+pub struct Recipient {
+    /// 1-based line in the CSV.
+    pub line: usize,
+    pub wallet: Pubkey,
+    /// Base units.
+    pub amount: u64,
+}
+
+/// Reads `recipient,amount` rows, amounts in whole tokens. A header row and
+/// blank lines are skipped. Every invalid row is reported, not just the
+/// first, and a wallet listed twice is an error rather than paid twice.
+pub fn read_recipients(path: &Path, decimals: u8) -> Result<Vec<Recipient>> {
+    let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut recipients = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen: HashMap<Pubkey, usize> = HashMap::new();
+    for (i, row) in contents.lines().enumerate() {
+        let line = i + 1;
+        let fields: Vec<&str> = row.split(',').map(|field| field.trim().trim_matches('"').trim()).collect();
+        if fields.iter().all(|field| field.is_empty()) {
+            continue;
+        }
+        if line == 1 && fields[0].parse::<Pubkey>().is_err() && fields.get(1).map_or(true, |f| f.parse::<f64>().is_err()) {
+            continue;
+        }
+        let [wallet, amount] = fields[..] else {
+            errors.push(format!("line {}: expected `recipient,amount`", line));
+            continue;
+        };
+        let wallet: Pubkey = match wallet.parse() {
+            Ok(wallet) => wallet,
+            Err(_) => {
+                errors.push(format!("line {}: invalid address {:?}", line, wallet));
+                continue;
+            }
+        };
+        let amount = match parse_amount(amount, decimals) {
+            Ok(0) => {
+                errors.push(format!("line {}: amount must be greater than zero", line));
+                continue;
+            }
+            Ok(amount) => amount,
+            Err(e) => {
+                errors.push(format!("line {}: {}", line, e));
+                continue;
+            }
+        };
+        if let Some(first) = seen.insert(wallet, line) {
+            errors.push(format!("line {}: {} is already listed on line {}", line, wallet, first));
+            continue;
+        }
+        recipients.push(Recipient { line, wallet, amount });
+    }
+    if !errors.is_empty() {
+        return Err(anyhow!("{} invalid row(s) in {}:\n{}", errors.len(), path.display(), errors.join("\n")));
+    }
+    Ok(recipients)
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentState {
+    /// Signed and about to be sent; whether it landed is not known yet.
+    Pending,
+    Confirmed,
+    Failed,
+}
+
+/// Latest known state of one recipient's payment.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PaymentRecord {
+    pub recipient: String,
+    pub amount: u64,
+    pub state: PaymentState,
+    pub signature: Option<String>,
+    /// Blockhash the pending transaction was signed with, to tell whether it
+    /// can still land.
+    pub blockhash: Option<String>,
+    pub error: Option<String>,
+    pub updated_at: i64,
+}
+
+/// First line of a state file: the distribution its records belong to.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct StateHeader {
+    mint: String,
+    cluster: String,
+}
+
+/// Append-only JSON Lines log of payment records, after a [`StateHeader`];
+/// the last record for a recipient wins.
+pub struct AirdropState {
+    records: Mutex<HashMap<String, PaymentRecord>>,
+    file: Mutex<File>,
+}
+
+impl AirdropState {
+    /// Opens or creates the state file at `path` for paying out `mint` on
+    /// `cluster`. Fails when the file belongs to another mint or cluster,
+    /// whose payments must not count as made. A torn last line, left by a
+    /// crash mid-write, is ignored.
+    pub fn open(path: &Path, mint: &Pubkey, cluster: &str) -> Result<Self> {
+        let expected = StateHeader { mint: mint.to_string(), cluster: cluster.to_string() };
+        let mut header = None;
+        let mut records = HashMap::new();
+        match File::open(path) {
+            Ok(file) => {
+                let mut lines = BufReader::new(file).lines();
+                if let Some(line) = lines.next() {
+                    let line = line?;
+                    let found = serde_json::from_str::<StateHeader>(&line)
+                        .map_err(|_| anyhow!("{} does not say which mint and cluster it records", path.display()))?;
+                    if found != expected {
+                        return Err(anyhow!(
+                            "{} records an airdrop of {} on {}, not {} on {}; pass another --state file",
+                            path.display(),
+                            found.mint,
+                            found.cluster,
+                            expected.mint,
+                            expected.cluster
+                        ));
+                    }
+                    header = Some(found);
+                }
+                for line in lines {
+                    if let Ok(record) = serde_json::from_str::<PaymentRecord>(&line?) {
+                        records.insert(record.recipient.clone(), record);
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(anyhow!("Failed to read airdrop state {}: {}", path.display(), e)),
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open airdrop state {}", path.display()))?;
+        if header.is_none() {
+            writeln!(file, "{}", serde_json::to_string(&expected)?)?;
+            file.sync_data()?;
+        }
+        Ok(Self { records: Mutex::new(records), file: Mutex::new(file) })
+    }
+
+    /// `recipients.csv` -> `recipients.state.jsonl`.
+    pub fn path_for(csv: &Path) -> PathBuf {
+        csv.with_extension("state.jsonl")
+    }
+
+    pub fn get(&self, recipient: &Pubkey) -> Option<PaymentRecord> {
+        self.records.lock().unwrap().get(&recipient.to_string()).cloned()
+    }
+
+    fn pending(&self) -> Vec<PaymentRecord> {
+        let records = self.records.lock().unwrap();
+        records.values().filter(|record| record.state == PaymentState::Pending).cloned().collect()
+    }
+
+    /// Writes and syncs `records` before returning.
+    fn record(&self, records: &[PaymentRecord]) -> Result<()> {
+        let mut lines = String::new();
+        for record in records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        let mut file = self.file.lock().unwrap();
+        file.write_all(lines.as_bytes())?;
+        file.sync_data()?;
+        drop(file);
+        let mut known = self.records.lock().unwrap();
+        for record in records {
+            known.insert(record.recipient.clone(), record.clone());
+        }
+        Ok(())
+    }
+
+    fn update(
+        &self,
+        rows: &[Recipient],
+        state: PaymentState,
+        signature: Option<&Signature>,
+        blockhash: Option<&Hash>,
+        error: Option<String>,
+    ) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let records: Vec<PaymentRecord> = rows
+            .iter()
+            .map(|row| PaymentRecord {
+                recipient: row.wallet.to_string(),
+                amount: row.amount,
+                state,
+                signature: signature.map(|signature| signature.to_string()),
+                blockhash: blockhash.map(|hash| hash.to_string()),
+                error: error.clone(),
+                updated_at: now,
+            })
+            .collect();
+        self.record(&records)
+    }
+}
+
+/// Settles payments a previous run left pending: confirmed ones are marked
+/// paid, and ones that can no longer land are marked failed so they are
+/// retried. Transaction history is searched, so a run resumed long after its
+/// crash still sees what landed. A payment is only marked failed once its
+/// blockhash has expired and a last look at the history still finds
+/// nothing; on an RPC error the payments stay pending for the next run.
+pub async fn settle_pending(client: &RpcClient, state: &AirdropState) -> Result<()> {
+    let pending = state.pending();
+    let mut by_signature: HashMap<String, Vec<PaymentRecord>> = HashMap::new();
+    for record in pending {
+        by_signature.entry(record.signature.clone().unwrap_or_default()).or_default().push(record);
+    }
+    let signatures: Vec<String> = by_signature.keys().cloned().collect();
+    for chunk in signatures.chunks(SIGNATURES_PER_REQUEST) {
+        let parsed = chunk
+            .iter()
+            .map(|signature| signature.parse::<Signature>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("Corrupt airdrop state: {}", e))?;
+        let statuses = client.get_signature_statuses_with_history(&parsed).await?.value;
+        for ((signature, text), status) in parsed.iter().zip(chunk).zip(statuses) {
+            let records = &by_signature[text];
+            let settlement = match settlement(status.as_ref()) {
+                Settlement::Unknown => {
+                    let blockhash: Hash = records[0]
+                        .blockhash
+                        .as_deref()
+                        .unwrap_or_default()
+                        .parse()
+                        .map_err(|e| anyhow!("Corrupt airdrop state: {}", e))?;
+                    wait_for_settlement(client, signature, &blockhash).await?
+                }
+                settlement => settlement,
+            };
+            let now = Utc::now().timestamp();
+            let settled: Vec<PaymentRecord> = records
+                .iter()
+                .map(|record| {
+                    let mut record = record.clone();
+                    record.updated_at = now;
+                    match &settlement {
+                        Settlement::Landed => record.state = PaymentState::Confirmed,
+                        Settlement::Rejected(e) => {
+                            record.state = PaymentState::Failed;
+                            record.error = Some(format!("Transaction {} failed: {}", signature, e));
+                        }
+                        Settlement::Expired => {
+                            record.state = PaymentState::Failed;
+                            record.error = Some(format!("Transaction {} expired before it landed", signature));
+                        }
+                        Settlement::Unknown => {}
+                    }
+                    record
+                })
+                .collect();
+            state.record(&settled)?;
+        }
+    }
+    Ok(())
+}
+
+/// What became of a pending payment.
+enum Settlement {
+    Landed,
+    /// Landed with an error, so nothing was paid.
+    Rejected(TransactionError),
+    /// Never landed and no longer can.
+    Expired,
+    /// Not seen yet, or seen only at `processed` where it may still be
+    /// dropped with its fork.
+    Unknown,
+}
+
+fn settlement(status: Option<&TransactionStatus>) -> Settlement {
+    match status {
+        Some(status) if status.satisfies_commitment(CommitmentConfig::confirmed()) => match &status.status {
+            Ok(()) => Settlement::Landed,
+            Err(e) => Settlement::Rejected(e.clone()),
+        },
+        _ => Settlement::Unknown,
+    }
+}
+
+/// Waits until `signature` is confirmed or `blockhash` has expired, then
+/// reads the history once more: a payment that lands between the two checks
+/// must not be taken for an expired one. The expiry is judged at
+/// `finalized`, after which no new block can include the transaction.
+async fn wait_for_settlement(client: &RpcClient, signature: &Signature, blockhash: &Hash) -> Result<Settlement> {
+    loop {
+        let status = client.get_signature_statuses_with_history(&[*signature]).await?.value.remove(0);
+        if let settled @ (Settlement::Landed | Settlement::Rejected(_)) = settlement(status.as_ref()) {
+            return Ok(settled);
+        }
+        if !client.is_blockhash_valid(blockhash, CommitmentConfig::finalized()).await? {
+            break;
+        }
+        tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+    }
+    let status = client.get_signature_statuses_with_history(&[*signature]).await?.value.remove(0);
+    Ok(match status {
+        None => Settlement::Expired,
+        Some(status) => settlement(Some(&status)),
+    })
+}
+
+/// Recipients still to pay, packed into transactions.
+#[derive(Debug)]
+pub struct AirdropPlan {
+    pub mode: DistributionMode,
+    pub transactions: Vec<PlannedTransaction>,
+    /// Rows an earlier run already paid.
+    pub already_paid: Vec<Recipient>,
+    /// Token accounts that will be created.
+    pub accounts_to_create: usize,
+    pub lookup_table: Option<LookupTablePlan>,
+}
+
+/// Whether a distribution loads its repeated accounts from a lookup table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LookupTableUse {
+    /// Create one when it saves transactions.
+    Auto,
+    Never,
+    Existing(Pubkey),
+}
+
+#[derive(Clone, Debug)]
+pub enum LookupTablePlan {
+    Existing(AddressLookupTableAccount),
+    /// Created with these addresses when the run starts.
+    Create(Vec<Pubkey>),
+}
+
+#[derive(Clone, Debug)]
+pub struct PlannedTransaction {
+    pub recipients: Vec<Recipient>,
+    instructions: Vec<Instruction>,
+}
+
+impl AirdropPlan {
+    pub fn unpaid(&self) -> impl Iterator<Item = &Recipient> {
+        self.transactions.iter().flat_map(|transaction| &transaction.recipients)
+    }
+}
+
+/// Works out what is left to pay. Fails when a row was already paid a
+/// different amount, and, for transfers, when the payer's balance does not
+/// cover the rest. Call [`settle_pending`] first.
+pub async fn plan_airdrop(
+    client: &RpcClient,
+    mint: &MintAccount,
+    payer: &Pubkey,
+    mode: DistributionMode,
+    recipients: Vec<Recipient>,
+    state: &AirdropState,
+    lookup_table: LookupTableUse,
+) -> Result<AirdropPlan> {
+    let mut already_paid = Vec::new();
+    let mut unpaid = Vec::new();
+    let mut conflicts = Vec::new();
+    for recipient in recipients {
+        match state.get(&recipient.wallet) {
+            Some(record) if record.state == PaymentState::Confirmed => {
+                if record.amount != recipient.amount {
+                    conflicts.push(format!(
+                        "line {}: {} was already paid {}, but the CSV now says {}",
+                        recipient.line,
+                        recipient.wallet,
+                        format_amount(record.amount, mint.decimals),
+                        format_amount(recipient.amount, mint.decimals)
+                    ));
+                }
+                already_paid.push(recipient);
+            }
+            Some(record) if record.state == PaymentState::Pending => {
+                return Err(anyhow!("Payment to {} is still pending; settle it before planning", recipient.wallet));
+            }
+            _ => unpaid.push(recipient),
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(anyhow!("The CSV disagrees with payments already made:\n{}", conflicts.join("\n")));
+    }
+
+    match mode {
+        DistributionMode::Mint => {
+            if mint.mint_authority != Some(*payer) {
+                return Err(anyhow!("{} is not the mint authority of {}", payer, mint.address));
+            }
+        }
+        DistributionMode::Transfer => {
+            let needed = unpaid.iter().try_fold(0u64, |total, recipient| total.checked_add(recipient.amount));
+            let needed = needed.ok_or_else(|| anyhow!("Total amount overflows"))?;
+            let source = mint.token_account(payer);
+            let exists = client.get_account_with_commitment(&source, client.commitment()).await?.value.is_some();
+            let balance = if exists {
+                client.get_token_account_balance(&source).await?.amount.parse::<u64>()?
+            } else {
+                0
+            };
+            if !unpaid.is_empty() && balance < needed {
+                return Err(anyhow!(
+                    "{} holds {} but the remaining rows need {}",
+                    payer,
+                    format_amount(balance, mint.decimals),
+                    format_amount(needed, mint.decimals)
+                ));
+            }
+        }
+    }
+
+    let missing = missing_accounts(client, mint, &unpaid).await?;
+    let payments = unpaid
+        .into_iter()
+        .map(|recipient| {
+            let instructions = payment_instructions(mint, payer, mode, &recipient, missing.contains(&recipient.wallet))?;
+            Ok((recipient, instructions))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (transactions, lookup_table) = match lookup_table {
+        LookupTableUse::Never => (pack(&payments, payer, &[])?, None),
+        LookupTableUse::Existing(address) => {
+            let table = load_lookup_table(client, &address).await?;
+            (pack(&payments, payer, &[table.clone()])?, Some(LookupTablePlan::Existing(table)))
+        }
+        LookupTableUse::Auto => {
+            let legacy = pack(&payments, payer, &[])?;
+            // Only the addresses matter for sizing, so the table's own
+            // address can be a placeholder until it is created.
+            let addresses = lookup_table_addresses(mint, payer, mode);
+            let table = AddressLookupTableAccount { key: Pubkey::default(), addresses: addresses.clone() };
+            let with_table = if legacy.len() > 1 { pack(&payments, payer, &[table])? } else { Vec::new() };
+            if !with_table.is_empty() && with_table.len() < legacy.len() {
+                (with_table, Some(LookupTablePlan::Create(addresses)))
+            } else {
+                (legacy, None)
+            }
+        }
+    };
+    Ok(AirdropPlan { mode, transactions, already_paid, accounts_to_create: missing.len(), lookup_table })
+}
+
+/// Packs payments, in order, into as few transactions as fit.
+fn pack(
+    payments: &[(Recipient, Vec<Instruction>)],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Vec<PlannedTransaction>> {
+    let mut transactions = Vec::new();
+    let mut current = PlannedTransaction { recipients: Vec::new(), instructions: Vec::new() };
+    for (recipient, instructions) in payments {
+        let candidate: Vec<Instruction> = current.instructions.iter().chain(instructions).cloned().collect();
+        if transaction_size(&candidate, payer, lookup_tables)? > MAX_TRANSACTION_SIZE {
+            if current.recipients.is_empty() {
+                return Err(anyhow!("line {}: payment does not fit in a transaction", recipient.line));
+            }
+            let next = PlannedTransaction { recipients: Vec::new(), instructions: instructions.clone() };
+            transactions.push(std::mem::replace(&mut current, next));
+        } else {
+            current.instructions = candidate;
+        }
+        current.recipients.push(recipient.clone());
+    }
+    if !current.recipients.is_empty() {
+        transactions.push(current);
+    }
+    Ok(transactions)
+}
+
+/// Accounts every payment repeats that a lookup table can hold. The payer
+/// signs and the token programs are invoked, so those stay in the message.
+fn lookup_table_addresses(mint: &MintAccount, payer: &Pubkey, mode: DistributionMode) -> Vec<Pubkey> {
+    let mut addresses = vec![mint.address, system_program::id()];
+    if mode == DistributionMode::Transfer {
+        addresses.push(mint.token_account(payer));
+    }
+    addresses
+}
+
+/// Recipients whose associated token account does not exist yet.
+async fn missing_accounts(client: &RpcClient, mint: &MintAccount, recipients: &[Recipient]) -> Result<HashSet<Pubkey>> {
+    let mut missing = HashSet::new();
+    for chunk in recipients.chunks(ACCOUNTS_PER_REQUEST) {
+        let addresses: Vec<Pubkey> = chunk.iter().map(|recipient| mint.token_account(&recipient.wallet)).collect();
+        let accounts = client.get_multiple_accounts(&addresses).await?;
+        for (recipient, account) in chunk.iter().zip(accounts) {
+            if account.is_none() {
+                missing.insert(recipient.wallet);
+            }
+        }
+    }
+    Ok(missing)
+}
+
+fn payment_instructions(
+    mint: &MintAccount,
+    payer: &Pubkey,
+    mode: DistributionMode,
+    recipient: &Recipient,
+    create_account: bool,
+) -> Result<Vec<Instruction>> {
+    let destination = mint.token_account(&recipient.wallet);
+    let mut instructions = Vec::with_capacity(2);
+    if create_account {
+        instructions.push(create_associated_token_account_idempotent(payer, &recipient.wallet, &mint.address, &mint.program_id));
+    }
+    instructions.push(match mode {
+        DistributionMode::Transfer => spl_token_2022::instruction::transfer_checked(
+            &mint.program_id,
+            &mint.token_account(payer),
+            &mint.address,
+            &destination,
+            payer,
+            &[],
+            recipient.amount,
+            mint.decimals,
+        )?,
+        DistributionMode::Mint => spl_token_2022::instruction::mint_to_checked(
+            &mint.program_id,
+            &mint.address,
+            &destination,
+            payer,
+            &[],
+            recipient.amount,
+            mint.decimals,
+        )?,
+    });
+    Ok(instructions)
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RowStatus {
+    Paid,
+    /// Paid by an earlier run.
+    Skipped,
+    Failed,
+}
+
+impl RowStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Paid => "paid",
+            Self::Skipped => "skipped",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RowOutcome {
+    pub line: usize,
+    pub recipient: String,
+    /// Whole tokens.
+    pub amount: String,
+    pub status: RowStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Reconciliation of the CSV against what has been paid, in CSV order.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AirdropReport {
+    pub mint: String,
+    pub mode: DistributionMode,
+    pub paid: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub transactions: usize,
+    pub accounts_created: usize,
+    /// Lookup table the transactions loaded accounts from.
+    pub lookup_table: Option<String>,
+    /// Whether this run created `lookup_table`; its rent is returned once it
+    /// is deactivated and closed.
+    pub lookup_table_created: bool,
+    /// Whole tokens listed in the CSV.
+    pub total_requested: String,
+    /// Whole tokens received by recipients, this run and earlier ones.
+    pub total_distributed: String,
+    /// Whole tokens still owed to failed rows.
+    pub total_outstanding: String,
+    pub rows: Vec<RowOutcome>,
+}
+
+impl AirdropReport {
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("line,recipient,amount,status,signature,error\n");
+        for row in &self.rows {
+            let fields = [
+                row.line.to_string(),
+                row.recipient.clone(),
+                row.amount.clone(),
+                row.status.label().to_string(),
+                row.signature.clone().unwrap_or_default(),
+                row.error.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Writes CSV for a `.csv` path and JSON otherwise.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => self.to_csv(),
+            _ => serde_json::to_string_pretty(self)?,
+        };
+        fs::write(path, contents).with_context(|| format!("Failed to write report {}", path.display()))
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Sends every planned transaction, `concurrency` at a time, and reconciles
+/// the result. A failed transaction fails only its own rows; running again
+/// retries them. `on_transaction` is called as each one finishes, and
+/// payments whose confirmation was interrupted are settled before the
+/// report is built.
+pub async fn run_airdrop(
+    client: &RpcClient,
+    mint: &MintAccount,
+    payer: &dyn TransactionSigner,
+    plan: &AirdropPlan,
+    state: &AirdropState,
+    concurrency: usize,
+    on_transaction: impl Fn(&PlannedTransaction, &Result<Signature>),
+) -> Result<AirdropReport> {
+    let table = match &plan.lookup_table {
+        None => None,
+        Some(LookupTablePlan::Existing(table)) => Some(table.clone()),
+        Some(LookupTablePlan::Create(addresses)) => {
+            let (key, _) = create_lookup_table(client, payer, addresses).await?;
+            wait_until_usable(client, &key).await?;
+            Some(AddressLookupTableAccount { key, addresses: addresses.clone() })
+        }
+    };
+    let lookup_tables: &[AddressLookupTableAccount] = table.as_slice();
+    let mut results = stream::iter(&plan.transactions)
+        .map(|transaction| async move { (transaction, pay(client, payer, transaction, lookup_tables, state).await) })
+        .buffer_unordered(concurrency.max(1));
+    let mut accounts_created = 0;
+    let mut sent = 0;
+    while let Some((transaction, result)) = results.next().await {
+        let outcome = result?;
+        sent += 1;
+        if outcome.is_ok() {
+            accounts_created += transaction.instructions.len() - transaction.recipients.len();
+        }
+        on_transaction(transaction, &outcome);
+    }
+    settle_pending(client, state).await?;
+
+    let mut rows: Vec<(&Recipient, bool)> = plan.already_paid.iter().map(|row| (row, true)).collect();
+    rows.extend(plan.unpaid().map(|row| (row, false)));
+    rows.sort_by_key(|(row, _)| row.line);
+    let (mut requested, mut distributed, mut outstanding) = (0u64, 0u64, 0u64);
+    let mut report = AirdropReport {
+        mint: mint.address.to_string(),
+        mode: plan.mode,
+        paid: 0,
+        skipped: 0,
+        failed: 0,
+        transactions: sent,
+        accounts_created,
+        lookup_table: table.as_ref().map(|table| table.key.to_string()),
+        lookup_table_created: matches!(plan.lookup_table, Some(LookupTablePlan::Create(_))),
+        total_requested: String::new(),
+        total_distributed: String::new(),
+        total_outstanding: String::new(),
+        rows: Vec::with_capacity(rows.len()),
+    };
+    for (row, earlier) in rows {
+        let record = state.get(&row.wallet);
+        let confirmed = record.as_ref().is_some_and(|record| record.state == PaymentState::Confirmed);
+        let status = match (confirmed, earlier) {
+            (true, true) => RowStatus::Skipped,
+            (true, false) => RowStatus::Paid,
+            (false, _) => RowStatus::Failed,
+        };
+        requested = requested.saturating_add(row.amount);
+        if confirmed {
+            distributed = distributed.saturating_add(row.amount);
+        } else {
+            outstanding = outstanding.saturating_add(row.amount);
+        }
+        match status {
+            RowStatus::Paid => report.paid += 1,
+            RowStatus::Skipped => report.skipped += 1,
+            RowStatus::Failed => report.failed += 1,
+        }
+        report.rows.push(RowOutcome {
+            line: row.line,
+            recipient: row.wallet.to_string(),
+            amount: format_amount(row.amount, mint.decimals),
+            status,
+            signature: record.as_ref().and_then(|record| record.signature.clone()),
+            error: record.and_then(|record| if confirmed { None } else { record.error }),
+        });
+    }
+    report.total_requested = format_amount(requested, mint.decimals);
+    report.total_distributed = format_amount(distributed, mint.decimals);
+    report.total_outstanding = format_amount(outstanding, mint.decimals);
+    Ok(report)
+}
+
+/// Sends one transaction. The outer error is a state-file failure, which
+/// stops the run; the inner one is this transaction's failure.
+async fn pay(
+    client: &RpcClient,
+    payer: &dyn TransactionSigner,
+    planned: &PlannedTransaction,
+    lookup_tables: &[AddressLookupTableAccount],
+    state: &AirdropState,
+) -> Result<Result<Signature>> {
+    let rows = &planned.recipients;
+    let prepared = async {
+        let blockhash = client.get_latest_blockhash().await?;
+        let message = compile_message(&planned.instructions, &payer.pubkey(), lookup_tables, blockhash)?;
+        let transaction = sign_transaction(message, &[payer]).await?;
+        let simulation = client.simulate_transaction(&transaction).await?.value;
+        if let Some(err) = simulation.err {
+            let logs = simulation.logs.unwrap_or_default().join("\n");
+            return Err(anyhow!("Simulation failed: {}\n{}", err, logs));
+        }
+        Ok((transaction, blockhash))
+    };
+    let (transaction, blockhash) = match prepared.await {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            state.update(rows, PaymentState::Failed, None, None, Some(format!("{:#}", e)))?;
+            return Ok(Err(e));
+        }
+    };
+
+    // Recorded before sending: from here on the payment may land, and a
+    // resumed run has to find out whether it did before paying again.
+    let signature = transaction.signatures[0];
+    state.update(rows, PaymentState::Pending, Some(&signature), Some(&blockhash), None)?;
+    let sent = client.send_transaction(&transaction).await;
+    match confirm_signature(client, &signature, &blockhash).await {
+        Ok(()) => {
+            state.update(rows, PaymentState::Confirmed, Some(&signature), None, None)?;
+            Ok(Ok(signature))
+        }
+        // Left pending: an RPC error here says nothing about whether the
+        // payment landed. `settle_pending` finds out.
+        Err(e) => Ok(Err(match sent {
+            Err(send_error) => anyhow!(send_error).context("Failed to send transaction"),
+            Ok(_) => e,
+        })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paid(path: &Path, mint: &Pubkey, wallet: Pubkey) {
+        let state = AirdropState::open(path, mint, "devnet").unwrap();
+        let row = Recipient { line: 1, wallet, amount: 5 };
+        state.update(&[row], PaymentState::Confirmed, Some(&Signature::default()), None, None).unwrap();
+    }
+
+    #[test]
+    fn resumes_the_same_mint_and_cluster() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("recipients.state.jsonl");
+        let (mint, wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+        paid(&path, &mint, wallet);
+
+        let state = AirdropState::open(&path, &mint, "devnet").unwrap();
+        let record = state.get(&wallet).unwrap();
+        assert_eq!((record.state, record.amount), (PaymentState::Confirmed, 5));
+    }
+
+    #[test]
+    fn refuses_state_from_another_mint_or_cluster() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("recipients.state.jsonl");
+        let mint = Pubkey::new_unique();
+        paid(&path, &mint, Pubkey::new_unique());
+
+        let error = AirdropState::open(&path, &Pubkey::new_unique(), "devnet").err().unwrap();
+        assert!(error.to_string().contains(&mint.to_string()), "{}", error);
+        let error = AirdropState::open(&path, &mint, "mainnet").err().unwrap();
+        assert!(error.to_string().contains("devnet"), "{}", error);
+    }
+
+    #[test]
+    fn refuses_state_without_a_header() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("recipients.state.jsonl");
+        let record = PaymentRecord {
+            recipient: Pubkey::new_unique().to_string(),
+            amount: 5,
+            state: PaymentState::Confirmed,
+            signature: None,
+            blockhash: None,
+            error: None,
+            updated_at: 0,
+        };
+        fs::write(&path, format!("{}\n", serde_json::to_string(&record).unwrap())).unwrap();
+        assert!(AirdropState::open(&path, &Pubkey::new_unique(), "devnet").is_err());
+    }
+}
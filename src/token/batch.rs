@@ -0,0 +1,494 @@
+//! Creating many tokens from one manifest. Entries are independent, so their
+//! transactions run in parallel. Every mint is appended to a ledger as soon
+//! as it exists and again once its metadata and supply are in place.
+//! Finished entries are skipped and unfinished ones completed on their
+//! existing mint, so an interrupted run can simply be started again.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+
+use super::{
+    create_token_reporting_mint, finish_token, ops, signer::TransactionSigner, CreateTokenParams, NetworkType,
+};
+
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub tokens: Vec<BatchEntry>,
+}
+
+/// One token to create. `id` identifies the entry in the ledger and must be
+/// unique within the manifest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BatchEntry {
+    pub id: String,
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub description: String,
+    pub uri: String,
+    #[serde(default = "default_decimals")]
+    pub decimals: u8,
+    /// Initial supply in whole tokens, minted to the payer.
+    #[serde(default = "default_supply")]
+    pub supply: String,
+    #[serde(default = "default_true")]
+    pub mutable: bool,
+    #[serde(default = "default_true")]
+    pub freeze_authority: bool,
+}
+
+fn default_decimals() -> u8 {
+    9
+}
+
+fn default_supply() -> String {
+    "0".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl BatchEntry {
+    pub fn params(&self, network: &NetworkType) -> Result<CreateTokenParams> {
+        let params = CreateTokenParams {
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            description: self.description.clone(),
+            metadata_uri: self.uri.clone(),
+            decimals: self.decimals,
+            initial_supply: ops::parse_amount(&self.supply, self.decimals)?,
+            is_mutable: self.mutable,
+            freeze_authority: self.freeze_authority,
+            rate_limit: None,
+            transfer_fee: None,
+            max_transfer_amount: None,
+            network: network.clone(),
+            payer: None,
+        };
+        params.validate()?;
+        crate::metadata::validate_metadata_uri(&self.uri)?;
+        Ok(params)
+    }
+}
+
+impl Manifest {
+    /// Reads a JSON, YAML or TOML manifest, by extension.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        let manifest: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&contents)?,
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents)?,
+            _ => toml::from_str(&contents)?,
+        };
+        Ok(manifest)
+    }
+
+    /// Checks every entry up front so a bad row fails the run before any
+    /// SOL is spent.
+    pub fn validate(&self, network: &NetworkType) -> Result<()> {
+        let mut ids = HashSet::new();
+        for (i, entry) in self.tokens.iter().enumerate() {
+            if entry.id.trim().is_empty() {
+                return Err(anyhow!("Entry {} has no id", i));
+            }
+            if !ids.insert(entry.id.as_str()) {
+                return Err(anyhow!("Duplicate entry id {:?}", entry.id));
+            }
+            entry.params(network).with_context(|| format!("Invalid entry {:?}", entry.id))?;
+        }
+        Ok(())
+    }
+}
+
+/// A token the ledger has recorded as created.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LedgerRecord {
+    pub id: String,
+    /// Cluster the mint was created on.
+    #[serde(default)]
+    pub network: String,
+    pub mint: String,
+    pub signature: String,
+    /// Every transaction sent for the entry; `signature` is the first.
+    #[serde(default)]
+    pub signatures: Vec<String>,
+    /// False while only the mint exists; the next run adds the rest.
+    #[serde(default = "default_true")]
+    pub complete: bool,
+    /// Lamports the payer spent on fees and rent across all of
+    /// `signatures`, when known.
+    pub cost_lamports: Option<u64>,
+    pub created_at: i64,
+}
+
+/// Append-only JSON Lines record of created entries, all on one cluster.
+pub struct Ledger {
+    network: String,
+    records: HashMap<String, LedgerRecord>,
+    file: Mutex<File>,
+}
+
+impl Ledger {
+    /// Opens or creates the ledger at `path` for creating tokens on
+    /// `network`. Fails when it records mints made on another cluster,
+    /// which must not count as created here. A torn last line, left by a
+    /// crash mid-write, is ignored.
+    pub fn open(path: &Path, network: &str) -> Result<Self> {
+        let mut records = HashMap::new();
+        match File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    if let Ok(record) = serde_json::from_str::<LedgerRecord>(&line?) {
+                        if record.network != network {
+                            return Err(anyhow!(
+                                "Ledger {} records {} as created on {}, not {}; use another ledger",
+                                path.display(),
+                                record.id,
+                                if record.network.is_empty() { "an unknown cluster" } else { &record.network },
+                                network
+                            ));
+                        }
+                        records.insert(record.id.clone(), record);
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(anyhow!("Failed to read ledger {}: {}", path.display(), e)),
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open ledger {}", path.display()))?;
+        Ok(Self { network: network.to_string(), records, file: Mutex::new(file) })
+    }
+
+    /// `tokens.json` -> `tokens.ledger.jsonl`.
+    pub fn path_for(manifest: &Path) -> PathBuf {
+        manifest.with_extension("ledger.jsonl")
+    }
+
+    pub fn get(&self, id: &str) -> Option<&LedgerRecord> {
+        self.records.get(id)
+    }
+
+    fn append(&self, record: &LedgerRecord) -> Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryStatus {
+    Created,
+    /// Already in the ledger from an earlier run.
+    Skipped,
+    Failed,
+}
+
+impl EntryStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Skipped => "skipped",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntryOutcome {
+    pub id: String,
+    pub status: EntryStatus,
+    pub mint: Option<String>,
+    pub signature: Option<String>,
+    /// Every transaction sent for the entry; `signature` is the first.
+    #[serde(default)]
+    pub signatures: Vec<String>,
+    pub cost_lamports: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl EntryOutcome {
+    fn from_record(record: &LedgerRecord, status: EntryStatus) -> Self {
+        Self {
+            id: record.id.clone(),
+            status,
+            mint: Some(record.mint.clone()),
+            signature: Some(record.signature.clone()),
+            signatures: record.signatures.clone(),
+            cost_lamports: record.cost_lamports,
+            error: None,
+        }
+    }
+}
+
+/// Outcome of every manifest entry, in manifest order.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BatchSummary {
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Lamports spent on the entries this run created or finished; skipped
+    /// entries are not counted.
+    pub total_cost_lamports: u64,
+    pub entries: Vec<EntryOutcome>,
+}
+
+impl BatchSummary {
+    /// Adds one finished entry to the counts.
+    pub fn push(&mut self, outcome: EntryOutcome) {
+        match outcome.status {
+            EntryStatus::Created => {
+                self.created += 1;
+                self.total_cost_lamports += outcome.cost_lamports.unwrap_or(0);
+            }
+            EntryStatus::Skipped => self.skipped += 1,
+            EntryStatus::Failed => self.failed += 1,
+        }
+        self.entries.push(outcome);
+    }
+
+    /// Puts the entries back in manifest order.
+    fn sort(&mut self, order: &[BatchEntry]) {
+        let position: HashMap<&str, usize> = order.iter().enumerate().map(|(i, entry)| (entry.id.as_str(), i)).collect();
+        self.entries.sort_by_key(|outcome| position.get(outcome.id.as_str()).copied());
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("id,status,mint,signature,cost_lamports,error\n");
+        for outcome in &self.entries {
+            let fields = [
+                outcome.id.clone(),
+                outcome.status.label().to_string(),
+                outcome.mint.clone().unwrap_or_default(),
+                outcome.signature.clone().unwrap_or_default(),
+                outcome.cost_lamports.map(|cost| cost.to_string()).unwrap_or_default(),
+                outcome.error.clone().unwrap_or_default(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Writes CSV for a `.csv` path and JSON otherwise.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => self.to_csv(),
+            _ => serde_json::to_string_pretty(self)?,
+        };
+        fs::write(path, contents).with_context(|| format!("Failed to write summary {}", path.display()))
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Creates every entry of `manifest` not yet in `ledger`, `concurrency` at a
+/// time. Failures are reported per entry and do not stop the run.
+/// `on_outcome` is called as each entry finishes, after the ledger write.
+pub async fn run_batch(
+    manifest: &Manifest,
+    ledger: &Ledger,
+    rpc_url: &str,
+    network: &NetworkType,
+    payer: Arc<dyn TransactionSigner>,
+    concurrency: usize,
+    on_outcome: impl Fn(&BatchEntry, &EntryOutcome),
+) -> BatchSummary {
+    let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
+    let mut summary = BatchSummary::default();
+    let mut pending = Vec::new();
+    for entry in &manifest.tokens {
+        match ledger.get(&entry.id) {
+            Some(record) if record.complete => {
+                let outcome = EntryOutcome::from_record(record, EntryStatus::Skipped);
+                on_outcome(entry, &outcome);
+                summary.push(outcome);
+            }
+            started => pending.push((entry, started)),
+        }
+    }
+
+    let client = &client;
+    let mut results = stream::iter(pending)
+        .map(|(entry, started)| {
+            let payer = payer.clone();
+            async move { (entry, create_entry(client, rpc_url, network, ledger, entry, started, payer).await) }
+        })
+        .buffer_unordered(concurrency.max(1));
+    while let Some((entry, result)) = results.next().await {
+        let outcome = match result.and_then(|record| ledger.append(&record).map(|()| record)) {
+            Ok(record) => EntryOutcome::from_record(&record, EntryStatus::Created),
+            Err(e) => EntryOutcome {
+                id: entry.id.clone(),
+                status: EntryStatus::Failed,
+                mint: None,
+                signature: None,
+                signatures: Vec::new(),
+                cost_lamports: None,
+                error: Some(format!("{:#}", e)),
+            },
+        };
+        on_outcome(entry, &outcome);
+        summary.push(outcome);
+    }
+    summary.sort(&manifest.tokens);
+    summary
+}
+
+/// Creates `entry`, or completes the mint `started` when an earlier run
+/// created it but stopped before its metadata or supply landed.
+async fn create_entry(
+    client: &RpcClient,
+    rpc_url: &str,
+    network: &NetworkType,
+    ledger: &Ledger,
+    entry: &BatchEntry,
+    started: Option<&LedgerRecord>,
+    payer: Arc<dyn TransactionSigner>,
+) -> Result<LedgerRecord> {
+    let mut params = entry.params(network)?;
+    params.payer = Some(payer);
+    let (mint, signatures) = match started {
+        Some(started) => {
+            let mint: Pubkey = started.mint.parse()?;
+            let finished = finish_token(client, params, &mint)
+                .await
+                .with_context(|| format!("Failed to finish mint {} from an earlier run", mint))?;
+            let mut signatures = started.signatures.clone();
+            signatures.extend(finished.iter().map(ToString::to_string));
+            (started.mint.clone(), signatures)
+        }
+        None => {
+            let record_mint = |mint: &Pubkey, signature: &Signature| {
+                ledger.append(&LedgerRecord {
+                    id: entry.id.clone(),
+                    network: ledger.network.clone(),
+                    mint: mint.to_string(),
+                    signature: signature.to_string(),
+                    signatures: vec![signature.to_string()],
+                    complete: false,
+                    cost_lamports: None,
+                    created_at: Utc::now().timestamp(),
+                })
+            };
+            let result = create_token_reporting_mint(params, rpc_url, |_| {}, record_mint).await?;
+            (result.mint, result.signatures)
+        }
+    };
+    Ok(LedgerRecord {
+        id: entry.id.clone(),
+        network: ledger.network.clone(),
+        mint,
+        signature: signatures[0].clone(),
+        cost_lamports: payer_cost(client, &signatures).await,
+        signatures,
+        complete: true,
+        created_at: Utc::now().timestamp(),
+    })
+}
+
+/// What the fee payer spent across `signatures`, rent included; `None` if
+/// any of them cannot be read.
+async fn payer_cost(client: &RpcClient, signatures: &[String]) -> Option<u64> {
+    let mut total = 0u64;
+    for signature in signatures {
+        total = total.checked_add(transaction_cost(client, &signature.parse().ok()?).await?)?;
+    }
+    Some(total)
+}
+
+/// What the fee payer (always the first account) spent in a confirmed
+/// transaction, rent included.
+async fn transaction_cost(client: &RpcClient, signature: &Signature) -> Option<u64> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+    let transaction = client.get_transaction_with_config(signature, config).await.ok()?;
+    let meta = transaction.transaction.meta?;
+    meta.pre_balances.first()?.checked_sub(*meta.post_balances.first()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created(ledger: &Ledger, id: &str) -> LedgerRecord {
+        LedgerRecord {
+            id: id.to_string(),
+            network: ledger.network.clone(),
+            mint: Pubkey::new_unique().to_string(),
+            signature: "first".to_string(),
+            signatures: vec!["first".to_string(), "second".to_string()],
+            complete: true,
+            cost_lamports: None,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn resumes_on_the_same_network() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("tokens.ledger.jsonl");
+        let ledger = Ledger::open(&path, "devnet").unwrap();
+        let record = created(&ledger, "gold");
+        ledger.append(&record).unwrap();
+
+        let ledger = Ledger::open(&path, "devnet").unwrap();
+        let found = ledger.get("gold").unwrap();
+        assert_eq!((&found.mint, &found.signatures), (&record.mint, &record.signatures));
+        let outcome = EntryOutcome::from_record(found, EntryStatus::Skipped);
+        assert_eq!(outcome.signatures, ["first", "second"]);
+    }
+
+    #[test]
+    fn refuses_a_ledger_from_another_network() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("tokens.ledger.jsonl");
+        let ledger = Ledger::open(&path, "devnet").unwrap();
+        ledger.append(&created(&ledger, "gold")).unwrap();
+
+        let error = Ledger::open(&path, "mainnet").err().unwrap();
+        assert!(error.to_string().contains("devnet"), "{}", error);
+    }
+
+    #[test]
+    fn refuses_records_without_a_network() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("tokens.ledger.jsonl");
+        fs::write(&path, r#"{"id":"gold","mint":"m","signature":"s","cost_lamports":null,"created_at":0}"#).unwrap();
+        assert!(Ledger::open(&path, "devnet").is_err());
+    }
+}
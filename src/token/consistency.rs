@@ -0,0 +1,215 @@
+//! Metadata consistency checks: whether the name and symbol on chain match
+//! the JSON the metadata URI points to, and whether that JSON and its image
+//! can still be read.
+
+use anyhow::Result;
+use chrono::Utc;
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use super::{details::load_metadata_account, NetworkType};
+use crate::{
+    config::ServerConfig,
+    metadata::{fetch_with_fallback, Fetched, OffchainMetadata},
+    registry::TokenRegistry,
+};
+
+/// Registry tokens checked at once.
+const CONCURRENT_CHECKS: usize = 4;
+
+/// One mismatch or dead link, identified by a stable `code` such as
+/// `name_mismatch`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConsistencyIssue {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    pub mint: String,
+    pub network: NetworkType,
+    /// Account the on-chain name, symbol and URI were read from.
+    pub metadata_address: Option<String>,
+    pub uri: Option<String>,
+    /// URL the JSON was read from.
+    pub resolved_uri: Option<String>,
+    /// The JSON's `image`.
+    pub image: Option<String>,
+    /// URL the image was read from.
+    pub resolved_image: Option<String>,
+    /// Content type the image was served with.
+    pub image_type: Option<String>,
+    pub issues: Vec<ConsistencyIssue>,
+    /// Unix timestamp (seconds) of the check.
+    pub checked_at: i64,
+}
+
+impl ConsistencyReport {
+    fn new(mint: String, network: NetworkType) -> Self {
+        Self {
+            mint,
+            network,
+            metadata_address: None,
+            uri: None,
+            resolved_uri: None,
+            image: None,
+            resolved_image: None,
+            image_type: None,
+            issues: Vec::new(),
+            checked_at: Utc::now().timestamp(),
+        }
+    }
+
+    /// A report for a mint that could not be checked at all.
+    pub fn failed(mint: String, network: NetworkType, error: &anyhow::Error) -> Self {
+        let mut report = Self::new(mint, network);
+        report.issue("check_failed", format!("{:#}", error));
+        report
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn issue(&mut self, code: &str, message: String) {
+        self.issues.push(ConsistencyIssue { code: code.to_string(), message });
+    }
+
+    /// Reports the gateways `what` could not be read from before one
+    /// answered.
+    fn fell_back(&mut self, what: &str, fetched: &Fetched) {
+        if fetched.failures.is_empty() {
+            return;
+        }
+        let failed: Vec<String> = fetched.failures.iter().map(|(url, error)| format!("{} ({})", url, error)).collect();
+        self.issue(
+            "dead_gateway",
+            format!("{} only resolved through {}; failed at {}", what, fetched.url, failed.join(", ")),
+        );
+    }
+}
+
+/// Reads `mint`'s metadata, then its JSON and image through `ipfs_gateways`
+/// in order, and reports every way they disagree or fail to resolve.
+pub async fn check_consistency(
+    client: &RpcClient,
+    mint: &Pubkey,
+    network: NetworkType,
+    ipfs_gateways: &[String],
+) -> Result<ConsistencyReport> {
+    let mut report = ConsistencyReport::new(mint.to_string(), network);
+    let Some(metadata) = load_metadata_account(client, mint).await? else {
+        report.issue("no_metadata", "The mint has no metadata account".to_string());
+        return Ok(report);
+    };
+    report.metadata_address = Some(metadata.address.clone());
+    if metadata.uri.is_empty() {
+        report.issue("empty_uri", "The on-chain URI is empty".to_string());
+        return Ok(report);
+    }
+    report.uri = Some(metadata.uri.clone());
+
+    let fetched = match fetch_with_fallback(&metadata.uri, ipfs_gateways).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            report.issue("uri_unreachable", format!("{:#}", e));
+            return Ok(report);
+        }
+    };
+    report.fell_back("The metadata URI", &fetched);
+    report.resolved_uri = Some(fetched.url.clone());
+    let offchain: OffchainMetadata = match serde_json::from_slice(&fetched.bytes) {
+        Ok(offchain) => offchain,
+        Err(e) => {
+            report.issue("invalid_json", format!("{} is not token metadata JSON: {}", fetched.url, e));
+            return Ok(report);
+        }
+    };
+
+    if offchain.name != metadata.name {
+        report.issue(
+            "name_mismatch",
+            format!("The name is {:?} on chain but {:?} in the JSON", metadata.name, offchain.name),
+        );
+    }
+    if offchain.symbol != metadata.symbol {
+        report.issue(
+            "symbol_mismatch",
+            format!("The symbol is {:?} on chain but {:?} in the JSON", metadata.symbol, offchain.symbol),
+        );
+    }
+    check_image(&offchain, ipfs_gateways, &mut report).await;
+    Ok(report)
+}
+
+/// Checks every token in `registry`, each through the RPC endpoint
+/// configured for its network. Tokens that cannot be checked are reported
+/// with a `check_failed` issue rather than stopping the run.
+pub async fn check_registry(registry: &TokenRegistry, config: &ServerConfig) -> Result<Vec<ConsistencyReport>> {
+    let gateways = config.storage.ipfs_gateways();
+    let checks = registry.list(None)?.into_iter().map(|record| {
+        let gateways = &gateways;
+        async move {
+            let client =
+                RpcClient::new_with_commitment(config.rpc_url(&record.network).to_string(), CommitmentConfig::confirmed());
+            let checked = match record.mint.parse::<Pubkey>() {
+                Ok(mint) => check_consistency(&client, &mint, record.network.clone(), gateways).await,
+                Err(_) => Err(anyhow::anyhow!("Invalid mint address {}", record.mint)),
+            };
+            checked.unwrap_or_else(|e| ConsistencyReport::failed(record.mint, record.network, &e))
+        }
+    });
+    Ok(stream::iter(checks).buffered(CONCURRENT_CHECKS).collect().await)
+}
+
+/// Checks that the JSON's image resolves to an image, and to the file its
+/// `properties.files` digest describes when it records one.
+async fn check_image(offchain: &OffchainMetadata, ipfs_gateways: &[String], report: &mut ConsistencyReport) {
+    if offchain.image.is_empty() {
+        report.issue("no_image", "The JSON has no image".to_string());
+        return;
+    }
+    report.image = Some(offchain.image.clone());
+    let fetched = match fetch_with_fallback(&offchain.image, ipfs_gateways).await {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            report.issue("image_unreachable", format!("{:#}", e));
+            return;
+        }
+    };
+    report.fell_back("The image", &fetched);
+    report.resolved_image = Some(fetched.url.clone());
+    report.image_type = fetched.content_type.clone();
+
+    if !is_image(&fetched) {
+        report.issue(
+            "not_an_image",
+            format!(
+                "{} is not a PNG, JPEG, GIF, WebP or SVG image (served as {})",
+                fetched.url,
+                fetched.content_type.as_deref().unwrap_or("no content type")
+            ),
+        );
+    }
+    let recorded = offchain
+        .properties
+        .iter()
+        .flat_map(|properties| &properties.files)
+        .find(|file| file.uri == offchain.image);
+    if let Some(Err(e)) = recorded.map(|file| file.verify(&fetched.bytes)) {
+        report.issue("image_digest_mismatch", e.to_string());
+    }
+}
+
+/// Judges by the bytes rather than the content type, which gateways often
+/// get wrong. SVG has no magic number, so it is recognised by its root tag.
+fn is_image(fetched: &Fetched) -> bool {
+    if image::guess_format(&fetched.bytes).is_ok() {
+        return true;
+    }
+    let head = String::from_utf8_lossy(&fetched.bytes[..fetched.bytes.len().min(1024)]);
+    head.contains("<svg")
+}
@@ -0,0 +1,367 @@
+//! Everything the token page shows about a mint: its account with any
+//! Token-2022 extensions, its Metaplex or Token-2022 metadata, and the
+//! off-chain JSON that metadata points to.
+
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use super::NetworkType;
+use crate::metadata::OffchainMetadata;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{decode_extensions, load_metadata_account, load_token_details};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TokenDetails {
+    pub mint: String,
+    pub network: NetworkType,
+    /// `spl-token` or `spl-token-2022`.
+    pub program: String,
+    pub decimals: u8,
+    /// Base units.
+    pub supply: u64,
+    /// Whole tokens.
+    pub ui_supply: String,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    /// Token-2022 extensions on the mint; always empty for SPL Token.
+    pub extensions: Vec<MintExtension>,
+    pub metadata: Option<MetadataAccount>,
+    /// The document `metadata.uri` points to, when it could be read.
+    pub offchain: Option<OffchainMetadata>,
+    /// Why the off-chain document could not be read.
+    pub offchain_error: Option<String>,
+    /// Whether `offchain_error` is because the document was read but is
+    /// not token metadata JSON.
+    #[serde(default)]
+    pub offchain_invalid: bool,
+    /// Link for `metadata.uri`; `None` when its scheme is not safe to link.
+    #[serde(default)]
+    pub uri_url: Option<String>,
+    /// Fetchable URL of the off-chain `image`, when its scheme is safe to
+    /// load.
+    pub image_url: Option<String>,
+}
+
+/// Where a mint's name, symbol and URI are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataSource {
+    /// A Metaplex token metadata account.
+    Metaplex,
+    /// The Token-2022 metadata extension on the mint itself.
+    Token2022,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetadataAccount {
+    pub source: MetadataSource,
+    /// Account holding the metadata.
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub update_authority: Option<String>,
+    /// Token-2022 metadata stays mutable while it has an update authority.
+    pub is_mutable: bool,
+}
+
+/// A Token-2022 mint extension and the settings that matter to holders.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MintExtension {
+    /// Fee in effect for the current epoch.
+    TransferFee { basis_points: u16, maximum_fee: u64, authority: Option<String> },
+    PermanentDelegate { delegate: Option<String> },
+    TransferHook { program: Option<String>, authority: Option<String> },
+    MintCloseAuthority { authority: Option<String> },
+    DefaultAccountState { frozen: bool },
+    NonTransferable,
+    InterestBearing { rate_basis_points: i16, authority: Option<String> },
+    MetadataPointer { address: Option<String>, authority: Option<String> },
+    TokenMetadata,
+    Other { name: String },
+}
+
+impl MintExtension {
+    pub fn describe(&self) -> String {
+        let key = |key: &Option<String>| key.clone().unwrap_or_else(|| "none".to_string());
+        match self {
+            MintExtension::TransferFee { basis_points, maximum_fee, authority } => format!(
+                "Transfer fee: {:.2}% up to {} base units (authority {})",
+                *basis_points as f64 / 100.0,
+                maximum_fee,
+                key(authority)
+            ),
+            MintExtension::PermanentDelegate { delegate } => format!("Permanent delegate: {}", key(delegate)),
+            MintExtension::TransferHook { program, authority } => {
+                format!("Transfer hook: program {} (authority {})", key(program), key(authority))
+            }
+            MintExtension::MintCloseAuthority { authority } => format!("Close authority: {}", key(authority)),
+            MintExtension::DefaultAccountState { frozen } => {
+                format!("New accounts start {}", if *frozen { "frozen" } else { "initialized" })
+            }
+            MintExtension::NonTransferable => "Non-transferable".to_string(),
+            MintExtension::InterestBearing { rate_basis_points, authority } => format!(
+                "Interest bearing: {:.2}% a year (authority {})",
+                *rate_basis_points as f64 / 100.0,
+                key(authority)
+            ),
+            MintExtension::MetadataPointer { address, authority } => {
+                format!("Metadata pointer: {} (authority {})", key(address), key(authority))
+            }
+            MintExtension::TokenMetadata => "Token metadata".to_string(),
+            MintExtension::Other { name } => name.clone(),
+        }
+    }
+}
+
+/// Something the holder of one of the mint's authorities can do, with the
+/// `create_spl` command that does it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuthorityAction {
+    pub label: &'static str,
+    pub command: String,
+}
+
+impl TokenDetails {
+    /// Actions open to `wallet` through the authorities it holds.
+    pub fn authority_actions(&self, wallet: &str) -> Vec<AuthorityAction> {
+        let cluster = match self.network {
+            NetworkType::Devnet => "devnet",
+            NetworkType::Mainnet => "mainnet",
+        };
+        let command = |args: &str| format!("create_spl -u {} {}", cluster, args.replace("MINT", &self.mint));
+        let holds = |authority: &Option<String>| authority.as_deref() == Some(wallet);
+        let mut actions = Vec::new();
+        if holds(&self.mint_authority) {
+            actions.push(AuthorityAction { label: "Mint more supply", command: command("mint MINT <AMOUNT> --to <WALLET>") });
+            actions.push(AuthorityAction {
+                label: "Revoke the mint authority",
+                command: command("authority MINT --kind mint --revoke"),
+            });
+        }
+        if holds(&self.freeze_authority) {
+            actions.push(AuthorityAction { label: "Freeze a holder", command: command("freeze MINT <HOLDER>") });
+            actions.push(AuthorityAction { label: "Thaw a holder", command: command("thaw MINT <HOLDER>") });
+            actions.push(AuthorityAction {
+                label: "Revoke the freeze authority",
+                command: command("authority MINT --kind freeze --revoke"),
+            });
+        }
+        if self.metadata.is_none() && holds(&self.mint_authority) {
+            actions.push(AuthorityAction {
+                label: "Attach a name, symbol and logo",
+                command: command("metadata attach MINT --name <NAME> --symbol <SYMBOL> --image <LOGO>"),
+            });
+        }
+        if let Some(metadata) = &self.metadata {
+            // The CLI updates Metaplex metadata only.
+            if metadata.source == MetadataSource::Metaplex && metadata.is_mutable && holds(&metadata.update_authority) {
+                actions.push(AuthorityAction {
+                    label: "Update the name, symbol or URI",
+                    command: command("metadata update MINT --name <NAME> --symbol <SYMBOL> --uri <URI>"),
+                });
+                actions.push(AuthorityAction {
+                    label: "Lock the metadata",
+                    command: command("metadata update MINT --immutable"),
+                });
+            }
+        }
+        actions
+    }
+}
+
+#[server(GetTokenDetails, "/api")]
+pub async fn get_token_details(mint: String, network: NetworkType) -> Result<TokenDetails, ServerFnError> {
+    use crate::config::ServerConfig;
+    use actix_web::web;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+    let config = leptos_actix::extract::<web::Data<ServerConfig>>().await?;
+    let mint: Pubkey = mint
+        .parse()
+        .map_err(|_| ServerFnError::new(format!("Invalid mint address {}", mint)))?;
+    let client = RpcClient::new_with_commitment(config.rpc_url(&network).to_string(), CommitmentConfig::confirmed());
+    load_token_details(&client, &mint, network, &config.storage.ipfs_gateways())
+        .await
+        .map_err(|e| ServerFnError::new(format!("{:#}", e)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use anyhow::{Context, Result};
+    use mpl_token_metadata::accounts::Metadata;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::{account::Account, pubkey::Pubkey};
+    use spl_token_2022::{
+        extension::{
+            default_account_state::DefaultAccountState, interest_bearing_mint::InterestBearingConfig,
+            metadata_pointer::MetadataPointer, mint_close_authority::MintCloseAuthority,
+            permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig, transfer_hook::TransferHook,
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
+        state::{AccountState, Mint},
+    };
+    use spl_token_metadata_interface::state::TokenMetadata;
+
+    use super::{MetadataAccount, MetadataSource, MintExtension, TokenDetails};
+    use crate::{
+        metadata::{fetch_with_fallback, gateway_urls, link_url, OffchainMetadata},
+        token::{
+            ops::{decode_mint, format_amount, load_metadata, trim_padding},
+            NetworkType,
+        },
+    };
+
+    /// Loads the mint, its metadata and the off-chain document. A missing or
+    /// unreadable document is reported in `offchain_error` rather than
+    /// failing the whole load.
+    pub async fn load_token_details(
+        client: &RpcClient,
+        mint: &Pubkey,
+        network: NetworkType,
+        ipfs_gateways: &[String],
+    ) -> Result<TokenDetails> {
+        let account = fetch_mint(client, mint).await?;
+        let base = decode_mint(mint, &account)?;
+        let state = StateWithExtensions::<Mint>::unpack(&account.data)?;
+        let extensions = if base.program_id == spl_token_2022::id() {
+            let epoch = client.get_epoch_info().await?.epoch;
+            decode_extensions(&state, epoch)
+        } else {
+            Vec::new()
+        };
+
+        let metadata = metadata_account(client, mint, &state).await?;
+
+        let (offchain, offchain_error, offchain_invalid) = match metadata.as_ref().map(|metadata| metadata.uri.as_str()) {
+            Some(uri) if !uri.is_empty() => match fetch_with_fallback(uri, ipfs_gateways).await {
+                Ok(fetched) => match serde_json::from_slice::<OffchainMetadata>(&fetched.bytes) {
+                    Ok(offchain) => (Some(offchain), None, false),
+                    Err(e) => (None, Some(format!("{} is not token metadata JSON: {}", fetched.url, e)), true),
+                },
+                Err(e) => (None, Some(format!("{:#}", e)), false),
+            },
+            _ => (None, None, false),
+        };
+        let gateway = ipfs_gateways.first().map(String::as_str).unwrap_or_default();
+        let uri_url = metadata.as_ref().and_then(|metadata| link_url(&metadata.uri, gateway));
+        let image_url = offchain
+            .as_ref()
+            .filter(|offchain| link_url(&offchain.image, gateway).is_some())
+            .and_then(|offchain| gateway_urls(&offchain.image, ipfs_gateways).into_iter().next());
+
+        Ok(TokenDetails {
+            mint: mint.to_string(),
+            network,
+            program: if base.program_id == spl_token_2022::id() { "spl-token-2022" } else { "spl-token" }.to_string(),
+            decimals: base.decimals,
+            supply: base.supply,
+            ui_supply: format_amount(base.supply, base.decimals),
+            mint_authority: base.mint_authority.map(|key| key.to_string()),
+            freeze_authority: base.freeze_authority.map(|key| key.to_string()),
+            extensions,
+            metadata,
+            offchain,
+            offchain_error,
+            offchain_invalid,
+            uri_url,
+            image_url,
+        })
+    }
+
+    /// Reads the name, symbol and URI of `mint`: its Token-2022 metadata
+    /// extension when it has one, its Metaplex metadata account otherwise.
+    pub async fn load_metadata_account(client: &RpcClient, mint: &Pubkey) -> Result<Option<MetadataAccount>> {
+        let account = fetch_mint(client, mint).await?;
+        decode_mint(mint, &account)?;
+        let state = StateWithExtensions::<Mint>::unpack(&account.data)?;
+        metadata_account(client, mint, &state).await
+    }
+
+    async fn fetch_mint(client: &RpcClient, mint: &Pubkey) -> Result<Account> {
+        client
+            .get_account(mint)
+            .await
+            .with_context(|| format!("Failed to fetch mint {}", mint))
+    }
+
+    async fn metadata_account(
+        client: &RpcClient,
+        mint: &Pubkey,
+        state: &StateWithExtensions<'_, Mint>,
+    ) -> Result<Option<MetadataAccount>> {
+        Ok(match state.get_variable_len_extension::<TokenMetadata>() {
+            Ok(metadata) => Some(MetadataAccount {
+                source: MetadataSource::Token2022,
+                address: mint.to_string(),
+                name: metadata.name,
+                symbol: metadata.symbol,
+                uri: metadata.uri,
+                update_authority: Option::<Pubkey>::from(metadata.update_authority).map(|key| key.to_string()),
+                is_mutable: Option::<Pubkey>::from(metadata.update_authority).is_some(),
+            }),
+            Err(_) => load_metadata(client, mint).await?.map(|metadata| MetadataAccount {
+                source: MetadataSource::Metaplex,
+                address: Metadata::find_pda(mint).0.to_string(),
+                name: trim_padding(&metadata.name),
+                symbol: trim_padding(&metadata.symbol),
+                uri: trim_padding(&metadata.uri),
+                update_authority: Some(metadata.update_authority.to_string()),
+                is_mutable: metadata.is_mutable,
+            }),
+        })
+    }
+
+    /// The mint's Token-2022 extensions, with transfer fees as of `epoch`.
+    pub fn decode_extensions(state: &StateWithExtensions<Mint>, epoch: u64) -> Vec<MintExtension> {
+        let key = |key: Option<Pubkey>| key.map(|key| key.to_string());
+        let types = state.get_extension_types().unwrap_or_default();
+        types
+            .into_iter()
+            .map(|extension| {
+                let decoded = match extension {
+                    ExtensionType::TransferFeeConfig => state.get_extension::<TransferFeeConfig>().map(|config| {
+                        let fee = config.get_epoch_fee(epoch);
+                        MintExtension::TransferFee {
+                            basis_points: u16::from(fee.transfer_fee_basis_points),
+                            maximum_fee: u64::from(fee.maximum_fee),
+                            authority: key(config.transfer_fee_config_authority.into()),
+                        }
+                    }),
+                    ExtensionType::PermanentDelegate => state
+                        .get_extension::<PermanentDelegate>()
+                        .map(|delegate| MintExtension::PermanentDelegate { delegate: key(delegate.delegate.into()) }),
+                    ExtensionType::TransferHook => state.get_extension::<TransferHook>().map(|hook| {
+                        MintExtension::TransferHook { program: key(hook.program_id.into()), authority: key(hook.authority.into()) }
+                    }),
+                    ExtensionType::MintCloseAuthority => state.get_extension::<MintCloseAuthority>().map(|close| {
+                        MintExtension::MintCloseAuthority { authority: key(close.close_authority.into()) }
+                    }),
+                    ExtensionType::DefaultAccountState => state.get_extension::<DefaultAccountState>().map(|default| {
+                        MintExtension::DefaultAccountState { frozen: default.state == AccountState::Frozen as u8 }
+                    }),
+                    ExtensionType::NonTransferable => Ok(MintExtension::NonTransferable),
+                    ExtensionType::InterestBearingConfig => state.get_extension::<InterestBearingConfig>().map(|config| {
+                        MintExtension::InterestBearing {
+                            rate_basis_points: i16::from(config.current_rate),
+                            authority: key(config.rate_authority.into()),
+                        }
+                    }),
+                    ExtensionType::MetadataPointer => state.get_extension::<MetadataPointer>().map(|pointer| {
+                        MintExtension::MetadataPointer {
+                            address: key(pointer.metadata_address.into()),
+                            authority: key(pointer.authority.into()),
+                        }
+                    }),
+                    ExtensionType::TokenMetadata => Ok(MintExtension::TokenMetadata),
+                    other => Ok(MintExtension::Other { name: format!("{:?}", other) }),
+                };
+                decoded.unwrap_or_else(|_| MintExtension::Other { name: format!("{:?}", extension) })
+            })
+            .collect()
+    }
+
+}
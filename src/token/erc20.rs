@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Initcode assembled from `contracts/erc20/erc20.etk`.
+const ERC20_INITCODE: &str = include_str!("../../contracts/erc20/erc20.hex");
+
+/// Longest name or symbol the contract can keep in a single storage slot.
+pub const MAX_STRING_LEN: usize = 31;
+pub const MAX_DECIMALS: u8 = 18;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc20Params {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Whole tokens minted to the deployer.
+    pub initial_supply: u64,
+    /// Maximum supply in whole tokens; `None` leaves the token uncapped.
+    pub cap: Option<u64>,
+    pub mintable: bool,
+    pub burnable: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Erc20DeployResult {
+    pub contract_address: String,
+    pub transaction_hash: String,
+    pub chain_id: u64,
+    pub explorer_url: Option<String>,
+}
+
+impl Erc20Params {
+    pub fn validate(&self) -> Result<(), String> {
+        for (field, value) in [("Name", &self.name), ("Symbol", &self.symbol)] {
+            if value.trim().is_empty() {
+                return Err(format!("{} is required", field));
+            }
+            if value.len() > MAX_STRING_LEN {
+                return Err(format!("{} must be at most {} bytes", field, MAX_STRING_LEN));
+            }
+        }
+        if self.decimals > MAX_DECIMALS {
+            return Err(format!("Decimals must be between 0 and {}", MAX_DECIMALS));
+        }
+        if let Some(cap) = self.cap {
+            if cap == 0 {
+                return Err("Cap must be greater than zero".to_string());
+            }
+            if cap < self.initial_supply {
+                return Err("Initial supply exceeds the cap".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts a whole-token amount into base units.
+    pub fn to_base_units(&self, amount: u64) -> u128 {
+        // 2^64 * 10^18 < 2^128, so this cannot overflow for valid decimals.
+        amount as u128 * 10u128.pow(self.decimals as u32)
+    }
+
+    /// Returns the `0x`-prefixed deployment payload: initcode followed by the
+    /// ABI-encoded constructor arguments.
+    pub fn deployment_data(&self) -> Result<String, String> {
+        self.validate()?;
+
+        let name = self.name.as_bytes();
+        let symbol = self.symbol.as_bytes();
+        let head_len = 7 * 32;
+        let name_offset = head_len;
+        let symbol_offset = name_offset + 32 + padded_len(name.len());
+
+        let mut args = Vec::with_capacity(symbol_offset + 64 + padded_len(symbol.len()));
+        args.extend_from_slice(&word(name_offset as u128));
+        args.extend_from_slice(&word(symbol_offset as u128));
+        args.extend_from_slice(&word(self.decimals as u128));
+        args.extend_from_slice(&word(self.to_base_units(self.initial_supply)));
+        args.extend_from_slice(&word(self.cap.map(|cap| self.to_base_units(cap)).unwrap_or(0)));
+        args.extend_from_slice(&word(self.mintable as u128));
+        args.extend_from_slice(&word(self.burnable as u128));
+        encode_bytes(&mut args, name);
+        encode_bytes(&mut args, symbol);
+
+        Ok(format!("0x{}{}", ERC20_INITCODE.trim(), to_hex(&args)))
+    }
+}
+
+/// Block explorer page for a contract on the chains we know about.
+pub fn explorer_url(chain_id: u64, address: &str) -> Option<String> {
+    let base = match chain_id {
+        1 => "https://etherscan.io",
+        11155111 => "https://sepolia.etherscan.io",
+        17000 => "https://holesky.etherscan.io",
+        137 => "https://polygonscan.com",
+        8453 => "https://basescan.org",
+        42161 => "https://arbiscan.io",
+        10 => "https://optimistic.etherscan.io",
+        _ => return None,
+    };
+    Some(format!("{}/address/{}", base, address))
+}
+
+/// Parses a `0x`-prefixed hex quantity as returned by JSON-RPC.
+pub fn parse_quantity(value: &str) -> Result<u64, String> {
+    let digits = value.strip_prefix("0x").ok_or("Quantity is missing the 0x prefix")?;
+    u64::from_str_radix(digits, 16).map_err(|e| format!("Invalid quantity {}: {}", value, e))
+}
+
+fn word(value: u128) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out[16..].copy_from_slice(&value.to_be_bytes());
+    out
+}
+
+fn padded_len(len: usize) -> usize {
+    (len + 31) / 32 * 32
+}
+
+fn encode_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&word(bytes.len() as u128));
+    out.extend_from_slice(bytes);
+    out.resize(out.len() + padded_len(bytes.len()) - bytes.len(), 0);
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut out, b| {
+        let _ = write!(out, "{:02x}", b);
+        out
+    })
+}
@@ -0,0 +1,314 @@
+//! Holder snapshots: every token account of a mint at one slot, aggregated
+//! by the wallet that owns it. Accounts of both token programs are read, so
+//! a snapshot works for any mint.
+
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use super::NetworkType;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{snapshot_holders, HolderCache};
+
+/// One owner's combined balance.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Holder {
+    pub owner: String,
+    /// Base units across all of the owner's token accounts.
+    pub amount: u64,
+    /// Whole tokens.
+    pub ui_amount: String,
+    /// Fraction of the supply held, from 0 to 1.
+    pub share: f64,
+    pub token_accounts: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HolderSnapshot {
+    pub mint: String,
+    /// `spl-token` or `spl-token-2022`.
+    pub program: String,
+    pub decimals: u8,
+    /// Base units.
+    pub supply: u64,
+    /// Slot the token accounts were read at.
+    pub slot: u64,
+    /// Unix timestamp (seconds) the snapshot was taken.
+    pub taken_at: i64,
+    /// Largest first. Owners whose accounts are all empty are left out.
+    pub holders: Vec<Holder>,
+    /// Whether PDAs and program-owned accounts were left out of `holders`.
+    pub programs_excluded: bool,
+    /// Owners left out as PDAs or program-owned accounts.
+    pub excluded_holders: usize,
+    /// Base units those owners hold.
+    pub excluded_amount: u64,
+}
+
+impl HolderSnapshot {
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("rank,owner,amount,ui_amount,share,token_accounts,slot,taken_at\n");
+        for (index, holder) in self.holders.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{:.6},{},{},{}\n",
+                index + 1,
+                holder.owner,
+                holder.amount,
+                holder.ui_amount,
+                holder.share,
+                holder.token_accounts,
+                self.slot,
+                self.taken_at
+            ));
+        }
+        csv
+    }
+}
+
+#[server(GetHolders, "/api")]
+pub async fn get_holders(
+    mint: String,
+    network: NetworkType,
+    exclude_programs: bool,
+) -> Result<HolderSnapshot, ServerFnError> {
+    use crate::config::ServerConfig;
+    use actix_web::web;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+    let config = leptos_actix::extract::<web::Data<ServerConfig>>().await?;
+    let holders = leptos_actix::extract::<web::Data<HolderCache>>().await?;
+    let mint: Pubkey = mint
+        .parse()
+        .map_err(|_| ServerFnError::new(format!("Invalid mint address {}", mint)))?;
+    let client = RpcClient::new_with_commitment(config.rpc_url(&network).to_string(), CommitmentConfig::confirmed());
+    holders
+        .snapshot(&client, &mint, exclude_programs)
+        .await
+        .map_err(|e| ServerFnError::new(format!("{:#}", e)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use std::{
+        collections::HashMap,
+        fs,
+        path::Path,
+        sync::Mutex,
+        time::{Duration, Instant},
+    };
+
+    use anyhow::{anyhow, Context, Result};
+    use chrono::Utc;
+    use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+    use solana_client::{
+        nonblocking::rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+        rpc_request::RpcRequest,
+        rpc_response::{OptionalContext, RpcKeyedAccount},
+    };
+    use solana_sdk::{program_pack::Pack, pubkey::Pubkey, system_program};
+    use tokio::sync::Semaphore;
+
+    use super::{Holder, HolderSnapshot};
+    use crate::token::ops::{format_amount, load_mint};
+
+    /// Accounts fetched per `getMultipleAccounts` call, the RPC maximum.
+    const ACCOUNTS_PER_REQUEST: usize = 100;
+    /// Where the owner and amount sit in a token account: the mint comes
+    /// first, then the 32-byte owner and the little-endian `u64` amount.
+    const OWNER_OFFSET: usize = 32;
+    const OWNER_AND_AMOUNT_LEN: usize = 40;
+    /// How long [`HolderCache`] reuses a snapshot, and how many it keeps.
+    const CACHE_TTL: Duration = Duration::from_secs(60);
+    const CACHE_ENTRIES: usize = 64;
+    /// Snapshots [`HolderCache`] takes at once.
+    const CONCURRENT_SNAPSHOTS: usize = 2;
+
+    /// RPC URL, mint and whether programs were excluded.
+    type CacheKey = (String, Pubkey, bool);
+
+    /// Snapshots taken for web requests, which anyone can make. Each one
+    /// reads every account of the mint, so a snapshot is reused for a
+    /// minute per RPC endpoint, mint and exclusion setting, and only a
+    /// couple are taken at a time; other requests wait their turn.
+    pub struct HolderCache {
+        snapshots: Mutex<HashMap<CacheKey, (Instant, HolderSnapshot)>>,
+        permits: Semaphore,
+    }
+
+    impl Default for HolderCache {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl HolderCache {
+        pub fn new() -> Self {
+            Self { snapshots: Mutex::new(HashMap::new()), permits: Semaphore::new(CONCURRENT_SNAPSHOTS) }
+        }
+
+        /// [`snapshot_holders`], or the cached snapshot while it is fresh.
+        pub async fn snapshot(&self, client: &RpcClient, mint: &Pubkey, exclude_programs: bool) -> Result<HolderSnapshot> {
+            let key = (client.url(), *mint, exclude_programs);
+            if let Some(snapshot) = self.cached(&key) {
+                return Ok(snapshot);
+            }
+            let _permit = self.permits.acquire().await?;
+            // Another request may have taken it while this one waited.
+            if let Some(snapshot) = self.cached(&key) {
+                return Ok(snapshot);
+            }
+            let snapshot = snapshot_holders(client, mint, exclude_programs).await?;
+
+            let mut snapshots = self.snapshots.lock().unwrap();
+            snapshots.retain(|_, (taken, _)| taken.elapsed() < CACHE_TTL);
+            if snapshots.len() >= CACHE_ENTRIES {
+                let oldest = snapshots.iter().min_by_key(|(_, (taken, _))| *taken).map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    snapshots.remove(&oldest);
+                }
+            }
+            snapshots.insert(key, (Instant::now(), snapshot.clone()));
+            Ok(snapshot)
+        }
+
+        fn cached(&self, key: &CacheKey) -> Option<HolderSnapshot> {
+            let snapshots = self.snapshots.lock().unwrap();
+            snapshots.get(key).filter(|(taken, _)| taken.elapsed() < CACHE_TTL).map(|(_, snapshot)| snapshot.clone())
+        }
+    }
+
+    impl HolderSnapshot {
+        /// Writes CSV for a `.csv` path and JSON otherwise.
+        pub fn write(&self, path: &Path) -> Result<()> {
+            let contents = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("csv") => self.to_csv(),
+                _ => serde_json::to_string_pretty(self)?,
+            };
+            fs::write(path, contents).with_context(|| format!("Failed to write snapshot {}", path.display()))
+        }
+    }
+
+    /// Reads every account holding `mint` and totals them per owner. With
+    /// `exclude_programs`, owners that are PDAs or accounts owned by a
+    /// program other than the system program (pools, vaults, multisigs)
+    /// are counted separately instead of listed.
+    pub async fn snapshot_holders(client: &RpcClient, mint: &Pubkey, exclude_programs: bool) -> Result<HolderSnapshot> {
+        let account = load_mint(client, mint).await?;
+        let taken_at = Utc::now().timestamp();
+        // Token-2022 accounts carry extensions after the base layout, so only
+        // classic accounts can be filtered by size.
+        let (classic, extended) = futures::try_join!(
+            token_accounts(client, spl_token::id(), mint, Some(spl_token::state::Account::LEN)),
+            token_accounts(client, spl_token_2022::id(), mint, None),
+        )?;
+        let slot = classic.0.max(extended.0);
+
+        let mut balances: HashMap<Pubkey, (u64, usize)> = HashMap::new();
+        for (owner, amount) in classic.1.into_iter().chain(extended.1) {
+            let entry = balances.entry(owner).or_default();
+            entry.0 += amount;
+            entry.1 += 1;
+        }
+        balances.retain(|_, (amount, _)| *amount > 0);
+
+        let mut excluded_holders = 0;
+        let mut excluded_amount = 0;
+        if exclude_programs {
+            for owner in program_owners(client, balances.keys().copied().collect()).await? {
+                if let Some((amount, _)) = balances.remove(&owner) {
+                    excluded_holders += 1;
+                    excluded_amount += amount;
+                }
+            }
+        }
+
+        let mut holders: Vec<Holder> = balances
+            .into_iter()
+            .map(|(owner, (amount, token_accounts))| Holder {
+                owner: owner.to_string(),
+                amount,
+                ui_amount: format_amount(amount, account.decimals),
+                share: if account.supply == 0 { 0.0 } else { amount as f64 / account.supply as f64 },
+                token_accounts,
+            })
+            .collect();
+        holders.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.owner.cmp(&b.owner)));
+
+        Ok(HolderSnapshot {
+            mint: mint.to_string(),
+            program: if account.program_id == spl_token_2022::id() { "spl-token-2022" } else { "spl-token" }.to_string(),
+            decimals: account.decimals,
+            supply: account.supply,
+            slot,
+            taken_at,
+            holders,
+            programs_excluded: exclude_programs,
+            excluded_holders,
+            excluded_amount,
+        })
+    }
+
+    /// The owner and amount of every `program` account for `mint`, with the
+    /// slot they were read at.
+    async fn token_accounts(
+        client: &RpcClient,
+        program: Pubkey,
+        mint: &Pubkey,
+        data_size: Option<usize>,
+    ) -> Result<(u64, Vec<(Pubkey, u64)>)> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &mint.to_bytes()))];
+        if let Some(size) = data_size {
+            filters.push(RpcFilterType::DataSize(size as u64));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig { offset: OWNER_OFFSET, length: OWNER_AND_AMOUNT_LEN }),
+                commitment: Some(client.commitment()),
+                min_context_slot: None,
+            },
+            with_context: Some(true),
+        };
+        let response: OptionalContext<Vec<RpcKeyedAccount>> = client
+            .send(RpcRequest::GetProgramAccounts, serde_json::json!([program.to_string(), config]))
+            .await
+            .with_context(|| format!("Failed to list {} accounts of {}", program, mint))?;
+        let (slot, accounts) = match response {
+            OptionalContext::Context(response) => (response.context.slot, response.value),
+            OptionalContext::NoContext(accounts) => (client.get_slot().await?, accounts),
+        };
+
+        let mut holdings = Vec::with_capacity(accounts.len());
+        for keyed in accounts {
+            let data = keyed
+                .account
+                .data
+                .decode()
+                .filter(|data| data.len() == OWNER_AND_AMOUNT_LEN)
+                .ok_or_else(|| anyhow!("Unexpected data for token account {}", keyed.pubkey))?;
+            let owner = Pubkey::try_from(&data[..32]).expect("slice is 32 bytes");
+            let amount = u64::from_le_bytes(data[32..].try_into().expect("slice is 8 bytes"));
+            holdings.push((owner, amount));
+        }
+        Ok((slot, holdings))
+    }
+
+    /// Which of `owners` are PDAs or accounts owned by a program. Wallets
+    /// never funded with SOL have no account and count as wallets.
+    async fn program_owners(client: &RpcClient, owners: Vec<Pubkey>) -> Result<Vec<Pubkey>> {
+        let (on_curve, mut programs): (Vec<Pubkey>, Vec<Pubkey>) = owners.into_iter().partition(Pubkey::is_on_curve);
+        for chunk in on_curve.chunks(ACCOUNTS_PER_REQUEST) {
+            let accounts = client.get_multiple_accounts(chunk).await?;
+            for (owner, account) in chunk.iter().zip(accounts) {
+                if account.is_some_and(|account| account.executable || account.owner != system_program::id()) {
+                    programs.push(*owner);
+                }
+            }
+        }
+        Ok(programs)
+    }
+}
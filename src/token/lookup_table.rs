@@ -0,0 +1,180 @@
+//! Address lookup tables: creating, extending, deactivating and closing
+//! them, and loading them for v0 transactions. The signer is both the
+//! table's authority and the payer.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::{self, instruction, state::AddressLookupTable, AddressLookupTableAccount},
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Signature,
+    slot_hashes::MAX_ENTRIES,
+};
+
+use super::{
+    signer::TransactionSigner,
+    transaction::{send_and_confirm, send_series},
+};
+
+/// Addresses appended per extend instruction, small enough to fit one
+/// transaction.
+const ADDRESSES_PER_EXTEND: usize = 20;
+const SLOT_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// On-chain state of a lookup table.
+#[derive(Clone, Debug, Serialize)]
+pub struct LookupTableInfo {
+    pub address: String,
+    /// `None` once the table is frozen.
+    pub authority: Option<String>,
+    pub last_extended_slot: u64,
+    /// Slot the table was deactivated in, if it has been.
+    pub deactivation_slot: Option<u64>,
+    pub addresses: Vec<String>,
+}
+
+struct LoadedTable {
+    authority: Option<Pubkey>,
+    last_extended_slot: u64,
+    deactivation_slot: Option<u64>,
+    addresses: Vec<Pubkey>,
+}
+
+async fn fetch(client: &RpcClient, table: &Pubkey) -> Result<LoadedTable> {
+    let account = client
+        .get_account(table)
+        .await
+        .with_context(|| format!("Failed to fetch lookup table {}", table))?;
+    if account.owner != address_lookup_table::program::id() {
+        return Err(anyhow!("{} is not an address lookup table (owned by {})", table, account.owner));
+    }
+    let state = AddressLookupTable::deserialize(&account.data)
+        .map_err(|e| anyhow!("{} is not an address lookup table: {}", table, e))?;
+    Ok(LoadedTable {
+        authority: state.meta.authority,
+        last_extended_slot: state.meta.last_extended_slot,
+        deactivation_slot: (state.meta.deactivation_slot != u64::MAX).then_some(state.meta.deactivation_slot),
+        addresses: state.addresses.to_vec(),
+    })
+}
+
+/// Loads `table` for compiling v0 messages.
+pub async fn load_lookup_table(client: &RpcClient, table: &Pubkey) -> Result<AddressLookupTableAccount> {
+    let loaded = fetch(client, table).await?;
+    if loaded.deactivation_slot.is_some() {
+        return Err(anyhow!("Lookup table {} has been deactivated", table));
+    }
+    Ok(AddressLookupTableAccount { key: *table, addresses: loaded.addresses })
+}
+
+pub async fn lookup_table_info(client: &RpcClient, table: &Pubkey) -> Result<LookupTableInfo> {
+    let loaded = fetch(client, table).await?;
+    Ok(LookupTableInfo {
+        address: table.to_string(),
+        authority: loaded.authority.map(|authority| authority.to_string()),
+        last_extended_slot: loaded.last_extended_slot,
+        deactivation_slot: loaded.deactivation_slot,
+        addresses: loaded.addresses.iter().map(Pubkey::to_string).collect(),
+    })
+}
+
+/// Creates a table holding `addresses` and returns its address with the
+/// signatures of every transaction sent. Extensions share the create
+/// transaction while they fit.
+pub async fn create_lookup_table(
+    client: &RpcClient,
+    authority: &dyn TransactionSigner,
+    addresses: &[Pubkey],
+) -> Result<(Pubkey, Vec<Signature>)> {
+    let addresses = dedup(addresses);
+    check_capacity(0, addresses.len())?;
+    // Derivation needs a slot the program can find in the slot hashes.
+    let recent_slot = client.get_slot_with_commitment(CommitmentConfig::finalized()).await?;
+    let (create, table) = instruction::create_lookup_table(authority.pubkey(), authority.pubkey(), recent_slot);
+    let mut groups = vec![vec![create]];
+    groups.extend(extensions(authority, &table, &addresses));
+    let signatures = send_series(client, &groups, authority, &[], &[], |_| {}).await?;
+    Ok((table, signatures))
+}
+
+/// Appends the `addresses` not already in `table`.
+pub async fn extend_lookup_table(
+    client: &RpcClient,
+    authority: &dyn TransactionSigner,
+    table: &Pubkey,
+    addresses: &[Pubkey],
+) -> Result<Vec<Signature>> {
+    let loaded = fetch(client, table).await?;
+    let new: Vec<Pubkey> = dedup(addresses).into_iter().filter(|address| !loaded.addresses.contains(address)).collect();
+    check_capacity(loaded.addresses.len(), new.len())?;
+    send_series(client, &extensions(authority, table, &new), authority, &[], &[], |_| {}).await
+}
+
+fn extensions(authority: &dyn TransactionSigner, table: &Pubkey, addresses: &[Pubkey]) -> Vec<Vec<Instruction>> {
+    addresses
+        .chunks(ADDRESSES_PER_EXTEND)
+        .map(|chunk| vec![instruction::extend_lookup_table(*table, authority.pubkey(), Some(authority.pubkey()), chunk.to_vec())])
+        .collect()
+}
+
+/// Starts the cool-down after which the table can be closed. A deactivated
+/// table can no longer be used in transactions.
+pub async fn deactivate_lookup_table(client: &RpcClient, authority: &dyn TransactionSigner, table: &Pubkey) -> Result<Signature> {
+    if let Some(slot) = fetch(client, table).await?.deactivation_slot {
+        return Err(anyhow!("Lookup table {} was already deactivated in slot {}", table, slot));
+    }
+    let instruction = instruction::deactivate_lookup_table(*table, authority.pubkey());
+    send_and_confirm(client, &[instruction], authority, &[], &[], |_| {}).await
+}
+
+/// Closes a deactivated table and returns its rent to `recipient`.
+pub async fn close_lookup_table(
+    client: &RpcClient,
+    authority: &dyn TransactionSigner,
+    table: &Pubkey,
+    recipient: &Pubkey,
+) -> Result<Signature> {
+    let Some(deactivated) = fetch(client, table).await?.deactivation_slot else {
+        return Err(anyhow!("Lookup table {} must be deactivated before it is closed", table));
+    };
+    let slot = client.get_slot().await?;
+    let remaining = (deactivated + MAX_ENTRIES as u64).saturating_sub(slot);
+    if remaining > 0 {
+        return Err(anyhow!("Lookup table {} is still cooling down; try again in about {} slots", table, remaining));
+    }
+    let instruction = instruction::close_lookup_table(*table, authority.pubkey(), *recipient);
+    send_and_confirm(client, &[instruction], authority, &[], &[], |_| {}).await
+}
+
+/// Waits until addresses added to `table` can be used, which is from the
+/// slot after its last extension.
+pub async fn wait_until_usable(client: &RpcClient, table: &Pubkey) -> Result<()> {
+    let last_extended = fetch(client, table).await?.last_extended_slot;
+    while client.get_slot().await? <= last_extended {
+        tokio::time::sleep(SLOT_POLL_INTERVAL).await;
+    }
+    Ok(())
+}
+
+fn dedup(addresses: &[Pubkey]) -> Vec<Pubkey> {
+    let mut unique = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        if !unique.contains(address) {
+            unique.push(*address);
+        }
+    }
+    unique
+}
+
+fn check_capacity(existing: usize, new: usize) -> Result<()> {
+    let max = address_lookup_table::state::LOOKUP_TABLE_MAX_ADDRESSES;
+    if existing + new > max {
+        return Err(anyhow!("A lookup table holds at most {} addresses; this would make {}", max, existing + new));
+    }
+    Ok(())
+}
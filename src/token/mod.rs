@@ -1,9 +1,11 @@
 use serde::{Serialize, Deserialize};
 
+use crate::metadata::{validate_metadata_uri, validate_name, validate_symbol, MetadataError};
+
 #[cfg(not(target_arch = "wasm32"))]
-use solana_sdk::signature::Keypair;
+use std::sync::Arc;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTokenParams {
     pub name: String,
@@ -20,7 +22,21 @@ pub struct CreateTokenParams {
     pub network: NetworkType,
     #[cfg(not(target_arch = "wasm32"))]
     #[serde(skip)]
-    pub payer: Option<Keypair>,
+    pub payer: Option<Arc<dyn signer::TransactionSigner>>,
+}
+
+impl CreateTokenParams {
+    /// Checks the fields written on chain against the token metadata program's
+    /// limits. An empty `metadata_uri` passes, since jobs fill it in after
+    /// uploading the metadata.
+    pub fn validate(&self) -> Result<(), MetadataError> {
+        validate_name(&self.name)?;
+        validate_symbol(&self.symbol)?;
+        if !self.metadata_uri.is_empty() {
+            validate_metadata_uri(&self.metadata_uri)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -30,6 +46,14 @@ pub enum NetworkType {
 }
 
 impl NetworkType {
+    /// Lower-case name ledgers record the cluster under.
+    pub fn key(&self) -> &'static str {
+        match self {
+            NetworkType::Devnet => "devnet",
+            NetworkType::Mainnet => "mainnet",
+        }
+    }
+
     pub fn rpc_url(&self) -> &str {
         match self {
             NetworkType::Devnet => "https://api.devnet.solana.com",
@@ -43,21 +67,66 @@ impl NetworkType {
             NetworkType::Mainnet => "https://explorer.solana.com/address/{}",
         }
     }
+
+    pub fn tx_explorer_url(&self) -> &str {
+        match self {
+            NetworkType::Devnet => "https://explorer.solana.com/tx/{}?cluster=devnet",
+            NetworkType::Mainnet => "https://explorer.solana.com/tx/{}",
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Steps of an on-chain token creation, reported while it is in flight.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum CreationStage {
+    Simulating,
+    Sending,
+    Confirming,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TokenCreationResult {
     pub status: String,
     pub mint: String,
     pub explorer_url: String,
     pub signature: String,
+    /// Every transaction sent, when creation took more than one; `signature`
+    /// is the first.
+    #[serde(default)]
+    pub signatures: Vec<String>,
 }
 
+pub mod details;
+pub mod erc20;
+pub mod holders;
+pub mod risk;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod airdrop;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod consistency;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lookup_table;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ops;
 #[cfg(not(target_arch = "wasm32"))]
 mod server;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod signer;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod spec;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transaction;
 
 #[cfg(not(target_arch = "wasm32"))]
-pub use server::create_token;
+pub use server::{
+    attach_metadata, check_can_attach_metadata, create_token, create_token_reporting_mint, create_token_with_progress,
+    create_token_with_rpc, finish_token,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use transaction::{send_and_confirm, send_series};
 
 #[cfg(target_arch = "wasm32")]
 pub async fn create_token(_params: CreateTokenParams) -> Result<TokenCreationResult, Box<dyn std::error::Error>> {
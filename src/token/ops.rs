@@ -0,0 +1,359 @@
+//! Operations on existing mints: minting, burning, transfers, freezing,
+//! authority changes and metadata updates. Mints owned by the SPL Token and
+//! the Token-2022 program are both supported; wallets are addressed through
+//! their associated token accounts.
+
+use anyhow::{anyhow, Context, Result};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{UpdateMetadataAccountV2, UpdateMetadataAccountV2InstructionArgs},
+    types::DataV2,
+};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_sdk::{account::Account, signature::Signature};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::{extension::StateWithExtensions, instruction::AuthorityType, state::Mint};
+
+use super::{signer::TransactionSigner, transaction::send_and_confirm};
+use crate::metadata::{validate_metadata_uri, validate_name, validate_symbol};
+
+/// On-chain state of a mint and its Metaplex metadata.
+#[derive(Clone, Debug, Serialize)]
+pub struct TokenInfo {
+    pub mint: String,
+    /// `spl-token` or `spl-token-2022`.
+    pub program: &'static str,
+    pub decimals: u8,
+    /// Supply in base units.
+    pub supply: u64,
+    /// Supply in whole tokens.
+    pub ui_supply: String,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    pub metadata: Option<OnchainMetadata>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OnchainMetadata {
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub update_authority: String,
+    pub is_mutable: bool,
+}
+
+/// Which mint authority [`set_authority`] changes.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MintAuthority {
+    Mint,
+    Freeze,
+}
+
+impl MintAuthority {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Mint => "mint",
+            Self::Freeze => "freeze",
+        }
+    }
+}
+
+/// Changes to a token's metadata account. Unset fields keep their value.
+#[derive(Clone, Debug, Default)]
+pub struct MetadataUpdate {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    pub new_update_authority: Option<Pubkey>,
+    /// Locks the metadata for good.
+    pub make_immutable: bool,
+}
+
+/// A mint as read from chain, with the token program that owns it.
+pub struct MintAccount {
+    pub address: Pubkey,
+    pub program_id: Pubkey,
+    pub decimals: u8,
+    pub supply: u64,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl MintAccount {
+    /// Associated token account of `owner` for this mint.
+    pub fn token_account(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.address, &self.program_id)
+    }
+}
+
+pub async fn load_mint(client: &RpcClient, mint: &Pubkey) -> Result<MintAccount> {
+    let account = client
+        .get_account(mint)
+        .await
+        .with_context(|| format!("Failed to fetch mint {}", mint))?;
+    decode_mint(mint, &account)
+}
+
+/// Decodes the base mint state of `account`, the account at `mint`.
+pub fn decode_mint(mint: &Pubkey, account: &Account) -> Result<MintAccount> {
+    if account.owner != spl_token::id() && account.owner != spl_token_2022::id() {
+        return Err(anyhow!("{} is not a token mint (owned by {})", mint, account.owner));
+    }
+    let state = StateWithExtensions::<Mint>::unpack(&account.data)
+        .map_err(|e| anyhow!("{} is not a token mint: {}", mint, e))?
+        .base;
+    Ok(MintAccount {
+        address: *mint,
+        program_id: account.owner,
+        decimals: state.decimals,
+        supply: state.supply,
+        mint_authority: state.mint_authority.into(),
+        freeze_authority: state.freeze_authority.into(),
+    })
+}
+
+pub async fn load_metadata(client: &RpcClient, mint: &Pubkey) -> Result<Option<Metadata>> {
+    let address = Metadata::find_pda(mint).0;
+    let Some(account) = client
+        .get_account_with_commitment(&address, client.commitment())
+        .await?
+        .value
+    else {
+        return Ok(None);
+    };
+    let metadata = Metadata::from_bytes(&account.data)
+        .with_context(|| format!("Failed to decode metadata account {}", address))?;
+    Ok(Some(metadata))
+}
+
+pub async fn token_info(client: &RpcClient, mint: &Pubkey) -> Result<TokenInfo> {
+    let account = load_mint(client, mint).await?;
+    let metadata = load_metadata(client, mint).await?.map(|metadata| OnchainMetadata {
+        address: Metadata::find_pda(mint).0.to_string(),
+        name: trim_padding(&metadata.name),
+        symbol: trim_padding(&metadata.symbol),
+        uri: trim_padding(&metadata.uri),
+        update_authority: metadata.update_authority.to_string(),
+        is_mutable: metadata.is_mutable,
+    });
+    Ok(TokenInfo {
+        mint: mint.to_string(),
+        program: if account.program_id == spl_token_2022::id() { "spl-token-2022" } else { "spl-token" },
+        decimals: account.decimals,
+        supply: account.supply,
+        ui_supply: format_amount(account.supply, account.decimals),
+        mint_authority: account.mint_authority.map(|key| key.to_string()),
+        freeze_authority: account.freeze_authority.map(|key| key.to_string()),
+        metadata,
+    })
+}
+
+/// Mints `amount` base units to `recipient`'s token account, creating it if
+/// needed.
+pub async fn mint_tokens(
+    client: &RpcClient,
+    authority: &dyn TransactionSigner,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Result<Signature> {
+    let account = load_mint(client, mint).await?;
+    let destination = account.token_account(recipient);
+    let instructions = [
+        create_associated_token_account_idempotent(&authority.pubkey(), recipient, mint, &account.program_id),
+        spl_token_2022::instruction::mint_to_checked(
+            &account.program_id,
+            mint,
+            &destination,
+            &authority.pubkey(),
+            &[],
+            amount,
+            account.decimals,
+        )?,
+    ];
+    send(client, &instructions, authority).await
+}
+
+/// Burns `amount` base units from `owner`'s token account.
+pub async fn burn_tokens(client: &RpcClient, owner: &dyn TransactionSigner, mint: &Pubkey, amount: u64) -> Result<Signature> {
+    let account = load_mint(client, mint).await?;
+    let instruction = spl_token_2022::instruction::burn_checked(
+        &account.program_id,
+        &account.token_account(&owner.pubkey()),
+        mint,
+        &owner.pubkey(),
+        &[],
+        amount,
+        account.decimals,
+    )?;
+    send(client, &[instruction], owner).await
+}
+
+/// Sends `amount` base units from `owner` to `recipient`, creating the
+/// recipient's token account if needed.
+pub async fn transfer_tokens(
+    client: &RpcClient,
+    owner: &dyn TransactionSigner,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Result<Signature> {
+    let account = load_mint(client, mint).await?;
+    let instructions = [
+        create_associated_token_account_idempotent(&owner.pubkey(), recipient, mint, &account.program_id),
+        spl_token_2022::instruction::transfer_checked(
+            &account.program_id,
+            &account.token_account(&owner.pubkey()),
+            mint,
+            &account.token_account(recipient),
+            &owner.pubkey(),
+            &[],
+            amount,
+            account.decimals,
+        )?,
+    ];
+    send(client, &instructions, owner).await
+}
+
+/// Freezes `holder`'s token account. Requires the freeze authority.
+pub async fn freeze_account(client: &RpcClient, authority: &dyn TransactionSigner, mint: &Pubkey, holder: &Pubkey) -> Result<Signature> {
+    let account = load_mint(client, mint).await?;
+    let instruction = spl_token_2022::instruction::freeze_account(
+        &account.program_id,
+        &account.token_account(holder),
+        mint,
+        &authority.pubkey(),
+        &[],
+    )?;
+    send(client, &[instruction], authority).await
+}
+
+/// Thaws `holder`'s token account. Requires the freeze authority.
+pub async fn thaw_account(client: &RpcClient, authority: &dyn TransactionSigner, mint: &Pubkey, holder: &Pubkey) -> Result<Signature> {
+    let account = load_mint(client, mint).await?;
+    let instruction = spl_token_2022::instruction::thaw_account(
+        &account.program_id,
+        &account.token_account(holder),
+        mint,
+        &authority.pubkey(),
+        &[],
+    )?;
+    send(client, &[instruction], authority).await
+}
+
+/// Hands the mint or freeze authority to `new_authority`, or revokes it for
+/// good when `None`.
+pub async fn set_authority(
+    client: &RpcClient,
+    current: &dyn TransactionSigner,
+    mint: &Pubkey,
+    kind: MintAuthority,
+    new_authority: Option<&Pubkey>,
+) -> Result<Signature> {
+    let account = load_mint(client, mint).await?;
+    let authority_type = match kind {
+        MintAuthority::Mint => AuthorityType::MintTokens,
+        MintAuthority::Freeze => AuthorityType::FreezeAccount,
+    };
+    let instruction = spl_token_2022::instruction::set_authority(
+        &account.program_id,
+        mint,
+        new_authority,
+        authority_type,
+        &current.pubkey(),
+        &[],
+    )?;
+    send(client, &[instruction], current).await
+}
+
+/// Applies `update` to the mint's metadata account. Requires the update
+/// authority and mutable metadata.
+pub async fn update_metadata(
+    client: &RpcClient,
+    update_authority: &dyn TransactionSigner,
+    mint: &Pubkey,
+    update: MetadataUpdate,
+) -> Result<Signature> {
+    let metadata = load_metadata(client, mint)
+        .await?
+        .ok_or_else(|| anyhow!("{} has no metadata account", mint))?;
+    if !metadata.is_mutable {
+        return Err(anyhow!("Metadata of {} is immutable", mint));
+    }
+
+    let name = update.name.unwrap_or_else(|| trim_padding(&metadata.name));
+    let symbol = update.symbol.unwrap_or_else(|| trim_padding(&metadata.symbol));
+    let uri = update.uri.unwrap_or_else(|| trim_padding(&metadata.uri));
+    validate_name(&name)?;
+    validate_symbol(&symbol)?;
+    validate_metadata_uri(&uri)?;
+
+    let instruction = UpdateMetadataAccountV2 {
+        metadata: Metadata::find_pda(mint).0,
+        update_authority: update_authority.pubkey(),
+    }
+    .instruction(UpdateMetadataAccountV2InstructionArgs {
+        data: Some(DataV2 {
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata.creators,
+            collection: metadata.collection,
+            uses: metadata.uses,
+        }),
+        new_update_authority: update.new_update_authority,
+        primary_sale_happened: None,
+        is_mutable: update.make_immutable.then_some(false),
+    });
+    send(client, &[instruction], update_authority).await
+}
+
+/// Parses a whole-token amount such as `1.5` into base units.
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<u64> {
+    let invalid = || anyhow!("Invalid amount {:?} for a token with {} decimals", amount, decimals);
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > decimals as usize
+        || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    digits.parse().map_err(|_| invalid())
+}
+
+/// Formats base units as a whole-token amount without trailing zeros.
+pub fn format_amount(amount: u64, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// Strips the NUL padding Metaplex stores fixed-size strings with.
+pub(crate) fn trim_padding(value: &str) -> String {
+    value.trim_end_matches('\0').to_string()
+}
+
+async fn send(client: &RpcClient, instructions: &[Instruction], payer: &dyn TransactionSigner) -> Result<Signature> {
+    send_and_confirm(client, instructions, payer, &[], &[], |_| {}).await
+}
@@ -0,0 +1,427 @@
+//! Risk reports for arbitrary mints: the powers an issuer keeps over a
+//! token and its holders, read from the same decoders as the token page,
+//! scored so listings can be gated on them.
+
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use super::NetworkType;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::assess_risk;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Danger,
+}
+
+/// One finding, identified by a stable `code` such as `mint_authority`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RiskFlag {
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Points this finding adds to the score.
+    pub weight: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskRating {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RiskReport {
+    pub mint: String,
+    pub network: NetworkType,
+    /// 0 (nothing found) to 100, the sum of the flags' weights.
+    pub score: u8,
+    pub rating: RiskRating,
+    /// Most severe first.
+    pub flags: Vec<RiskFlag>,
+    /// Share of the supply held by the largest wallet, leaving out PDAs and
+    /// program-owned accounts such as pools.
+    pub top_holder_share: Option<f64>,
+    /// Share held by the ten largest wallets.
+    pub top_ten_share: Option<f64>,
+    /// Unix timestamp (seconds) of the checks.
+    pub checked_at: i64,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Danger => "danger",
+        }
+    }
+}
+
+impl RiskRating {
+    pub fn from_score(score: u8) -> Self {
+        match score {
+            0..=19 => RiskRating::Low,
+            20..=49 => RiskRating::Medium,
+            _ => RiskRating::High,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RiskRating::Low => "Low",
+            RiskRating::Medium => "Medium",
+            RiskRating::High => "High",
+        }
+    }
+}
+
+#[server(GetRiskReport, "/api")]
+pub async fn get_risk_report(mint: String, network: NetworkType) -> Result<RiskReport, ServerFnError> {
+    use crate::{config::ServerConfig, token::holders::HolderCache};
+    use actix_web::web;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+    let config = leptos_actix::extract::<web::Data<ServerConfig>>().await?;
+    let holders = leptos_actix::extract::<web::Data<HolderCache>>().await?;
+    let mint: Pubkey = mint
+        .parse()
+        .map_err(|_| ServerFnError::new(format!("Invalid mint address {}", mint)))?;
+    let client = RpcClient::new_with_commitment(config.rpc_url(&network).to_string(), CommitmentConfig::confirmed());
+    assess_risk(&client, &mint, network, &config.storage.ipfs_gateways(), &holders)
+        .await
+        .map_err(|e| ServerFnError::new(format!("{:#}", e)))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use anyhow::Result;
+    use chrono::Utc;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::{RiskFlag, RiskRating, RiskReport, Severity};
+    use crate::token::{
+        details::{load_token_details, MintExtension, TokenDetails},
+        holders::{HolderCache, HolderSnapshot},
+        NetworkType,
+    };
+
+    /// Share of the supply one wallet may hold before it is flagged, and the
+    /// share at which the flag becomes a danger.
+    const TOP_HOLDER_WARNING: f64 = 0.2;
+    const TOP_HOLDER_DANGER: f64 = 0.5;
+    /// Share of the supply the ten largest wallets may hold before it is
+    /// flagged.
+    const TOP_TEN_WARNING: f64 = 0.8;
+
+    /// Checks `mint` for issuer powers, metadata problems and concentrated
+    /// holdings, taking the holder snapshot through `holders`. A snapshot
+    /// the RPC refuses is reported as a flag rather than failing the report,
+    /// since many public endpoints disable `getProgramAccounts`.
+    pub async fn assess_risk(
+        client: &RpcClient,
+        mint: &Pubkey,
+        network: NetworkType,
+        ipfs_gateways: &[String],
+        holders: &HolderCache,
+    ) -> Result<RiskReport> {
+        let details = load_token_details(client, mint, network.clone(), ipfs_gateways).await?;
+        let mut flags = authority_flags(&details);
+        flags.extend(metadata_flags(&details));
+
+        let (top_holder_share, top_ten_share) = match holders.snapshot(client, mint, true).await {
+            Ok(snapshot) => {
+                flags.extend(concentration_flags(&snapshot));
+                let top_ten = snapshot.holders.iter().take(10).map(|holder| holder.share).sum();
+                (Some(snapshot.holders.first().map_or(0.0, |holder| holder.share)), Some(top_ten))
+            }
+            Err(e) => {
+                flags.push(flag("holders_unavailable", Severity::Info, 0, format!("Holder distribution unavailable: {:#}", e)));
+                (None, None)
+            }
+        };
+
+        flags.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| b.weight.cmp(&a.weight)));
+        let score = score(&flags);
+        Ok(RiskReport {
+            mint: mint.to_string(),
+            network,
+            score,
+            rating: RiskRating::from_score(score),
+            flags,
+            top_holder_share,
+            top_ten_share,
+            checked_at: Utc::now().timestamp(),
+        })
+    }
+
+    /// The flags' weights summed, capped at 100.
+    fn score(flags: &[RiskFlag]) -> u8 {
+        flags.iter().map(|flag| flag.weight as u32).sum::<u32>().min(100) as u8
+    }
+
+    fn flag(code: &str, severity: Severity, weight: u8, message: String) -> RiskFlag {
+        RiskFlag { code: code.to_string(), severity, message, weight }
+    }
+
+    fn authority_flags(details: &TokenDetails) -> Vec<RiskFlag> {
+        let mut flags = Vec::new();
+        if let Some(authority) = &details.mint_authority {
+            flags.push(flag("mint_authority", Severity::Danger, 30, format!("{} can mint more supply", authority)));
+        }
+        if let Some(authority) = &details.freeze_authority {
+            flags.push(flag("freeze_authority", Severity::Danger, 25, format!("{} can freeze any holder's tokens", authority)));
+        }
+        for extension in &details.extensions {
+            match extension {
+                MintExtension::PermanentDelegate { delegate: Some(delegate) } => flags.push(flag(
+                    "permanent_delegate",
+                    Severity::Danger,
+                    30,
+                    format!("{} can transfer or burn tokens from any account", delegate),
+                )),
+                MintExtension::TransferHook { program: Some(program), .. } => flags.push(flag(
+                    "transfer_hook",
+                    Severity::Warning,
+                    20,
+                    format!("Every transfer calls program {}, which can block it", program),
+                )),
+                MintExtension::TransferFee { basis_points, authority, .. } if *basis_points > 0 || authority.is_some() => {
+                    let message = match authority {
+                        Some(authority) => {
+                            format!("Transfers pay a {:.2}% fee that {} can raise", *basis_points as f64 / 100.0, authority)
+                        }
+                        None => format!("Transfers pay a {:.2}% fee", *basis_points as f64 / 100.0),
+                    };
+                    flags.push(flag("transfer_fee", Severity::Warning, 15, message));
+                }
+                _ => {}
+            }
+        }
+        flags
+    }
+
+    fn metadata_flags(details: &TokenDetails) -> Vec<RiskFlag> {
+        let Some(metadata) = &details.metadata else {
+            return vec![flag("no_metadata", Severity::Warning, 10, "The token has no name, symbol or URI on chain".to_string())];
+        };
+        let mut flags = Vec::new();
+        if metadata.is_mutable {
+            let authority = metadata.update_authority.as_deref().unwrap_or("the update authority");
+            flags.push(flag("mutable_metadata", Severity::Warning, 10, format!("{} can change the name, symbol and URI", authority)));
+        }
+        if metadata.uri.is_empty() {
+            flags.push(flag("metadata_uri_missing", Severity::Warning, 10, "The metadata URI is empty".to_string()));
+        } else if let Some(error) = &details.offchain_error {
+            if details.offchain_invalid {
+                flags.push(flag("metadata_invalid_json", Severity::Warning, 10, error.clone()));
+            } else {
+                flags.push(flag("metadata_uri_unreachable", Severity::Warning, 10, format!("Metadata URI unreachable: {}", error)));
+            }
+        }
+        flags
+    }
+
+    fn concentration_flags(snapshot: &HolderSnapshot) -> Vec<RiskFlag> {
+        let mut flags = Vec::new();
+        if let Some(top) = snapshot.holders.first() {
+            let message = format!("{} holds {:.1}% of the supply", top.owner, top.share * 100.0);
+            if top.share >= TOP_HOLDER_DANGER {
+                flags.push(flag("holder_concentration", Severity::Danger, 25, message));
+            } else if top.share >= TOP_HOLDER_WARNING {
+                flags.push(flag("holder_concentration", Severity::Warning, 10, message));
+            }
+        }
+        let top_ten: f64 = snapshot.holders.iter().take(10).map(|holder| holder.share).sum();
+        if snapshot.holders.len() > 1 && top_ten >= TOP_TEN_WARNING {
+            flags.push(flag(
+                "top_ten_concentration",
+                Severity::Warning,
+                10,
+                format!("The ten largest wallets hold {:.1}% of the supply", top_ten * 100.0),
+            ));
+        }
+        flags
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::token::{details::MetadataAccount, details::MetadataSource, holders::Holder};
+
+        fn details() -> TokenDetails {
+            TokenDetails {
+                mint: Pubkey::new_unique().to_string(),
+                network: NetworkType::Devnet,
+                program: "spl-token".to_string(),
+                decimals: 6,
+                supply: 1_000_000,
+                ui_supply: "1".to_string(),
+                mint_authority: None,
+                freeze_authority: None,
+                extensions: Vec::new(),
+                metadata: Some(MetadataAccount {
+                    source: MetadataSource::Metaplex,
+                    address: Pubkey::new_unique().to_string(),
+                    name: "Token".to_string(),
+                    symbol: "TKN".to_string(),
+                    uri: "https://example.com/token.json".to_string(),
+                    update_authority: Some("Authority".to_string()),
+                    is_mutable: false,
+                }),
+                offchain: None,
+                offchain_error: None,
+                offchain_invalid: false,
+                uri_url: None,
+                image_url: None,
+            }
+        }
+
+        fn codes(flags: &[RiskFlag]) -> Vec<(&str, Severity)> {
+            flags.iter().map(|flag| (flag.code.as_str(), flag.severity)).collect()
+        }
+
+        #[test]
+        fn flags_authorities_the_issuer_kept() {
+            let some = |key: &str| Some(key.to_string());
+            let cases = [
+                (None, None, vec![], vec![]),
+                (some("Minter"), None, vec![], vec!["mint_authority"]),
+                (None, some("Freezer"), vec![], vec!["freeze_authority"]),
+                (some("Minter"), some("Freezer"), vec![], vec!["mint_authority", "freeze_authority"]),
+                (None, None, vec![MintExtension::PermanentDelegate { delegate: some("Delegate") }], vec!["permanent_delegate"]),
+                (None, None, vec![MintExtension::PermanentDelegate { delegate: None }], vec![]),
+                (None, None, vec![MintExtension::TransferHook { program: some("Hook"), authority: None }], vec!["transfer_hook"]),
+                (
+                    None,
+                    None,
+                    vec![MintExtension::TransferFee { basis_points: 0, maximum_fee: 0, authority: None }],
+                    vec![],
+                ),
+                (
+                    None,
+                    None,
+                    vec![MintExtension::TransferFee { basis_points: 0, maximum_fee: 0, authority: some("Fees") }],
+                    vec!["transfer_fee"],
+                ),
+                (None, None, vec![MintExtension::NonTransferable], vec![]),
+            ];
+
+            for (mint_authority, freeze_authority, extensions, expected) in cases {
+                let details = TokenDetails { mint_authority, freeze_authority, extensions, ..details() };
+                let flags = authority_flags(&details);
+                assert_eq!(flags.iter().map(|flag| flag.code.as_str()).collect::<Vec<_>>(), expected, "{:?}", details);
+            }
+
+            let details = TokenDetails { mint_authority: some("Minter"), ..details() };
+            let flags = authority_flags(&details);
+            assert_eq!(codes(&flags), vec![("mint_authority", Severity::Danger)]);
+            assert!(flags[0].message.contains("Minter"), "{}", flags[0].message);
+        }
+
+        #[test]
+        fn flags_missing_mutable_or_unreadable_metadata() {
+            let metadata = details().metadata.unwrap();
+            let cases = [
+                (Some(metadata.clone()), None, false, vec![]),
+                (None, None, false, vec!["no_metadata"]),
+                (Some(MetadataAccount { is_mutable: true, ..metadata.clone() }), None, false, vec!["mutable_metadata"]),
+                (Some(MetadataAccount { uri: String::new(), ..metadata.clone() }), None, false, vec!["metadata_uri_missing"]),
+                (Some(metadata.clone()), Some("HTTP 404"), false, vec!["metadata_uri_unreachable"]),
+                (Some(metadata.clone()), Some("expected value at line 1"), true, vec!["metadata_invalid_json"]),
+                (
+                    Some(MetadataAccount { is_mutable: true, ..metadata.clone() }),
+                    Some("timed out"),
+                    false,
+                    vec!["mutable_metadata", "metadata_uri_unreachable"],
+                ),
+            ];
+
+            for (metadata, error, invalid, expected) in cases {
+                let details = TokenDetails {
+                    metadata,
+                    offchain_error: error.map(str::to_string),
+                    offchain_invalid: invalid,
+                    ..details()
+                };
+                let flags = metadata_flags(&details);
+                assert!(flags.iter().all(|flag| flag.severity == Severity::Warning));
+                assert_eq!(flags.iter().map(|flag| flag.code.as_str()).collect::<Vec<_>>(), expected, "{:?}", details);
+            }
+        }
+
+        fn snapshot(shares: &[f64]) -> HolderSnapshot {
+            HolderSnapshot {
+                mint: Pubkey::new_unique().to_string(),
+                program: "spl-token".to_string(),
+                decimals: 0,
+                supply: 1_000,
+                slot: 1,
+                taken_at: 0,
+                holders: shares
+                    .iter()
+                    .map(|share| Holder {
+                        owner: Pubkey::new_unique().to_string(),
+                        amount: (share * 1_000.0) as u64,
+                        ui_amount: String::new(),
+                        share: *share,
+                        token_accounts: 1,
+                    })
+                    .collect(),
+                programs_excluded: true,
+                excluded_holders: 0,
+                excluded_amount: 0,
+            }
+        }
+
+        #[test]
+        fn flags_concentrated_holdings() {
+            let cases = [
+                (vec![], vec![]),
+                (vec![0.19, 0.19, 0.19, 0.19], vec![]),
+                (vec![0.2, 0.1], vec![("holder_concentration", Severity::Warning)]),
+                (vec![0.49, 0.1], vec![("holder_concentration", Severity::Warning)]),
+                (vec![0.5, 0.1], vec![("holder_concentration", Severity::Danger)]),
+                // A sole holder is only the top-holder finding.
+                (vec![1.0], vec![("holder_concentration", Severity::Danger)]),
+                (vec![0.1; 9], vec![("top_ten_concentration", Severity::Warning)]),
+                (vec![0.15; 5], vec![]),
+                (vec![0.3, 0.3, 0.3], vec![("holder_concentration", Severity::Warning), ("top_ten_concentration", Severity::Warning)]),
+            ];
+
+            for (shares, expected) in cases {
+                assert_eq!(codes(&concentration_flags(&snapshot(&shares))), expected, "{:?}", shares);
+            }
+        }
+
+        #[test]
+        fn scores_and_rates_the_flags() {
+            let weighted = |weights: &[u8]| -> Vec<RiskFlag> {
+                weights.iter().map(|weight| flag("test", Severity::Warning, *weight, String::new())).collect()
+            };
+            let cases: [(&[u8], u8, RiskRating); 7] = [
+                (&[], 0, RiskRating::Low),
+                (&[10], 10, RiskRating::Low),
+                (&[10, 9], 19, RiskRating::Low),
+                (&[10, 10], 20, RiskRating::Medium),
+                (&[30, 10, 9], 49, RiskRating::Medium),
+                (&[30, 20], 50, RiskRating::High),
+                (&[30, 30, 25, 25, 20], 100, RiskRating::High),
+            ];
+
+            for (weights, expected, rating) in cases {
+                let score = score(&weighted(weights));
+                assert_eq!(score, expected, "{:?}", weights);
+                assert_eq!(RiskRating::from_score(score), rating, "{:?}", weights);
+            }
+        }
+    }
+}
@@ -1,11 +1,12 @@
-use anyhow::Result;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
 use solana_sdk::{
-    signature::Keypair,
-    signer::Signer,
+    signature::{Keypair, Signature},
     commitment_config::CommitmentConfig,
-    transaction::Transaction,
+    instruction::Instruction,
+    pubkey::Pubkey,
 };
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use spl_token::state::Mint;
 use solana_program::{program_pack::Pack, system_instruction};
 use mpl_token_metadata::{
@@ -13,20 +14,59 @@ use mpl_token_metadata::{
     instructions::{CreateMetadataAccountV3, CreateMetadataAccountV3InstructionArgs},
     types::DataV2,
 };
-use super::{CreateTokenParams, TokenCreationResult};
+use super::{
+    details::load_metadata_account,
+    ops::load_mint,
+    signer::TransactionSigner,
+    transaction::{send_and_confirm, send_series, send_series_with, SeriesError},
+    CreateTokenParams, CreationStage, TokenCreationResult,
+};
+use crate::metadata::{validate_metadata_uri, validate_name, validate_symbol};
 
 pub async fn create_token(params: CreateTokenParams) -> Result<TokenCreationResult> {
-    let payer = params.payer.unwrap_or_else(Keypair::new);
+    let rpc_url = params.network.rpc_url().to_string();
+    create_token_with_rpc(params, &rpc_url).await
+}
+
+pub async fn create_token_with_rpc(params: CreateTokenParams, rpc_url: &str) -> Result<TokenCreationResult> {
+    create_token_with_progress(params, rpc_url, |_| {}).await
+}
+
+/// Creates the token, calling `on_stage` as each transaction moves through
+/// simulation, submission and confirmation. Everything usually fits one
+/// transaction; when it does not, the mint is created first and the
+/// metadata and initial supply follow.
+pub async fn create_token_with_progress(
+    params: CreateTokenParams,
+    rpc_url: &str,
+    on_stage: impl Fn(CreationStage),
+) -> Result<TokenCreationResult> {
+    create_token_reporting_mint(params, rpc_url, on_stage, |_, _| Ok(())).await
+}
+
+/// [`create_token_with_progress`], calling `on_mint` with the mint and the
+/// signature that created it as soon as that transaction confirms. When the
+/// metadata or initial supply follow in later transactions, a caller that
+/// recorded the mint can complete them after a failure with
+/// [`finish_token`].
+pub async fn create_token_reporting_mint(
+    params: CreateTokenParams,
+    rpc_url: &str,
+    on_stage: impl Fn(CreationStage),
+    on_mint: impl FnOnce(&Pubkey, &Signature) -> Result<()>,
+) -> Result<TokenCreationResult> {
+    params.validate()?;
+    let payer = params.payer.unwrap_or_else(|| Arc::new(Keypair::new()));
     let mint = Keypair::new();
     
     let client = RpcClient::new_with_commitment(
-        params.network.rpc_url(),
+        rpc_url.to_string(),
         CommitmentConfig::confirmed(),
     );
     
-    let mint_rent = client.get_minimum_balance_for_rent_exemption(Mint::LEN)?;
+    let mint_rent = client.get_minimum_balance_for_rent_exemption(Mint::LEN).await?;
     
-    let mut instructions = vec![
+    let mut groups = vec![vec![
         // Create mint account
         system_instruction::create_account(
             &payer.pubkey(),
@@ -40,73 +80,168 @@ pub async fn create_token(params: CreateTokenParams) -> Result<TokenCreationResu
             &spl_token::id(),
             &mint.pubkey(),
             &payer.pubkey(),
-            Some(&payer.pubkey()),
+            params.freeze_authority.then(|| payer.pubkey()).as_ref(),
             params.decimals,
         )?,
-    ];
+    ]];
 
     // Create metadata account
-    let metadata_account = Metadata::find_pda(&mint.pubkey()).0;
+    let metadata_instruction = create_metadata_instruction(
+        &mint.pubkey(),
+        &payer.pubkey(),
+        params.name,
+        params.symbol,
+        params.metadata_uri,
+        params.is_mutable,
+    );
     
-    let metadata_instruction = CreateMetadataAccountV3 {
-        metadata: metadata_account,
-        mint: mint.pubkey(),
-        mint_authority: payer.pubkey(),
-        payer: payer.pubkey(),
-        update_authority: (payer.pubkey(), true),
+    groups.push(vec![metadata_instruction]);
+
+    // Add mint instruction if initial supply > 0
+    if params.initial_supply > 0 {
+        groups.push(supply_instructions(&payer.pubkey(), &mint.pubkey(), params.initial_supply)?);
+    }
+
+    let mint_address = mint.pubkey();
+    let mut on_mint = Some(on_mint);
+    let on_landed = |signature: &Signature| match on_mint.take() {
+        Some(on_mint) => on_mint(&mint_address, signature),
+        None => Ok(()),
+    };
+    let signatures = send_series_with(&client, &groups, &*payer, &[&mint], &[], |progress| on_stage(progress.stage), on_landed)
+        .await
+        .map_err(|e| match e.downcast_ref::<SeriesError>() {
+            Some(series) if !series.completed.is_empty() => {
+                e.context(format!("Mint {} was created but not fully set up", mint.pubkey()))
+            }
+            _ => e,
+        })?;
+
+    Ok(TokenCreationResult {
+        status: "Created".to_string(),
+        mint: mint.pubkey().to_string(),
+        explorer_url: params.network.explorer_url().replace("{}", &mint.pubkey().to_string()),
+        signature: signatures[0].to_string(),
+        signatures: signatures.iter().map(ToString::to_string).collect(),
+    })
+}
+
+/// Sends whatever [`create_token_reporting_mint`] did not get to for `mint`:
+/// its metadata account when there is none, and the initial supply while
+/// nothing has been minted. `params.payer` must hold the mint authority, as
+/// it did when the mint was created. Returns the signatures sent, none when
+/// the token was already complete.
+pub async fn finish_token(client: &RpcClient, params: CreateTokenParams, mint: &Pubkey) -> Result<Vec<Signature>> {
+    params.validate()?;
+    let payer = params.payer.ok_or_else(|| anyhow!("Finishing {} needs the payer that created it", mint))?;
+    let account = load_mint(client, mint).await?;
+    if account.mint_authority != Some(payer.pubkey()) {
+        return Err(anyhow!("{} is no longer mintable by {}, so it cannot be finished", mint, payer.pubkey()));
+    }
+
+    let mut groups = Vec::new();
+    if load_metadata_account(client, mint).await?.is_none() {
+        groups.push(vec![create_metadata_instruction(
+            mint,
+            &payer.pubkey(),
+            params.name,
+            params.symbol,
+            params.metadata_uri,
+            params.is_mutable,
+        )]);
+    }
+    if params.initial_supply > 0 && account.supply == 0 {
+        groups.push(supply_instructions(&payer.pubkey(), mint, params.initial_supply)?);
+    }
+    if groups.is_empty() {
+        return Ok(Vec::new());
+    }
+    send_series(client, &groups, &*payer, &[], &[], |_| {}).await
+}
+
+/// Creates `owner`'s token account for `mint` and mints `amount` to it.
+fn supply_instructions(owner: &Pubkey, mint: &Pubkey, amount: u64) -> Result<Vec<Instruction>> {
+    let recipient_ata = spl_associated_token_account::get_associated_token_address(owner, mint);
+    Ok(vec![
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            owner,
+            owner,
+            mint,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::mint_to(&spl_token::id(), mint, &recipient_ata, owner, &[], amount)?,
+    ])
+}
+
+/// Creates the Metaplex metadata account of a mint made without one, such
+/// as by `spl-token create-token`, so it can be branded after the fact.
+/// `mint_authority` must hold the mint authority; it pays and becomes the
+/// update authority.
+pub async fn attach_metadata(
+    client: &RpcClient,
+    mint_authority: &dyn TransactionSigner,
+    mint: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    is_mutable: bool,
+) -> Result<Signature> {
+    validate_name(&name)?;
+    validate_symbol(&symbol)?;
+    validate_metadata_uri(&uri)?;
+    check_can_attach_metadata(client, &mint_authority.pubkey(), mint).await?;
+
+    let instruction = create_metadata_instruction(mint, &mint_authority.pubkey(), name, symbol, uri, is_mutable);
+    send_and_confirm(client, &[instruction], mint_authority, &[], &[], |_| {}).await
+}
+
+/// Fails unless `mint` has no metadata yet and `mint_authority` holds its
+/// mint authority, the checks [`attach_metadata`] makes. Run it before
+/// uploading anything for the mint.
+pub async fn check_can_attach_metadata(client: &RpcClient, mint_authority: &Pubkey, mint: &Pubkey) -> Result<()> {
+    let account = load_mint(client, mint).await?;
+    match account.mint_authority {
+        Some(authority) if authority == *mint_authority => {}
+        Some(authority) => {
+            return Err(anyhow!("The mint authority of {} is {}, not the signer {}", mint, authority, mint_authority))
+        }
+        None => return Err(anyhow!("The mint authority of {} was revoked, so metadata can no longer be created", mint)),
+    }
+    if let Some(existing) = load_metadata_account(client, mint).await? {
+        return Err(anyhow!("{} already has metadata at {}; use `metadata update` to change it", mint, existing.address));
+    }
+    Ok(())
+}
+
+/// `CreateMetadataAccountV3` for `mint`, with `authority` as the mint
+/// authority, payer and update authority.
+fn create_metadata_instruction(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    is_mutable: bool,
+) -> Instruction {
+    CreateMetadataAccountV3 {
+        metadata: Metadata::find_pda(mint).0,
+        mint: *mint,
+        mint_authority: *authority,
+        payer: *authority,
+        update_authority: (*authority, true),
         system_program: solana_program::system_program::id(),
         rent: Some(solana_program::sysvar::rent::id()),
     }.instruction(CreateMetadataAccountV3InstructionArgs {
         data: DataV2 {
-            name: params.name,
-            symbol: params.symbol,
-            uri: params.metadata_uri,
+            name,
+            symbol,
+            uri,
             seller_fee_basis_points: 0,
             creators: None,
             collection: None,
             uses: None,
         },
-        is_mutable: params.is_mutable,
+        is_mutable,
         collection_details: None,
-    });
-    
-    instructions.push(metadata_instruction);
-
-    // Add mint instruction if initial supply > 0
-    if params.initial_supply > 0 {
-        let recipient_ata = spl_associated_token_account::get_associated_token_address(
-            &payer.pubkey(),
-            &mint.pubkey(),
-        );
-
-        instructions.extend_from_slice(&[
-            spl_associated_token_account::instruction::create_associated_token_account(
-                &payer.pubkey(),
-                &payer.pubkey(),
-                &mint.pubkey(),
-                &spl_token::id(),
-            ),
-            spl_token::instruction::mint_to(
-                &spl_token::id(),
-                &mint.pubkey(),
-                &recipient_ata,
-                &payer.pubkey(),
-                &[],
-                params.initial_supply,
-            )?,
-        ]);
-    }
-
-    let recent_blockhash = client.get_latest_blockhash()?;
-    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
-    transaction.sign(&[&payer, &mint], recent_blockhash);
-
-    let signature = client.send_and_confirm_transaction(&transaction)?;
-    
-    Ok(TokenCreationResult {
-        status: "Created".to_string(),
-        mint: mint.pubkey().to_string(),
-        explorer_url: format!("{}", params.network.explorer_url().replace("{}", &mint.pubkey().to_string())),
-        signature: signature.to_string(),
     })
-} 
\ No newline at end of file
+}
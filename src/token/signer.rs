@@ -0,0 +1,206 @@
+//! Signers for server-side transactions. Code that builds transactions takes
+//! a [`TransactionSigner`] rather than a `Keypair`, so the key can live in
+//! this process, in the encrypted keystore, or behind a remote signing
+//! service.
+
+use std::fmt;
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+
+use crate::utils::keystore::UnlockedEntry;
+
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    fn pubkey(&self) -> Pubkey;
+
+    /// Signs serialized transaction message bytes.
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+}
+
+impl fmt::Debug for dyn TransactionSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TransactionSigner({})", self.pubkey())
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for Keypair {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(Signer::sign_message(self, message))
+    }
+}
+
+/// Decrypts the key for each signature and wipes it straight after.
+#[async_trait]
+impl TransactionSigner for UnlockedEntry {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(Signer::sign_message(&self.keypair()?, message))
+    }
+}
+
+/// A signing service reached over HTTP. The service receives
+/// `{"pubkey": <base58>, "message": <base64>}` and answers
+/// `{"signature": <base58>}`; the signature is checked against `pubkey`
+/// before it is used.
+pub struct RemoteSigner {
+    url: String,
+    pubkey: Pubkey,
+    auth_token: Option<String>,
+    http: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct SignRequest<'a> {
+    pubkey: String,
+    message: &'a str,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, pubkey: Pubkey, auth_token: Option<String>) -> Self {
+        Self {
+            url: url.to_string(),
+            pubkey,
+            auth_token,
+            http: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RemoteSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let encoded = STANDARD.encode(message);
+        let mut request = self.http.post(&self.url).json(&SignRequest {
+            pubkey: self.pubkey.to_string(),
+            message: &encoded,
+        });
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach remote signer {}", self.url))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Remote signer returned {}: {}", status, body));
+        }
+        let signature: Signature = response
+            .json::<SignResponse>()
+            .await?
+            .signature
+            .parse()
+            .map_err(|e| anyhow!("Remote signer returned an invalid signature: {}", e))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(anyhow!("Remote signer returned a signature that does not verify for {}", self.pubkey));
+        }
+        Ok(signature)
+    }
+}
+
+/// Signs `message` with every signature it requires, taking each from the
+/// signer with the matching key.
+pub async fn sign_transaction(message: VersionedMessage, signers: &[&dyn TransactionSigner]) -> Result<VersionedTransaction> {
+    let data = message.serialize();
+    let required = message.header().num_required_signatures as usize;
+    let mut signatures = Vec::with_capacity(required);
+    for key in &message.static_account_keys()[..required] {
+        let signer = signers
+            .iter()
+            .find(|signer| signer.pubkey() == *key)
+            .ok_or_else(|| anyhow!("No signer for required account {}", key))?;
+        signatures.push(signer.sign_message(&data).await?);
+    }
+    Ok(VersionedTransaction { signatures, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::testing::MockServer;
+
+    const MESSAGE: &[u8] = b"transaction message";
+
+    fn answering(signature: Signature) -> MockServer {
+        MockServer::start(vec![(200, "application/json", json!({ "signature": signature.to_string() }).to_string().into_bytes())])
+    }
+
+    #[tokio::test]
+    async fn accepts_a_signature_from_the_configured_key() {
+        let key = Keypair::new();
+        let server = answering(Signer::sign_message(&key, MESSAGE));
+        let signer = RemoteSigner::new(&server.url, Signer::pubkey(&key), Some("secret".to_string()));
+
+        let signature = signer.sign_message(MESSAGE).await.unwrap();
+        assert!(signature.verify(Signer::pubkey(&key).as_ref(), MESSAGE));
+
+        let requests = server.requests();
+        let [request] = requests.as_slice() else { panic!("expected one request, got {:?}", requests) };
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.header("authorization"), Some("Bearer secret"));
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body, json!({ "pubkey": Signer::pubkey(&key).to_string(), "message": STANDARD.encode(MESSAGE) }));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_signature_from_another_key() {
+        let key = Keypair::new();
+        let server = answering(Signer::sign_message(&Keypair::new(), MESSAGE));
+        let signer = RemoteSigner::new(&server.url, Signer::pubkey(&key), None);
+
+        let error = signer.sign_message(MESSAGE).await.unwrap_err();
+        assert!(error.to_string().contains("does not verify"), "{}", error);
+        assert_eq!(server.requests()[0].header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_tampered_signature() {
+        let key = Keypair::new();
+        let mut bytes = Signer::sign_message(&key, MESSAGE).as_ref().to_vec();
+        bytes[0] ^= 1;
+        let server = answering(Signature::try_from(bytes.as_slice()).unwrap());
+        let signer = RemoteSigner::new(&server.url, Signer::pubkey(&key), None);
+
+        let error = signer.sign_message(MESSAGE).await.unwrap_err();
+        assert!(error.to_string().contains("does not verify"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn reports_service_errors() {
+        let server = MockServer::start(vec![(403, "text/plain", b"key not allowed".to_vec())]);
+        let signer = RemoteSigner::new(&server.url, Pubkey::new_unique(), None);
+
+        let error = signer.sign_message(MESSAGE).await.unwrap_err().to_string();
+        assert!(error.contains("403") && error.contains("key not allowed"), "{}", error);
+    }
+}
@@ -0,0 +1,644 @@
+//! Tokens declared as code. A spec file (TOML, or YAML by extension) names
+//! each token and its desired state; [`plan`] diffs that against the chain
+//! and [`apply`] creates or updates only what differs. Which mint each name
+//! refers to on each network is kept in a lockfile next to the spec.
+
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use super::{
+    create_token_reporting_mint, finish_token,
+    ops::{self, MetadataUpdate, MintAuthority, TokenInfo},
+    signer::TransactionSigner,
+    CreateTokenParams, NetworkType,
+};
+use crate::{
+    metadata::{gateway_url, OffchainMetadata},
+    registry::{TokenRecord, TokenRegistry},
+    storage::MetadataStore,
+};
+
+/// Authority value meaning the key that runs `apply`.
+pub const SELF_AUTHORITY: &str = "self";
+/// Authority value meaning the authority is revoked.
+pub const NO_AUTHORITY: &str = "none";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecFile {
+    #[serde(default)]
+    pub tokens: BTreeMap<String, TokenSpec>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenSpec {
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_decimals")]
+    pub decimals: u8,
+    /// Target total supply in whole tokens. `apply` mints any shortfall to
+    /// the signer; it never burns.
+    #[serde(default = "default_supply")]
+    pub supply: String,
+    /// Off-chain metadata URI. Mutually exclusive with `metadata`.
+    pub uri: Option<String>,
+    /// Off-chain metadata document, uploaded through the configured storage
+    /// backend whenever it differs from what the on-chain URI serves. `name`,
+    /// `symbol` and `description` default to the token's.
+    pub metadata: Option<Value>,
+    #[serde(default = "default_true")]
+    pub mutable: bool,
+    #[serde(default)]
+    pub authorities: AuthoritySpec,
+    #[serde(default)]
+    pub extensions: ExtensionSpec,
+}
+
+/// Each authority is `self`, `none` or an address. The update authority
+/// cannot be `none`; make the metadata immutable instead.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthoritySpec {
+    pub mint: String,
+    pub freeze: String,
+    pub update: String,
+}
+
+impl Default for AuthoritySpec {
+    fn default() -> Self {
+        Self {
+            mint: SELF_AUTHORITY.to_string(),
+            freeze: SELF_AUTHORITY.to_string(),
+            update: SELF_AUTHORITY.to_string(),
+        }
+    }
+}
+
+/// Passed through to [`CreateTokenParams`] at creation; there is no
+/// on-chain state to diff them against.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExtensionSpec {
+    pub rate_limit: Option<u64>,
+    pub transfer_fee: Option<u16>,
+    pub max_transfer_amount: Option<u64>,
+}
+
+fn default_decimals() -> u8 {
+    9
+}
+
+fn default_supply() -> String {
+    "0".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SpecFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).with_context(|| format!("Failed to read spec {}", path.display()))?;
+        let spec: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents)?,
+            _ => toml::from_str(&contents)?,
+        };
+        for (name, token) in &spec.tokens {
+            token.validate().with_context(|| format!("Invalid token {:?} in {}", name, path.display()))?;
+        }
+        Ok(spec)
+    }
+}
+
+impl TokenSpec {
+    fn validate(&self) -> Result<()> {
+        crate::metadata::validate_name(&self.name)?;
+        crate::metadata::validate_symbol(&self.symbol)?;
+        ops::parse_amount(&self.supply, self.decimals)?;
+        match (&self.uri, &self.metadata) {
+            (Some(_), Some(_)) => return Err(anyhow!("Set either uri or metadata, not both")),
+            (None, None) => return Err(anyhow!("Set uri or metadata")),
+            (Some(uri), None) => crate::metadata::validate_metadata_uri(uri)?,
+            (None, Some(_)) => self.offchain_metadata()?.validate()?,
+        }
+        if self.authorities.update == NO_AUTHORITY {
+            return Err(anyhow!("The update authority cannot be none; set mutable = false instead"));
+        }
+        for authority in [&self.authorities.mint, &self.authorities.freeze, &self.authorities.update] {
+            if authority != SELF_AUTHORITY && authority != NO_AUTHORITY {
+                authority
+                    .parse::<Pubkey>()
+                    .map_err(|_| anyhow!("Authority {:?} is not self, none or an address", authority))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The metadata document with the token's name, symbol and description
+    /// filled in.
+    pub fn offchain_metadata(&self) -> Result<OffchainMetadata> {
+        let mut document = match &self.metadata {
+            Some(Value::Object(fields)) => fields.clone(),
+            Some(_) => return Err(anyhow!("metadata must be a table")),
+            None => return Err(anyhow!("No metadata document in the spec")),
+        };
+        document.entry("name").or_insert_with(|| self.name.clone().into());
+        document.entry("symbol").or_insert_with(|| self.symbol.clone().into());
+        document.entry("description").or_insert_with(|| self.description.clone().into());
+        let metadata: OffchainMetadata = serde_json::from_value(Value::Object(document))?;
+        metadata.check_matches(&self.name, &self.symbol)?;
+        Ok(metadata)
+    }
+}
+
+/// Resolves `self`, `none` or an address.
+fn resolve_authority(value: &str, signer: &Pubkey) -> Result<Option<Pubkey>> {
+    match value {
+        SELF_AUTHORITY => Ok(Some(*signer)),
+        NO_AUTHORITY => Ok(None),
+        address => address.parse().map(Some).map_err(|e| anyhow!("Invalid authority {}: {}", address, e)),
+    }
+}
+
+/// Spec names to mints, per network.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(flatten)]
+    pub networks: BTreeMap<String, BTreeMap<String, LockedToken>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LockedToken {
+    pub mint: String,
+    pub created_at: i64,
+    /// Metadata URI of a creation whose metadata or supply transaction has
+    /// not landed yet; [`apply`] finishes it before anything else.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unfinished: Option<String>,
+}
+
+impl Lockfile {
+    /// `tokens.toml` -> `tokens.lock`.
+    pub fn path_for(spec: &Path) -> PathBuf {
+        spec.with_extension("lock")
+    }
+
+    /// Reads a lockfile; a missing file gives an empty one.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).with_context(|| format!("Failed to parse lockfile {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!("Failed to read lockfile {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = format!("# Generated by create_spl apply. Do not edit.\n{}", toml::to_string(self)?);
+        fs::write(path, contents).with_context(|| format!("Failed to write lockfile {}", path.display()))
+    }
+
+    pub fn get(&self, network: &str, name: &str) -> Option<&LockedToken> {
+        self.networks.get(network)?.get(name)
+    }
+
+    /// Locks a mint that has just landed, before the rest of its creation
+    /// has; [`Lockfile::finished`] marks it complete.
+    fn insert(&mut self, network: &str, name: &str, mint: String, uri: String) {
+        let token = LockedToken { mint, created_at: Utc::now().timestamp(), unfinished: Some(uri) };
+        self.networks.entry(network.to_string()).or_default().insert(name.to_string(), token);
+    }
+
+    fn finished(&mut self, network: &str, name: &str) {
+        if let Some(token) = self.networks.get_mut(network).and_then(|tokens| tokens.get_mut(name)) {
+            token.unfinished = None;
+        }
+    }
+}
+
+/// What [`apply`] would do, token by token.
+#[derive(Debug, Serialize)]
+pub struct Plan {
+    pub network: String,
+    pub tokens: Vec<TokenPlan>,
+    /// Lockfile entries on this network that the spec no longer declares.
+    /// They are left alone.
+    pub untracked: Vec<String>,
+}
+
+impl Plan {
+    pub fn conflicts(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.tokens
+            .iter()
+            .flat_map(|token| token.conflicts.iter().map(move |conflict| (token.name.as_str(), conflict.as_str())))
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.tokens.iter().any(|token| !token.is_noop())
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TokenPlan {
+    pub name: String,
+    pub mint: Option<String>,
+    /// On-chain state; `None` when the token is to be created.
+    pub current: Option<TokenInfo>,
+    pub create: bool,
+    /// The mint was created but its metadata or initial supply did not
+    /// follow; they are sent before anything else.
+    pub finish: bool,
+    pub upload_metadata: bool,
+    /// Base units to mint to the signer.
+    pub mint_amount: u64,
+    pub metadata: MetadataChanges,
+    pub authorities: Vec<AuthorityChange>,
+    /// Differences `apply` cannot resolve.
+    pub conflicts: Vec<String>,
+}
+
+impl TokenPlan {
+    pub fn is_noop(&self) -> bool {
+        !self.create
+            && !self.finish
+            && !self.upload_metadata
+            && self.mint_amount == 0
+            && self.metadata.is_empty()
+            && self.authorities.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MetadataChanges {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    /// New URI; with `upload_metadata` it is only known once uploaded.
+    pub uri: Option<String>,
+    pub update_authority: Option<String>,
+    pub make_immutable: bool,
+}
+
+impl MetadataChanges {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.symbol.is_none()
+            && self.uri.is_none()
+            && self.update_authority.is_none()
+            && !self.make_immutable
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthorityChange {
+    pub kind: MintAuthority,
+    /// `None` revokes the authority.
+    pub to: Option<String>,
+}
+
+/// Diffs every token in `spec` against the chain.
+pub async fn plan(
+    client: &RpcClient,
+    spec: &SpecFile,
+    lockfile: &Lockfile,
+    network: &str,
+    signer: &Pubkey,
+    ipfs_gateway: &str,
+) -> Result<Plan> {
+    let mut tokens = Vec::with_capacity(spec.tokens.len());
+    for (name, token) in &spec.tokens {
+        let locked = lockfile.get(network, name);
+        tokens.push(plan_token(client, name, token, locked, signer, ipfs_gateway).await?);
+    }
+    let untracked = lockfile
+        .networks
+        .get(network)
+        .map(|locked| locked.keys().filter(|name| !spec.tokens.contains_key(*name)).cloned().collect())
+        .unwrap_or_default();
+    Ok(Plan { network: network.to_string(), tokens, untracked })
+}
+
+async fn plan_token(
+    client: &RpcClient,
+    name: &str,
+    spec: &TokenSpec,
+    locked: Option<&LockedToken>,
+    signer: &Pubkey,
+    ipfs_gateway: &str,
+) -> Result<TokenPlan> {
+    let mut plan = TokenPlan { name: name.to_string(), ..TokenPlan::default() };
+    let Some(locked) = locked else {
+        plan.create = true;
+        plan.upload_metadata = spec.metadata.is_some();
+        return Ok(plan);
+    };
+    plan.mint = Some(locked.mint.clone());
+    if locked.unfinished.is_some() {
+        plan.finish = true;
+        return Ok(plan);
+    }
+    let mint: Pubkey = locked.mint.parse().map_err(|e| anyhow!("Invalid mint {} in lockfile: {}", locked.mint, e))?;
+    let current = match ops::token_info(client, &mint).await {
+        Ok(current) => current,
+        Err(e) => {
+            plan.conflicts.push(format!("Locked mint {} could not be read: {:#}", mint, e));
+            return Ok(plan);
+        }
+    };
+    let signer_address = signer.to_string();
+    let is_signer = |authority: &Option<String>| authority.as_deref() == Some(signer_address.as_str());
+
+    if current.decimals != spec.decimals {
+        plan.conflicts.push(format!("Decimals are {} on chain and cannot change to {}", current.decimals, spec.decimals));
+    } else {
+        let target = ops::parse_amount(&spec.supply, spec.decimals)?;
+        match current.supply.cmp(&target) {
+            Ordering::Greater => plan
+                .conflicts
+                .push(format!("Supply is {} on chain, above the target {}", current.ui_supply, spec.supply)),
+            Ordering::Less if is_signer(&current.mint_authority) => plan.mint_amount = target - current.supply,
+            Ordering::Less => plan
+                .conflicts
+                .push("Supply is below target but the signer is not the mint authority".to_string()),
+            Ordering::Equal => {}
+        }
+    }
+
+    for (kind, desired, current_authority) in [
+        (MintAuthority::Mint, &spec.authorities.mint, &current.mint_authority),
+        (MintAuthority::Freeze, &spec.authorities.freeze, &current.freeze_authority),
+    ] {
+        let desired = resolve_authority(desired, signer)?.map(|key| key.to_string());
+        if desired == *current_authority {
+            continue;
+        }
+        if current_authority.is_none() {
+            plan.conflicts.push(format!("The {} authority was revoked and cannot be restored", kind.label()));
+        } else if !is_signer(current_authority) {
+            plan.conflicts.push(format!("The signer does not hold the {} authority", kind.label()));
+        } else {
+            plan.authorities.push(AuthorityChange { kind, to: desired });
+        }
+    }
+
+    let Some(metadata) = &current.metadata else {
+        plan.conflicts.push("The mint has no metadata account".to_string());
+        plan.current = Some(current);
+        return Ok(plan);
+    };
+    let changes = &mut plan.metadata;
+    if metadata.name != spec.name {
+        changes.name = Some(spec.name.clone());
+    }
+    if metadata.symbol != spec.symbol {
+        changes.symbol = Some(spec.symbol.clone());
+    }
+    match &spec.uri {
+        Some(uri) if *uri != metadata.uri => changes.uri = Some(uri.clone()),
+        Some(_) => {}
+        None => {
+            let desired = serde_json::to_value(spec.offchain_metadata()?)?;
+            plan.upload_metadata = fetch_json(&metadata.uri, ipfs_gateway).await.ok() != Some(desired);
+        }
+    }
+    let update_authority = resolve_authority(&spec.authorities.update, signer)?.map(|key| key.to_string());
+    if update_authority.as_deref() != Some(metadata.update_authority.as_str()) {
+        changes.update_authority = update_authority;
+    }
+    changes.make_immutable = metadata.is_mutable && !spec.mutable;
+    let wants_update = !changes.is_empty() || plan.upload_metadata;
+    if wants_update && !metadata.is_mutable {
+        plan.conflicts.push("Metadata is immutable".to_string());
+    } else if wants_update && metadata.update_authority != signer_address {
+        plan.conflicts.push("The signer is not the metadata update authority".to_string());
+    } else if !metadata.is_mutable && spec.mutable {
+        plan.conflicts.push("Metadata is immutable and cannot be made mutable again".to_string());
+    }
+    plan.current = Some(current);
+    Ok(plan)
+}
+
+/// Fetches a JSON document, reading `ipfs://` and `ar://` URIs through a
+/// gateway.
+async fn fetch_json(uri: &str, ipfs_gateway: &str) -> Result<Value> {
+    Ok(reqwest::get(gateway_url(uri, ipfs_gateway)).await?.error_for_status()?.json().await?)
+}
+
+/// One transaction or upload made by [`apply`].
+#[derive(Debug, Serialize)]
+pub struct AppliedStep {
+    pub token: String,
+    pub action: String,
+    pub signature: Option<String>,
+}
+
+/// Where and as whom [`apply`] runs.
+pub struct ApplyContext<'a> {
+    pub client: &'a RpcClient,
+    pub rpc_url: &'a str,
+    pub network: NetworkType,
+    /// Lockfile key for the cluster.
+    pub network_key: &'a str,
+    pub signer: Arc<dyn TransactionSigner>,
+    pub ipfs_gateway: &'a str,
+    /// Needed only when a metadata document has to be uploaded.
+    pub store: Option<Arc<dyn MetadataStore>>,
+    /// Created mints and uploaded metadata are recorded here, like tokens
+    /// the server creates, so storage GC keeps their documents.
+    pub registry: &'a TokenRegistry,
+}
+
+/// Carries out `plan`, recording created mints in `lockfile` (saved to
+/// `lockfile_path` as soon as each mint lands, so a failed run can be
+/// resumed and a half-created token finished) and in the registry.
+/// Refuses to run while the plan has conflicts. `on_step` is called after
+/// each completed step.
+pub async fn apply(
+    context: &ApplyContext<'_>,
+    spec: &SpecFile,
+    plan: Plan,
+    lockfile: &mut Lockfile,
+    lockfile_path: &Path,
+    on_step: impl Fn(&AppliedStep),
+) -> Result<Vec<AppliedStep>> {
+    if let Some((token, conflict)) = plan.conflicts().next() {
+        return Err(anyhow!("Cannot apply while {} has a conflict: {}", token, conflict));
+    }
+    let signer = context.signer.pubkey();
+    let mut steps = Vec::new();
+    let mut record = |token: &str, action: String, signature: Option<String>| {
+        let step = AppliedStep { token: token.to_string(), action, signature };
+        on_step(&step);
+        steps.push(step);
+    };
+
+    for mut token_plan in plan.tokens {
+        let spec = &spec.tokens[&token_plan.name];
+        let name = token_plan.name.clone();
+        if token_plan.create || token_plan.finish {
+            if token_plan.create {
+                let uri = match &spec.uri {
+                    Some(uri) => uri.clone(),
+                    None => {
+                        let uri = upload_metadata(context, spec).await?;
+                        record(&name, format!("Uploaded metadata to {}", uri), None);
+                        uri
+                    }
+                };
+                let params = creation_params(context, spec, uri.clone())?;
+                // Locked as soon as the mint lands, so a failure in a later
+                // transaction is finished by the next run instead of
+                // creating the token again.
+                let lock_mint = |mint: &Pubkey, signature: &Signature| {
+                    lockfile.insert(context.network_key, &name, mint.to_string(), uri);
+                    lockfile.save(lockfile_path)?;
+                    context.registry.record_mint(params.clone(), &signer.to_string(), &mint.to_string(), &signature.to_string())
+                };
+                let result = create_token_reporting_mint(params.clone(), context.rpc_url, |_| {}, lock_mint).await?;
+                record(&name, format!("Created mint {}", result.mint), Some(result.signature));
+            } else {
+                let locked = lockfile.get(context.network_key, &name).ok_or_else(|| anyhow!("{} is not locked", name))?;
+                let mint: Pubkey = locked.mint.parse()?;
+                let params = creation_params(context, spec, locked.unfinished.clone().unwrap_or_default())?;
+                let signatures = finish_token(context.client, params, &mint)
+                    .await
+                    .with_context(|| format!("Failed to finish mint {} from an earlier run", mint))?;
+                let signature = signatures.last().map(ToString::to_string);
+                record(&name, format!("Finished creating mint {}", mint), signature);
+            }
+            lockfile.finished(context.network_key, &name);
+            lockfile.save(lockfile_path)?;
+
+            // Authorities other than the signer are set after creation.
+            let locked = lockfile.get(context.network_key, &name);
+            token_plan = plan_token(context.client, &name, spec, locked, &signer, context.ipfs_gateway).await?;
+            if let Some(conflict) = token_plan.conflicts.first() {
+                return Err(anyhow!("{} was created but cannot be finished: {}", name, conflict));
+            }
+            // Just uploaded; the gateway may not serve it yet.
+            token_plan.upload_metadata = false;
+        }
+
+        let Some(mint) = token_plan.mint.as_deref() else {
+            continue;
+        };
+        let mint: Pubkey = mint.parse()?;
+        let signer_ref = &*context.signer;
+        if token_plan.mint_amount > 0 {
+            let signature = ops::mint_tokens(context.client, signer_ref, &mint, &signer, token_plan.mint_amount).await?;
+            let amount = ops::format_amount(token_plan.mint_amount, spec.decimals);
+            record(&name, format!("Minted {}", amount), Some(signature.to_string()));
+        }
+        let mut changes = token_plan.metadata;
+        if token_plan.upload_metadata {
+            let uri = upload_metadata(context, spec).await?;
+            record(&name, format!("Uploaded metadata to {}", uri), None);
+            changes.uri = Some(uri);
+        }
+        if !changes.is_empty() {
+            let update = MetadataUpdate {
+                name: changes.name,
+                symbol: changes.symbol,
+                uri: changes.uri,
+                new_update_authority: changes.update_authority.as_deref().map(str::parse).transpose()?,
+                make_immutable: changes.make_immutable,
+            };
+            let uri = update.uri.clone();
+            let signature = ops::update_metadata(context.client, signer_ref, &mint, update).await?;
+            if let Some(uri) = uri {
+                register_uri(context, spec, &mint, &uri, &signature.to_string())?;
+            }
+            record(&name, "Updated metadata".to_string(), Some(signature.to_string()));
+        }
+        for change in token_plan.authorities {
+            let to = change.to.as_deref().map(str::parse::<Pubkey>).transpose()?;
+            let signature = ops::set_authority(context.client, signer_ref, &mint, change.kind, to.as_ref()).await?;
+            let to = change.to.unwrap_or_else(|| NO_AUTHORITY.to_string());
+            record(&name, format!("Set {} authority to {}", change.kind.label(), to), Some(signature.to_string()));
+        }
+    }
+    Ok(steps)
+}
+
+/// What a token declared by `spec` is created with, its metadata at `uri`.
+fn creation_params(context: &ApplyContext<'_>, spec: &TokenSpec, uri: String) -> Result<CreateTokenParams> {
+    let signer = context.signer.pubkey();
+    let update_authority = resolve_authority(&spec.authorities.update, &signer)?;
+    Ok(CreateTokenParams {
+        name: spec.name.clone(),
+        symbol: spec.symbol.clone(),
+        description: spec.description.clone(),
+        metadata_uri: uri,
+        decimals: spec.decimals,
+        initial_supply: ops::parse_amount(&spec.supply, spec.decimals)?,
+        // Stays mutable when the update authority still has to be handed
+        // over; the follow-up plan locks it.
+        is_mutable: spec.mutable || update_authority != Some(signer),
+        freeze_authority: spec.authorities.freeze != NO_AUTHORITY,
+        rate_limit: spec.extensions.rate_limit,
+        transfer_fee: spec.extensions.transfer_fee,
+        max_transfer_amount: spec.extensions.max_transfer_amount,
+        network: context.network.clone(),
+        payer: Some(context.signer.clone()),
+    })
+}
+
+/// Points the registry entry of `mint` at its new metadata URI, adding one
+/// for mints created before they were managed by a spec.
+fn register_uri(context: &ApplyContext<'_>, spec: &TokenSpec, mint: &Pubkey, uri: &str, signature: &str) -> Result<()> {
+    let record = match context.registry.get(&mint.to_string())? {
+        Some(mut record) => {
+            record.metadata_uri = uri.to_string();
+            record.params.metadata_uri = uri.to_string();
+            record
+        }
+        None => TokenRecord {
+            mint: mint.to_string(),
+            network: context.network.clone(),
+            signature: signature.to_string(),
+            params: CreateTokenParams {
+                name: spec.name.clone(),
+                symbol: spec.symbol.clone(),
+                description: spec.description.clone(),
+                metadata_uri: uri.to_string(),
+                decimals: spec.decimals,
+                initial_supply: 0,
+                is_mutable: spec.mutable,
+                freeze_authority: spec.authorities.freeze != NO_AUTHORITY,
+                rate_limit: spec.extensions.rate_limit,
+                transfer_fee: spec.extensions.transfer_fee,
+                max_transfer_amount: spec.extensions.max_transfer_amount,
+                network: context.network.clone(),
+                payer: None,
+            },
+            metadata_uri: uri.to_string(),
+            creator: context.signer.pubkey().to_string(),
+            created_at: Utc::now().timestamp(),
+        },
+    };
+    context.registry.record(&record)
+}
+
+async fn upload_metadata(context: &ApplyContext<'_>, spec: &TokenSpec) -> Result<String> {
+    let store = context
+        .store
+        .as_ref()
+        .ok_or_else(|| anyhow!("A storage backend is needed to upload metadata"))?;
+    let document = serde_json::to_value(spec.offchain_metadata()?)?;
+    Ok(store.put_json(&document).await?.uri)
+}
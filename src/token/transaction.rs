@@ -0,0 +1,420 @@
+//! Building and sending v0 transactions. Every transaction this crate sends
+//! is a `VersionedTransaction` with a v0 message, so accounts can be loaded
+//! from address lookup tables wherever a caller has one.
+//!
+//! Instruction lists too large for one transaction go through
+//! [`send_series`], which splits them by serialized size and by simulated
+//! compute units and sends the pieces in order.
+
+use std::{collections::VecDeque, fmt, time::Duration};
+
+use anyhow::{anyhow, Result};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount,
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    message::{v0, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+use super::{
+    signer::{sign_transaction, TransactionSigner},
+    CreationStage,
+};
+
+/// Largest serialized transaction the network accepts.
+pub const MAX_TRANSACTION_SIZE: usize = PACKET_DATA_SIZE;
+/// Most compute units one transaction may use.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+pub(crate) const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Compiles `instructions` into a v0 message paid by `payer`, loading
+/// accounts from `lookup_tables` where it can. Signers and invoked programs
+/// always stay in the message itself.
+pub fn compile_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedMessage> {
+    let message = v0::Message::try_compile(payer, instructions, lookup_tables, recent_blockhash)
+        .map_err(|e| anyhow!("Failed to compile transaction: {}", e))?;
+    Ok(VersionedMessage::V0(message))
+}
+
+/// Serialized size of the signed transaction `instructions` compile to.
+pub fn transaction_size(instructions: &[Instruction], payer: &Pubkey, lookup_tables: &[AddressLookupTableAccount]) -> Result<usize> {
+    let message = compile_message(instructions, payer, lookup_tables, Hash::default())?;
+    let signatures = message.header().num_required_signatures as usize;
+    // The signature count is a compact-u16; it is one byte below 128.
+    Ok(1 + signatures * 64 + message.serialize().len())
+}
+
+/// Signs `instructions` with `payer` and `signers` as one v0 transaction
+/// that loads accounts from `lookup_tables`, simulates, sends and waits for
+/// confirmation, reporting each step through `on_stage`. Use
+/// [`send_series`] when the instructions may not fit one transaction.
+pub async fn send_and_confirm(
+    client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &dyn TransactionSigner,
+    signers: &[&dyn TransactionSigner],
+    lookup_tables: &[AddressLookupTableAccount],
+    on_stage: impl Fn(CreationStage),
+) -> Result<Signature> {
+    let recent_blockhash = client.get_latest_blockhash().await?;
+    let message = compile_message(instructions, &payer.pubkey(), lookup_tables, recent_blockhash)?;
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = sign_transaction(message, &all_signers).await?;
+
+    on_stage(CreationStage::Simulating);
+    let simulation = client.simulate_transaction(&transaction).await?.value;
+    if let Some(err) = simulation.err {
+        let logs = simulation.logs.unwrap_or_default().join("\n");
+        return Err(anyhow!("Simulation failed: {}\n{}", err, logs));
+    }
+
+    on_stage(CreationStage::Sending);
+    let signature = client.send_transaction(&transaction).await?;
+
+    on_stage(CreationStage::Confirming);
+    confirm_signature(client, &signature, &recent_blockhash).await?;
+    Ok(signature)
+}
+
+/// Waits until `signature` lands or `blockhash` expires.
+pub(crate) async fn confirm_signature(client: &RpcClient, signature: &Signature, blockhash: &Hash) -> Result<()> {
+    loop {
+        if let Some(result) = client.get_signature_status(signature).await? {
+            return result.map_err(|e| anyhow!("Transaction {} failed: {}", signature, e));
+        }
+        if !client.is_blockhash_valid(blockhash, CommitmentConfig::processed()).await? {
+            // It may have landed between the two checks.
+            if let Some(result) = client.get_signature_status(signature).await? {
+                return result.map_err(|e| anyhow!("Transaction {} failed: {}", signature, e));
+            }
+            return Err(anyhow!("Transaction {} expired before it was confirmed", signature));
+        }
+        tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+    }
+}
+
+/// Where a series of transactions is: which one, out of how many, and what
+/// is happening to it. `total` can grow while the series runs, when a
+/// transaction turns out to need more compute units than one may use.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeriesProgress {
+    /// 0-based.
+    pub index: usize,
+    pub total: usize,
+    pub stage: CreationStage,
+}
+
+/// A series that stopped part-way. The transactions in `completed` landed
+/// and are not rolled back.
+#[derive(Debug)]
+pub struct SeriesError {
+    pub completed: Vec<Signature>,
+    /// 0-based index of the transaction that failed.
+    pub failed: usize,
+    pub total: usize,
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for SeriesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total == 1 {
+            return write!(f, "{:#}", self.error);
+        }
+        write!(f, "Transaction {} of {} failed", self.failed + 1, self.total)?;
+        if !self.completed.is_empty() {
+            let completed: Vec<String> = self.completed.iter().map(ToString::to_string).collect();
+            write!(f, " after {} landed ({})", completed.len(), completed.join(", "))?;
+        }
+        write!(f, ": {:#}", self.error)
+    }
+}
+
+impl std::error::Error for SeriesError {}
+
+/// Splits `groups` into as few transactions as fit [`MAX_TRANSACTION_SIZE`],
+/// keeping their order. Each group is a set of instructions that must land
+/// together and is never split; a group too large for a transaction on its
+/// own is an error. Room is left for the compute budget instruction
+/// [`send_series`] adds.
+pub fn split_by_size<'a>(
+    groups: &'a [Vec<Instruction>],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Vec<Vec<&'a [Instruction]>>> {
+    let mut transactions = Vec::new();
+    let mut current: Vec<&[Instruction]> = Vec::new();
+    for (i, group) in groups.iter().enumerate() {
+        if group.is_empty() {
+            continue;
+        }
+        current.push(group);
+        if size_with_budget(&current, payer, lookup_tables)? <= MAX_TRANSACTION_SIZE {
+            continue;
+        }
+        current.pop();
+        if current.is_empty() {
+            return Err(anyhow!("Instruction group {} does not fit in one transaction", i + 1));
+        }
+        transactions.push(std::mem::replace(&mut current, vec![group]));
+        if size_with_budget(&current, payer, lookup_tables)? > MAX_TRANSACTION_SIZE {
+            return Err(anyhow!("Instruction group {} does not fit in one transaction", i + 1));
+        }
+    }
+    if !current.is_empty() {
+        transactions.push(current);
+    }
+    Ok(transactions)
+}
+
+fn size_with_budget(groups: &[&[Instruction]], payer: &Pubkey, lookup_tables: &[AddressLookupTableAccount]) -> Result<usize> {
+    transaction_size(&with_compute_limit(groups, MAX_COMPUTE_UNITS), payer, lookup_tables)
+}
+
+/// `groups` flattened behind a compute unit limit instruction.
+fn with_compute_limit(groups: &[&[Instruction]], units: u32) -> Vec<Instruction> {
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(units)];
+    instructions.extend(groups.iter().flat_map(|group| group.iter().cloned()));
+    instructions
+}
+
+/// Sends `groups` as a series of v0 transactions, in order, each confirmed
+/// before the next is simulated so later instructions can rely on earlier
+/// ones (a mint must be initialised before its metadata is created, say).
+///
+/// The series is first split by size (see [`split_by_size`]). Each
+/// transaction is then simulated; one that runs out of compute units is
+/// split again between its groups, and the rest get a compute unit limit
+/// fitted to what they used. Callers must not add compute budget
+/// instructions of their own.
+///
+/// A failure stops the series and is returned as a [`SeriesError`]
+/// recording the transactions that already landed.
+pub async fn send_series(
+    client: &RpcClient,
+    groups: &[Vec<Instruction>],
+    payer: &dyn TransactionSigner,
+    signers: &[&dyn TransactionSigner],
+    lookup_tables: &[AddressLookupTableAccount],
+    on_progress: impl Fn(SeriesProgress),
+) -> Result<Vec<Signature>> {
+    send_series_with(client, groups, payer, signers, lookup_tables, on_progress, |_| Ok(())).await
+}
+
+/// [`send_series`], calling `on_landed` with each signature as soon as its
+/// transaction confirms and before the next is sent. An error from
+/// `on_landed` stops the series like a failed transaction.
+pub async fn send_series_with(
+    client: &RpcClient,
+    groups: &[Vec<Instruction>],
+    payer: &dyn TransactionSigner,
+    signers: &[&dyn TransactionSigner],
+    lookup_tables: &[AddressLookupTableAccount],
+    on_progress: impl Fn(SeriesProgress),
+    mut on_landed: impl FnMut(&Signature) -> Result<()>,
+) -> Result<Vec<Signature>> {
+    let mut pending: VecDeque<Vec<&[Instruction]>> = split_by_size(groups, &payer.pubkey(), lookup_tables)?.into();
+    let mut completed = Vec::new();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+
+    while let Some(transaction) = pending.pop_front() {
+        let index = completed.len();
+        let total = index + 1 + pending.len();
+        let progress = |stage| on_progress(SeriesProgress { index, total, stage });
+        let fail = |completed: &[Signature], error| SeriesError { completed: completed.to_vec(), failed: index, total, error };
+
+        progress(CreationStage::Simulating);
+        let units = match simulate_units(client, &transaction, payer, lookup_tables).await {
+            Ok(units) => units,
+            Err(Simulation::OverBudget) if transaction.len() > 1 => {
+                let second = transaction[transaction.len() / 2..].to_vec();
+                let first = transaction[..transaction.len() / 2].to_vec();
+                pending.push_front(second);
+                pending.push_front(first);
+                continue;
+            }
+            Err(Simulation::OverBudget) => {
+                let error = anyhow!("Instructions need more than {} compute units", MAX_COMPUTE_UNITS);
+                return Err(fail(&completed, error).into());
+            }
+            Err(Simulation::Failed(e)) => return Err(fail(&completed, e).into()),
+        };
+
+        let sent = async {
+            // Simulated usage can drift a little by the time the
+            // transaction executes.
+            let limit = units.saturating_add(units / 10).saturating_add(1_000).min(MAX_COMPUTE_UNITS);
+            let instructions = with_compute_limit(&transaction, limit);
+            let recent_blockhash = client.get_latest_blockhash().await?;
+            let message = compile_message(&instructions, &payer.pubkey(), lookup_tables, recent_blockhash)?;
+            let signed = sign_transaction(message, &all_signers).await?;
+            progress(CreationStage::Sending);
+            let signature = client.send_transaction(&signed).await?;
+            progress(CreationStage::Confirming);
+            confirm_signature(client, &signature, &recent_blockhash).await?;
+            Ok::<_, anyhow::Error>(signature)
+        };
+        match sent.await {
+            Ok(signature) => {
+                completed.push(signature);
+                if let Err(e) = on_landed(&signature) {
+                    return Err(fail(&completed, e.context(format!("{} landed", signature))).into());
+                }
+            }
+            Err(e) => return Err(fail(&completed, e).into()),
+        }
+    }
+    Ok(completed)
+}
+
+enum Simulation {
+    /// Ran out of compute units at the maximum limit.
+    OverBudget,
+    Failed(anyhow::Error),
+}
+
+/// Compute units `groups` use, simulated unsigned at the maximum limit.
+async fn simulate_units(
+    client: &RpcClient,
+    groups: &[&[Instruction]],
+    payer: &dyn TransactionSigner,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<u32, Simulation> {
+    let instructions = with_compute_limit(groups, MAX_COMPUTE_UNITS);
+    let message = compile_message(&instructions, &payer.pubkey(), lookup_tables, Hash::default()).map_err(Simulation::Failed)?;
+    let transaction = VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message,
+    };
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(client.commitment()),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let simulation = client
+        .simulate_transaction_with_config(&transaction, config)
+        .await
+        .map_err(|e| Simulation::Failed(e.into()))?
+        .value;
+    let logs = simulation.logs.unwrap_or_default();
+    match simulation.err {
+        Some(TransactionError::InstructionError(_, InstructionError::ComputationalBudgetExceeded)) => {
+            Err(Simulation::OverBudget)
+        }
+        // BPF programs that run out of units fail this way instead, saying
+        // so only in their log.
+        Some(TransactionError::InstructionError(_, InstructionError::ProgramFailedToComplete))
+            if logs.iter().any(|log| log.contains("exceeded CUs")) =>
+        {
+            Err(Simulation::OverBudget)
+        }
+        Some(err) => {
+            let logs = logs.join("\n");
+            Err(Simulation::Failed(anyhow!("Simulation failed: {}\n{}", err, logs)))
+        }
+        None => Ok(simulation.units_consumed.unwrap_or(MAX_COMPUTE_UNITS as u64).min(MAX_COMPUTE_UNITS as u64) as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use solana_sdk::{instruction::AccountMeta, signature::Keypair};
+
+    use super::*;
+    use crate::testing::MockServer;
+
+    fn instruction(data_len: usize) -> Instruction {
+        Instruction::new_with_bytes(Pubkey::new_unique(), &vec![7; data_len], vec![AccountMeta::new(Pubkey::new_unique(), false)])
+    }
+
+    #[test]
+    fn split_by_size_keeps_groups_whole_and_in_order() {
+        let payer = Pubkey::new_unique();
+        let groups: Vec<Vec<Instruction>> = [300, 200, 150, 500, 0, 100, 400, 250]
+            .iter()
+            .map(|&len| if len == 0 { Vec::new() } else { vec![instruction(len / 2), instruction(len / 2)] })
+            .collect();
+
+        let transactions = split_by_size(&groups, &payer, &[]).unwrap();
+        assert!(transactions.len() > 1);
+        let sent: Vec<&[Instruction]> = transactions.iter().flatten().copied().collect();
+        let expected: Vec<&[Instruction]> = groups.iter().filter(|group| !group.is_empty()).map(Vec::as_slice).collect();
+        assert_eq!(sent, expected);
+
+        for (i, transaction) in transactions.iter().enumerate() {
+            assert!(size_with_budget(transaction, &payer, &[]).unwrap() <= MAX_TRANSACTION_SIZE);
+            // Splitting is greedy: the next group would not have fitted.
+            if let Some(next) = transactions.get(i + 1) {
+                let mut grown = transaction.clone();
+                grown.push(next[0]);
+                assert!(size_with_budget(&grown, &payer, &[]).unwrap() > MAX_TRANSACTION_SIZE);
+            }
+        }
+    }
+
+    #[test]
+    fn split_by_size_refuses_a_group_too_large_for_one_transaction() {
+        let payer = Pubkey::new_unique();
+        let groups = vec![vec![instruction(100)], vec![instruction(600), instruction(600)], vec![instruction(100)]];
+
+        let err = split_by_size(&groups, &payer, &[]).unwrap_err();
+        assert_eq!(err.to_string(), "Instruction group 2 does not fit in one transaction");
+        assert!(split_by_size(&groups[1..2], &payer, &[]).is_err());
+    }
+
+    /// An RPC node answering the client's version check and then one
+    /// simulation.
+    fn simulation(err: Value, logs: &[&str]) -> MockServer {
+        let version = json!({ "jsonrpc": "2.0", "id": 1, "result": { "solana-core": "1.18.0", "feature-set": 1 } });
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": { "slot": 1 },
+                "value": { "err": err, "logs": logs, "accounts": null, "unitsConsumed": 1_400_000, "returnData": null },
+            },
+        });
+        MockServer::start(vec![
+            (200, "application/json", version.to_string().into_bytes()),
+            (200, "application/json", response.to_string().into_bytes()),
+        ])
+    }
+
+    async fn simulate(server: MockServer) -> Result<u32, Simulation> {
+        let client = RpcClient::new(server.url.clone());
+        simulate_units(&client, &[&[instruction(10)]], &Keypair::new(), &[]).await
+    }
+
+    #[tokio::test]
+    async fn simulate_units_recognises_running_out_of_compute_units() {
+        let budget = simulation(json!({ "InstructionError": [1, "ComputationalBudgetExceeded"] }), &[]);
+        assert!(matches!(simulate(budget).await, Err(Simulation::OverBudget)));
+
+        let bpf_logs = ["Program X consumed 1399850 of 1400000 compute units", "Program X failed: exceeded CUs meter at BPF instruction #42"];
+        let bpf = simulation(json!({ "InstructionError": [1, "ProgramFailedToComplete"] }), &bpf_logs);
+        assert!(matches!(simulate(bpf).await, Err(Simulation::OverBudget)));
+
+        let panicked = simulation(json!({ "InstructionError": [1, "ProgramFailedToComplete"] }), &["Program X failed: panicked"]);
+        let Err(Simulation::Failed(err)) = simulate(panicked).await else { panic!("expected a simulation failure") };
+        assert!(format!("{:#}", err).contains("panicked"), "{:#}", err);
+
+        let fits = simulation(Value::Null, &[]);
+        assert!(matches!(simulate(fits).await, Ok(1_400_000)));
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+pub use crate::metadata::MetadataFile;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{process_image, store_image};
+
+#[cfg(target_arch = "wasm32")]
+use {
+    wasm_bindgen::prelude::*,
+    wasm_bindgen_futures::JsFuture,
+    web_sys::File,
+};
+
+/// A processed token logo.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UploadedImage {
+    /// URI of the largest size, for the metadata `image` field.
+    pub image: String,
+    /// Fetchable URL of the same file, for previews.
+    pub preview_url: String,
+    /// Every stored size, largest first.
+    pub files: Vec<MetadataFile>,
+}
+
+/// Sends a logo to the server, which validates, crops, resizes and stores it.
+#[cfg(target_arch = "wasm32")]
+pub async fn upload_file(file: File) -> Result<UploadedImage, String> {
+    let form_data = web_sys::FormData::new()
+        .map_err(|_| "Failed to create FormData")?;
+
+    form_data.append_with_blob("file", &file.into())
+        .map_err(|_| "Failed to append file")?;
+
+    let request = web_sys::Request::new_with_str_and_init(
+        "/v1/images",
+        web_sys::RequestInit::new()
+            .method("POST")
+            .body(Some(&form_data.into()))
+    ).map_err(|_| "Failed to create request")?;
+
+    let window = web_sys::window().ok_or("No window found")?;
+    let resp_value = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|_| "Failed to fetch")?;
+
+    let resp: web_sys::Response = resp_value.dyn_into()
+        .map_err(|_| "Failed to convert response")?;
+
+    let body = JsFuture::from(resp.text().map_err(|_| "Failed to read response")?)
+        .await
+        .map_err(|_| "Failed to await response")?
+        .as_string()
+        .unwrap_or_default();
+
+    if !resp.ok() {
+        return Err(format!("Upload failed ({}): {}", resp.status(), body));
+    }
+
+    serde_json::from_str(&body).map_err(|e| format!("Invalid upload response: {}", e))
+}
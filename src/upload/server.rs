@@ -0,0 +1,121 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, Context, Result};
+use image::{
+    codecs::{png::PngEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    io::{Limits, Reader},
+    ColorType, DynamicImage, ImageEncoder,
+};
+
+use super::{MetadataFile, UploadedImage};
+use crate::{
+    config::{ImageConfig, ImageFormat},
+    metadata::sha256_hex,
+    storage::{MetadataStore, UploadQuota},
+};
+
+/// Formats accepted as logo input, identified by their magic bytes rather
+/// than the declared content type.
+const ACCEPTED_FORMATS: [image::ImageFormat; 4] = [
+    image::ImageFormat::Png,
+    image::ImageFormat::Jpeg,
+    image::ImageFormat::Gif,
+    image::ImageFormat::WebP,
+];
+
+/// An encoded logo at one size.
+pub struct ProcessedImage {
+    pub size: u32,
+    pub mime_type: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// Sniffs, decodes, centre-crops to a square and re-encodes `bytes` at every
+/// configured size, largest first. CPU-bound; run it off the async workers.
+pub fn process_image(bytes: &[u8], config: &ImageConfig) -> Result<Vec<ProcessedImage>> {
+    if bytes.len() > config.max_input_bytes {
+        return Err(anyhow!("Image exceeds {} bytes", config.max_input_bytes));
+    }
+    let format = image::guess_format(bytes)
+        .ok()
+        .filter(|format| ACCEPTED_FORMATS.contains(format))
+        .ok_or_else(|| anyhow!("Unsupported image type; upload a PNG, JPEG, GIF or WebP file"))?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(config.max_dimension);
+    limits.max_image_height = Some(config.max_dimension);
+    let mut reader = Reader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let decoded = reader.decode().context("Failed to decode image")?;
+
+    let side = decoded.width().min(decoded.height());
+    let square = decoded.crop_imm(
+        (decoded.width() - side) / 2,
+        (decoded.height() - side) / 2,
+        side,
+        side,
+    );
+
+    let mut sizes = config.sizes.clone();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    if sizes.is_empty() {
+        return Err(anyhow!("No logo sizes configured"));
+    }
+
+    sizes
+        .into_iter()
+        .map(|size| encode(&square.resize_exact(size, size, FilterType::Lanczos3), size, config.format))
+        .collect()
+}
+
+fn encode(image: &DynamicImage, size: u32, format: ImageFormat) -> Result<ProcessedImage> {
+    let rgba = image.to_rgba8();
+    let mut bytes = Vec::new();
+    let mime_type = match format {
+        ImageFormat::Png => {
+            PngEncoder::new(&mut bytes).write_image(&rgba, size, size, ColorType::Rgba8)?;
+            "image/png"
+        }
+        ImageFormat::Webp => {
+            WebPEncoder::new_lossless(&mut bytes).write_image(&rgba, size, size, ColorType::Rgba8)?;
+            "image/webp"
+        }
+    };
+    Ok(ProcessedImage { size, mime_type, bytes })
+}
+
+/// Processes an uploaded logo for `owner` and stores every size through
+/// `store`, charging the total against the owner's quota.
+pub async fn store_image(
+    store: &dyn MetadataStore,
+    quota: &UploadQuota,
+    owner: &str,
+    bytes: Vec<u8>,
+    config: ImageConfig,
+) -> Result<UploadedImage> {
+    let processed = actix_web::web::block(move || process_image(&bytes, &config)).await??;
+    quota.charge(owner, processed.iter().map(|image| image.bytes.len()).sum())?;
+
+    let mut files = Vec::with_capacity(processed.len());
+    let mut preview_url = None;
+    for image in processed {
+        let extension = image.mime_type.trim_start_matches("image/");
+        let file_name = format!("logo-{}.{}", image.size, extension);
+        let sha256 = sha256_hex(&image.bytes);
+        let stored = store.put(&file_name, image.mime_type, image.bytes).await?;
+        preview_url.get_or_insert(stored.gateway_url);
+        files.push(MetadataFile {
+            uri: stored.uri,
+            mime_type: image.mime_type.to_string(),
+            sha256: Some(sha256),
+        });
+    }
+
+    Ok(UploadedImage {
+        image: files[0].uri.clone(),
+        preview_url: preview_url.unwrap_or_default(),
+        files,
+    })
+}
@@ -0,0 +1,212 @@
+//! Keypair resolution shared by the server, the CLI and tests. A keypair
+//! source is one of:
+//!
+//! - a JSON keypair file, as a path (`~` expands to `$HOME`) or `file://` URI;
+//! - `prompt://`, which asks for a BIP39 seed phrase and optional passphrase
+//!   and derives the key along `m/44'/501'`, or the path given by
+//!   `?key=<account>[/<change>]` or `?full-path=m/44'/501'/...`, as the
+//!   Solana CLI does;
+//! - `env://NAME`, an environment variable holding a base58 secret key (as
+//!   wallets export it) or a JSON byte array.
+//!
+//! Hardware wallets (`usb://`) are not supported and rejected.
+
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Context, Result};
+use bip39::{Language, Mnemonic};
+use serde::Deserialize;
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    signature::{read_keypair_file, Keypair},
+    signer::keypair::{generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path},
+};
+
+/// Solana CLI settings, read from `~/.config/solana/cli/config.yml`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SolanaCliConfig {
+    pub json_rpc_url: Option<String>,
+    pub websocket_url: Option<String>,
+    pub keypair_path: Option<String>,
+    pub commitment: Option<String>,
+}
+
+impl SolanaCliConfig {
+    pub fn default_path() -> PathBuf {
+        expand_home("~/.config/solana/cli/config.yml")
+    }
+
+    /// Reads the CLI config from its default location; a missing file gives
+    /// an empty config.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_yaml::from_str(&contents)
+                .with_context(|| format!("Failed to parse Solana CLI config {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!("Failed to read Solana CLI config {}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// The keypair the Solana CLI would use: its configured `keypair_path`, or
+/// `~/.config/solana/id.json`.
+pub fn default_keypair() -> Result<Keypair> {
+    let source = SolanaCliConfig::load()?
+        .keypair_path
+        .unwrap_or_else(|| "~/.config/solana/id.json".to_string());
+    resolve_keypair(&source)
+}
+
+/// Loads the keypair `source` describes; see the module docs for the forms
+/// accepted.
+pub fn resolve_keypair(source: &str) -> Result<Keypair> {
+    let source = source.trim();
+    if source.starts_with("usb://") {
+        return Err(anyhow!(
+            "Hardware wallets ({}) are not supported; use a keypair file, prompt:// or env://NAME",
+            source
+        ));
+    }
+    if let Some(query) = source.strip_prefix("prompt:") {
+        let query = query.trim_start_matches("//");
+        return keypair_from_prompt(parse_derivation_path(query)?);
+    }
+    if let Some(name) = source.strip_prefix("env://") {
+        let value = std::env::var(name).map_err(|_| anyhow!("Environment variable {} not set", name))?;
+        return keypair_from_secret(&value).with_context(|| format!("Invalid secret key in {}", name));
+    }
+    let path = match source.strip_prefix("file://").or_else(|| source.strip_prefix("file:")) {
+        Some(path) => path,
+        None if source.contains("://") => return Err(anyhow!("Unsupported keypair source {}", source)),
+        None => source,
+    };
+    let path = expand_home(path);
+    read_keypair_file(&path).map_err(|e| anyhow!("Failed to load keypair from {}: {}", path.display(), e))
+}
+
+/// Parses a secret key given as base58 or as a JSON array of 64 bytes.
+pub fn keypair_from_secret(secret: &str) -> Result<Keypair> {
+    let secret = secret.trim();
+    let bytes = if secret.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(secret)?
+    } else {
+        bs58::decode(secret).into_vec()?
+    };
+    Keypair::from_bytes(&bytes).map_err(|e| anyhow!("Expected a 64-byte secret key: {}", e))
+}
+
+/// Derives a keypair from a validated BIP39 seed phrase.
+pub fn keypair_from_seed_phrase(
+    seed_phrase: &str,
+    passphrase: &str,
+    derivation_path: Option<DerivationPath>,
+) -> Result<Keypair> {
+    let seed_phrase = seed_phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    Mnemonic::validate(&seed_phrase, Language::English).map_err(|e| anyhow!("Invalid seed phrase: {}", e))?;
+    let seed = generate_seed_from_seed_phrase_and_passphrase(&seed_phrase, passphrase);
+    keypair_from_seed_and_derivation_path(&seed, derivation_path).map_err(|e| anyhow!("Failed to derive keypair: {}", e))
+}
+
+/// `~/rest` -> `$HOME/rest`; other paths are returned as they are.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Reads the query of a `prompt://` URI. No query means the Solana CLI
+/// default, `m/44'/501'`.
+fn parse_derivation_path(query: &str) -> Result<Option<DerivationPath>> {
+    let Some(query) = query.strip_prefix('?') else {
+        return if query.is_empty() {
+            Ok(None)
+        } else {
+            Err(anyhow!("Unexpected prompt:// source {:?}", query))
+        };
+    };
+    let (key, value) = query
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Expected key=<path> or full-path=<path>, got {:?}", query))?;
+    let path = match key {
+        "key" => DerivationPath::from_key_str(value),
+        "full-path" => DerivationPath::from_absolute_path_str(value),
+        _ => return Err(anyhow!("Unknown prompt:// query {:?}; use key= or full-path=", key)),
+    };
+    path.map(Some).map_err(|e| anyhow!("Invalid derivation path {:?}: {}", value, e))
+}
+
+fn keypair_from_prompt(derivation_path: Option<DerivationPath>) -> Result<Keypair> {
+    let seed_phrase = rpassword::prompt_password("Seed phrase: ")?;
+    let passphrase = rpassword::prompt_password("BIP39 passphrase (press ENTER for none): ")?;
+    keypair_from_seed_phrase(&seed_phrase, &passphrase, derivation_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{write_keypair_file, Signer};
+
+    #[test]
+    fn reads_base58_and_json_secrets_from_the_environment() {
+        let keypair = Keypair::new();
+        std::env::set_var("SOL_TOOLS_TEST_KEYPAIR_BASE58", keypair.to_base58_string());
+        std::env::set_var("SOL_TOOLS_TEST_KEYPAIR_JSON", format!("{:?}", keypair.to_bytes().to_vec()));
+
+        for name in ["SOL_TOOLS_TEST_KEYPAIR_BASE58", "SOL_TOOLS_TEST_KEYPAIR_JSON"] {
+            let resolved = resolve_keypair(&format!("env://{}", name)).unwrap();
+            assert_eq!(resolved.to_bytes(), keypair.to_bytes(), "{}", name);
+        }
+        assert!(resolve_keypair("env://SOL_TOOLS_TEST_KEYPAIR_UNSET").is_err());
+        std::env::set_var("SOL_TOOLS_TEST_KEYPAIR_SHORT", "[1,2,3]");
+        assert!(resolve_keypair("env://SOL_TOOLS_TEST_KEYPAIR_SHORT").is_err());
+    }
+
+    #[test]
+    fn reads_keypair_files_by_path_home_or_uri() {
+        let home = std::env::var("HOME").unwrap();
+        let dir = tempfile::tempdir_in(&home).unwrap();
+        let path = dir.path().join("id.json");
+        let keypair = Keypair::new();
+        write_keypair_file(&keypair, &path).unwrap();
+
+        let relative = path.strip_prefix(&home).unwrap().display().to_string();
+        for source in [path.display().to_string(), format!("~/{}", relative), format!("file://{}", path.display())] {
+            assert_eq!(Signer::pubkey(&resolve_keypair(&source).unwrap()), Signer::pubkey(&keypair), "{}", source);
+        }
+        assert_eq!(expand_home("/etc/~/id.json"), PathBuf::from("/etc/~/id.json"));
+        assert!(resolve_keypair(&dir.path().join("missing.json").display().to_string()).is_err());
+    }
+
+    #[test]
+    fn rejects_hardware_wallets_and_unknown_schemes() {
+        let err = resolve_keypair("usb://ledger?key=0").unwrap_err();
+        assert!(err.to_string().contains("not supported"), "{}", err);
+        assert!(resolve_keypair("s3://bucket/id.json").is_err());
+    }
+
+    #[test]
+    fn parses_prompt_derivation_paths() {
+        assert_eq!(parse_derivation_path("").unwrap(), None);
+        assert_eq!(parse_derivation_path("?key=1/2").unwrap(), Some(DerivationPath::new_bip44(Some(1), Some(2))));
+        assert_eq!(parse_derivation_path("?key=3").unwrap(), Some(DerivationPath::new_bip44(Some(3), None)));
+        assert_eq!(
+            parse_derivation_path("?full-path=m/44'/501'/4'/5'").unwrap(),
+            Some(DerivationPath::new_bip44(Some(4), Some(5)))
+        );
+
+        assert!(parse_derivation_path("?account=1").is_err());
+        assert!(parse_derivation_path("?key").is_err());
+        assert!(parse_derivation_path("?key=x").is_err());
+        assert!(parse_derivation_path("extra").is_err());
+    }
+}
@@ -0,0 +1,339 @@
+//! Passphrase-encrypted keystore. A keystore is a JSON file of named entries,
+//! each holding one secret key sealed with AES-256-GCM-SIV under a key
+//! derived from the passphrase with scrypt. The entry's address is bound as
+//! associated data, so a ciphertext cannot be relabelled with another
+//! address unnoticed. Names are not bound: whole entries can be renamed or
+//! swapped, and `export` then returns the key the entry's address names.
+//!
+//! Decrypted key material lives in zeroizing buffers; `Keypair`s zeroize
+//! their secret on drop.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs,
+    io::{BufRead, ErrorKind, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use aes_gcm_siv::{
+    aead::{Aead, NewAead, Payload},
+    Aes256GcmSiv, Key, Nonce,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+use zeroize::Zeroizing;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// scrypt cost used for new entries: N = 2^15, r = 8, p = 1 (32 MiB).
+/// Entries record their own parameters, so tests seal with a cheaper cost.
+#[cfg(not(test))]
+const SCRYPT_LOG_N: u8 = 15;
+#[cfg(test)]
+const SCRYPT_LOG_N: u8 = 10;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    pub entries: BTreeMap<String, KeystoreEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeystoreEntry {
+    pub address: String,
+    pub kdf: KdfParams,
+    /// Hex-encoded AES-256-GCM-SIV nonce.
+    pub nonce: String,
+    /// Hex-encoded sealed 64-byte secret key.
+    pub ciphertext: String,
+    /// Unix timestamp of the last (re-)encryption.
+    pub updated_at: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum KdfParams {
+    Scrypt { log_n: u8, r: u32, p: u32, salt: String },
+}
+
+impl KdfParams {
+    fn new_scrypt() -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self::Scrypt { log_n: SCRYPT_LOG_N, r: SCRYPT_R, p: SCRYPT_P, salt: hex::encode(salt) }
+    }
+
+    fn derive(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>> {
+        match self {
+            Self::Scrypt { log_n, r, p, salt } => {
+                let params = scrypt::Params::new(*log_n, *r, *p).map_err(|e| anyhow!("Invalid scrypt parameters: {}", e))?;
+                let salt = hex::decode(salt).context("Invalid keystore salt")?;
+                let mut key = Zeroizing::new([0u8; 32]);
+                scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut key[..])
+                    .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+                Ok(key)
+            }
+        }
+    }
+}
+
+impl KeystoreEntry {
+    /// Seals `keypair` under `passphrase` with a fresh salt and nonce.
+    pub fn seal(keypair: &Keypair, passphrase: &str) -> Result<Self> {
+        let kdf = KdfParams::new_scrypt();
+        let key = kdf.derive(passphrase)?;
+        Self::seal_with_key(keypair, kdf, &key)
+    }
+
+    fn seal_with_key(keypair: &Keypair, kdf: KdfParams, key: &[u8; 32]) -> Result<Self> {
+        let address = keypair.pubkey().to_string();
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let secret = Zeroizing::new(keypair.to_bytes());
+        let ciphertext = Aes256GcmSiv::new(Key::from_slice(key))
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &secret[..], aad: address.as_bytes() })
+            .map_err(|_| anyhow!("Failed to encrypt keypair"))?;
+        Ok(Self {
+            address,
+            kdf,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            updated_at: Utc::now().timestamp(),
+        })
+    }
+
+    /// Derives the entry's encryption key, checking it against the
+    /// ciphertext so a wrong passphrase fails here rather than at signing.
+    pub fn unlock(&self, passphrase: &str) -> Result<UnlockedEntry> {
+        let mut unlocked = UnlockedEntry {
+            entry: self.clone(),
+            pubkey: Pubkey::default(),
+            key: self.kdf.derive(passphrase)?,
+        };
+        unlocked.pubkey = unlocked.keypair()?.pubkey();
+        Ok(unlocked)
+    }
+
+    pub fn pubkey(&self) -> Result<Pubkey> {
+        self.address.parse().map_err(|e| anyhow!("Invalid keystore address {}: {}", self.address, e))
+    }
+}
+
+/// A keystore entry whose passphrase has been checked. Holds the derived
+/// encryption key, never the secret key: every [`UnlockedEntry::keypair`]
+/// call decrypts a fresh copy that is wiped once the caller drops it.
+pub struct UnlockedEntry {
+    entry: KeystoreEntry,
+    pubkey: Pubkey,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl UnlockedEntry {
+    pub fn address(&self) -> &str {
+        &self.entry.address
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    pub fn keypair(&self) -> Result<Keypair> {
+        let nonce = hex::decode(&self.entry.nonce).context("Invalid keystore nonce")?;
+        let ciphertext = hex::decode(&self.entry.ciphertext).context("Invalid keystore ciphertext")?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(anyhow!("Invalid keystore nonce length {}", nonce.len()));
+        }
+        let secret = Zeroizing::new(
+            Aes256GcmSiv::new(Key::from_slice(&self.key[..]))
+                .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: self.entry.address.as_bytes() })
+                .map_err(|_| anyhow!("Wrong passphrase or corrupted keystore entry for {}", self.entry.address))?,
+        );
+        let keypair = Keypair::from_bytes(&secret).map_err(|e| anyhow!("Invalid secret key in keystore: {}", e))?;
+        if keypair.pubkey().to_string() != self.entry.address {
+            return Err(anyhow!("Keystore entry does not match its address {}", self.entry.address));
+        }
+        Ok(keypair)
+    }
+}
+
+impl fmt::Debug for UnlockedEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnlockedEntry").field("address", &self.entry.address).finish_non_exhaustive()
+    }
+}
+
+impl Keystore {
+    /// Reads a keystore; a missing file gives an empty one.
+    pub fn open(path: &Path) -> Result<Self> {
+        let keystore: Self = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse keystore {}", path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self { version: KEYSTORE_VERSION, ..Self::default() }),
+            Err(e) => return Err(anyhow!("Failed to read keystore {}: {}", path.display(), e)),
+        };
+        if keystore.version != KEYSTORE_VERSION {
+            return Err(anyhow!("Unsupported keystore version {} in {}", keystore.version, path.display()));
+        }
+        Ok(keystore)
+    }
+
+    /// Writes the keystore through a temporary file so a crash never leaves
+    /// it half-written. The file is only readable by its owner.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        let temp = temp_path(path);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(&temp)
+            .with_context(|| format!("Failed to write keystore {}", temp.display()))?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, path).with_context(|| format!("Failed to replace keystore {}", path.display()))
+    }
+
+    pub fn entry(&self, name: &str) -> Result<&KeystoreEntry> {
+        self.entries.get(name).ok_or_else(|| anyhow!("No keystore entry named {:?}", name))
+    }
+
+    /// Adds `keypair` as `name`; existing entries are never overwritten.
+    pub fn insert(&mut self, name: &str, keypair: &Keypair, passphrase: &str) -> Result<&KeystoreEntry> {
+        if name.trim().is_empty() {
+            return Err(anyhow!("Keystore entry name cannot be empty"));
+        }
+        if self.entries.contains_key(name) {
+            return Err(anyhow!("Keystore entry {:?} already exists", name));
+        }
+        let entry = KeystoreEntry::seal(keypair, passphrase)?;
+        Ok(self.entries.entry(name.to_string()).or_insert(entry))
+    }
+
+    /// Decrypts entry `name`.
+    pub fn export(&self, name: &str, passphrase: &str) -> Result<Keypair> {
+        self.entry(name)?.unlock(passphrase)?.keypair()
+    }
+
+    /// Re-encrypts entry `name` under `new_passphrase` with a fresh salt and
+    /// nonce; the key itself is unchanged.
+    pub fn rotate(&mut self, name: &str, passphrase: &str, new_passphrase: &str) -> Result<&KeystoreEntry> {
+        let keypair = self.export(name, passphrase)?;
+        let entry = KeystoreEntry::seal(&keypair, new_passphrase)?;
+        self.entries.insert(name.to_string(), entry);
+        self.entry(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<KeystoreEntry> {
+        self.entries.remove(name).ok_or_else(|| anyhow!("No keystore entry named {:?}", name))
+    }
+}
+
+/// Reads a passphrase from `env_var` if set, which is then cleared from the
+/// environment; otherwise prompts on the terminal, or reads one line from
+/// stdin when it is not a terminal.
+pub fn read_passphrase(env_var: &str, prompt: &str) -> Result<Zeroizing<String>> {
+    if let Ok(value) = std::env::var(env_var) {
+        std::env::remove_var(env_var);
+        return Ok(Zeroizing::new(value));
+    }
+    if std::io::stdin().is_terminal() {
+        return Ok(Zeroizing::new(rpassword::prompt_password(prompt)?));
+    }
+    let mut line = Zeroizing::new(String::new());
+    std::io::stdin().lock().read_line(&mut line)?;
+    let passphrase = line.trim_end_matches(['\r', '\n']).to_string();
+    Ok(Zeroizing::new(passphrase))
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_keystore(keypair: &Keypair) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore.json");
+        let mut keystore = Keystore::open(&path).unwrap();
+        keystore.insert("main", keypair, "correct horse").unwrap();
+        keystore.save(&path).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let keypair = Keypair::new();
+        let (_dir, path) = saved_keystore(&keypair);
+
+        let keystore = Keystore::open(&path).unwrap();
+        let entry = keystore.entry("main").unwrap();
+        assert_eq!(entry.address, keypair.pubkey().to_string());
+        assert!(!entry.ciphertext.contains(&hex::encode(keypair.secret().as_bytes())));
+        assert_eq!(keystore.export("main", "correct horse").unwrap().to_bytes(), keypair.to_bytes());
+        #[cfg(unix)]
+        assert_eq!(std::os::unix::fs::PermissionsExt::mode(&fs::metadata(&path).unwrap().permissions()) & 0o777, 0o600);
+
+        let mut keystore = keystore;
+        assert!(keystore.insert("main", &Keypair::new(), "other").is_err());
+    }
+
+    #[test]
+    fn refuses_a_wrong_passphrase() {
+        let (_dir, path) = saved_keystore(&Keypair::new());
+        let keystore = Keystore::open(&path).unwrap();
+
+        let err = keystore.entry("main").unwrap().unlock("wrong horse").unwrap_err();
+        assert!(err.to_string().contains("Wrong passphrase"), "{}", err);
+    }
+
+    #[test]
+    fn refuses_a_tampered_address_or_ciphertext() {
+        let (_dir, path) = saved_keystore(&Keypair::new());
+        let entry = Keystore::open(&path).unwrap().entry("main").unwrap().clone();
+
+        let relabelled = KeystoreEntry { address: Keypair::new().pubkey().to_string(), ..entry.clone() };
+        assert!(relabelled.unlock("correct horse").is_err());
+
+        let mut ciphertext = hex::decode(&entry.ciphertext).unwrap();
+        ciphertext[0] ^= 0x01;
+        let corrupted = KeystoreEntry { ciphertext: hex::encode(ciphertext), ..entry.clone() };
+        assert!(corrupted.unlock("correct horse").is_err());
+
+        assert!(entry.unlock("correct horse").is_ok());
+    }
+
+    #[test]
+    fn rotate_reseals_the_same_key() {
+        let keypair = Keypair::new();
+        let (_dir, path) = saved_keystore(&keypair);
+        let mut keystore = Keystore::open(&path).unwrap();
+        let before = keystore.entry("main").unwrap().clone();
+
+        assert!(keystore.rotate("main", "wrong horse", "battery staple").is_err());
+        let after = keystore.rotate("main", "correct horse", "battery staple").unwrap().clone();
+        assert_eq!(after.address, before.address);
+        assert_ne!(after.nonce, before.nonce);
+        let (KdfParams::Scrypt { salt: old_salt, .. }, KdfParams::Scrypt { salt: new_salt, .. }) = (&before.kdf, &after.kdf);
+        assert_ne!(old_salt, new_salt);
+
+        assert!(keystore.export("main", "correct horse").is_err());
+        assert_eq!(keystore.export("main", "battery staple").unwrap().to_bytes(), keypair.to_bytes());
+    }
+}
@@ -1,18 +1,23 @@
 pub mod pinata;
 pub mod contract;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod keypair;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod keystore;
 
 #[cfg(not(target_arch = "wasm32"))]
 use {
-    solana_sdk::signature::{read_keypair_file, Keypair},
+    solana_sdk::signature::Keypair,
     anyhow::{Result, anyhow},
     std::fs::File,
     std::io::Read,
 };
 
+/// Loads a keypair from a file path or any other source
+/// [`keypair::resolve_keypair`] understands.
 #[cfg(not(target_arch = "wasm32"))]
 pub fn load_keypair_from_path(keypair_path: &str) -> Result<Keypair> {
-    read_keypair_file(&keypair_path)
-        .map_err(|e| anyhow!("Failed to load keypair from {}: {}", keypair_path, e))
+    keypair::resolve_keypair(keypair_path)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -1,68 +1,136 @@
-#[cfg(target_arch = "wasm32")]
-pub mod wasm {
-    use wasm_bindgen::prelude::*;
-    use js_sys::Promise;
-    use wasm_bindgen_futures::JsFuture;
-
-    #[wasm_bindgen(module = "/public/pinata.js")]
-    extern "C" {
-        #[wasm_bindgen(js_name = uploadToPinata)]
-        fn upload_to_pinata(api_key: &str, api_secret: &str, data: &JsValue) -> Promise;
-    }
+use leptos::{server_fn::codec::Json, *};
+use serde::{Deserialize, Serialize};
 
-    pub async fn upload_metadata_to_pinata(
-        api_key: &str,
-        secret_key: &str,
-        metadata: &serde_json::Value,
-    ) -> Result<String, String> {
-        let metadata_js = serde_wasm_bindgen::to_value(metadata)
-            .map_err(|e| format!("Failed to convert metadata: {:?}", e))?;
-        
-        let promise = upload_to_pinata(api_key, secret_key, &metadata_js);
-        let result = JsFuture::from(promise)
-            .await
-            .map_err(|e| format!("Failed to upload: {:?}", e))?;
+use crate::metadata::OffchainMetadata;
 
-        let ipfs_hash = js_sys::Reflect::get(&result, &JsValue::from_str("IpfsHash"))
-            .map_err(|_| "Failed to get IpfsHash")?
-            .as_string()
-            .ok_or("Invalid IpfsHash format")?;
+/// Where a metadata document was pinned.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PinnedMetadata {
+    pub uri: String,
+    pub gateway_url: String,
+}
 
-        Ok(format!("https://gateway.pinata.cloud/ipfs/{}", ipfs_hash))
-    }
+/// An object the storage backend holds for this server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PinnedObject {
+    pub uri: String,
+    pub name: Option<String>,
+    pub content_type: Option<String>,
+    pub size: u64,
+    /// Unix timestamp (seconds), when the backend reports one.
+    pub pinned_at: Option<i64>,
+}
+
+/// Outcome of a garbage-collection pass over stored metadata.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GcReport {
+    pub dry_run: bool,
+    /// Metadata documents old enough to be considered.
+    pub scanned: usize,
+    /// Documents a recorded token points to.
+    pub kept: usize,
+    /// Orphaned documents unpinned, or that would be on a dry run.
+    pub unpinned: Vec<String>,
+    /// Unpins that failed, with the reason.
+    pub failed: Vec<String>,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub mod server {
-    use reqwest::Client;
-    use anyhow::Result;
-    use serde_json::Value;
-    use reqwest::multipart::{Form, Part};
-
-    pub async fn upload_metadata_to_pinata(api_key: &str, api_secret: &str, metadata: &Value) -> Result<String> {
-        let client = Client::new();
-        let json_str = serde_json::to_string(metadata)?;
-        let part = Part::text(json_str).file_name("metadata.json");
-        let form = Form::new().part("file", part);
-
-        let result = client
-            .post("https://api.pinata.cloud/pinning/pinFileToIPFS")
-            .header("pinata_api_key", api_key)
-            .header("pinata_secret_api_key", api_secret)
-            .multipart(form)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?;
-
-        let ipfs_hash = result["IpfsHash"].as_str()
-            .ok_or_else(|| anyhow::anyhow!("Failed to get IPFS hash"))?;
-
-        Ok(format!("ipfs://{}", ipfs_hash))
+/// Pins `metadata` through the server's storage backend. The storage
+/// credentials stay on the server and the upload counts against the signed-in
+/// wallet's quota.
+#[server(name = PinMetadata, prefix = "/api", input = Json)]
+pub async fn pin_metadata(metadata: OffchainMetadata) -> Result<PinnedMetadata, ServerFnError> {
+    use actix_web::web;
+    use crate::{auth::require_session, storage::{MetadataStore, UploadQuota}};
+
+    let session = require_session().await?;
+    metadata.validate().map_err(ServerFnError::new)?;
+    let store = leptos_actix::extract::<web::Data<dyn MetadataStore>>().await?;
+    let quota = leptos_actix::extract::<web::Data<UploadQuota>>().await?;
+
+    let bytes = serde_json::to_vec(&metadata).map_err(ServerFnError::new)?;
+    quota.charge(&session.0.address, bytes.len()).map_err(ServerFnError::new)?;
+    let object = store
+        .put("metadata.json", "application/json", bytes)
+        .await
+        .map_err(ServerFnError::new)?;
+
+    Ok(PinnedMetadata {
+        uri: object.uri,
+        gateway_url: object.gateway_url,
+    })
+}
+
+/// Lists what the storage backend holds for this server. Admin only.
+#[server(ListPins, "/api")]
+pub async fn list_pins() -> Result<Vec<PinnedObject>, ServerFnError> {
+    use actix_web::web;
+    use crate::{auth::require_admin, storage::MetadataStore};
+
+    require_admin().await?;
+    let store = leptos_actix::extract::<web::Data<dyn MetadataStore>>().await?;
+    store.list().await.map_err(ServerFnError::new)
+}
+
+/// Releases a stored object by URI or CID. Admin only.
+#[server(UnpinObject, "/api")]
+pub async fn unpin_object(uri: String) -> Result<(), ServerFnError> {
+    use actix_web::web;
+    use crate::{auth::require_admin, storage::MetadataStore};
+
+    require_admin().await?;
+    let store = leptos_actix::extract::<web::Data<dyn MetadataStore>>().await?;
+    store.unpin(&uri).await.map_err(ServerFnError::new)
+}
+
+/// Pins content already on the network by URI or CID. Admin only.
+#[server(RepinObject, "/api")]
+pub async fn repin_object(uri: String) -> Result<PinnedMetadata, ServerFnError> {
+    use actix_web::web;
+    use crate::{auth::require_admin, storage::MetadataStore};
+
+    require_admin().await?;
+    let store = leptos_actix::extract::<web::Data<dyn MetadataStore>>().await?;
+    let object = store.repin(&uri).await.map_err(ServerFnError::new)?;
+    Ok(PinnedMetadata {
+        uri: object.uri,
+        gateway_url: object.gateway_url,
+    })
+}
+
+/// Runs a garbage-collection pass now instead of waiting for the scheduled
+/// one. Admin only.
+#[server(CollectPinGarbage, "/api")]
+pub async fn collect_pin_garbage(dry_run: bool) -> Result<GcReport, ServerFnError> {
+    use actix_web::web;
+    use crate::{
+        auth::require_admin,
+        config::ServerConfig,
+        registry::TokenRegistry,
+        storage::{collect_garbage, MetadataStore},
+    };
+
+    require_admin().await?;
+    let store = leptos_actix::extract::<web::Data<dyn MetadataStore>>().await?;
+    let registry = leptos_actix::extract::<web::Data<TokenRegistry>>().await?;
+    let config = leptos_actix::extract::<web::Data<ServerConfig>>().await?;
+    collect_garbage(&**store, &registry, config.storage.gc.min_age_secs, dry_run)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub mod wasm {
+    use crate::metadata::OffchainMetadata;
+
+    /// Pins metadata via [`super::pin_metadata`] and returns its URI.
+    pub async fn upload_metadata_to_pinata(metadata: &OffchainMetadata) -> Result<String, String> {
+        super::pin_metadata(metadata.clone())
+            .await
+            .map(|pinned| pinned.uri)
+            .map_err(|e| format!("Failed to upload: {}", e))
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 pub use self::wasm::upload_metadata_to_pinata;
-#[cfg(not(target_arch = "wasm32"))]
-pub use self::server::upload_metadata_to_pinata;
\ No newline at end of file
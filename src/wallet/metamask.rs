@@ -3,7 +3,12 @@ use wasm_bindgen_futures::JsFuture;
 use web_sys::window;
 use js_sys::{Function, Promise, Object, Reflect, Array};
 use leptos::SignalUpdate;
+use gloo::timers::future::TimeoutFuture;
 use super::{WalletContext, WalletType, TokenBalance, JsValueWrapper};
+use crate::token::erc20::{explorer_url, parse_quantity, Erc20DeployResult, Erc20Params};
+
+const RECEIPT_POLL_INTERVAL_MS: u32 = 2_000;
+const RECEIPT_POLL_ATTEMPTS: u32 = 90;
 
 pub async fn connect_metamask(ctx: &WalletContext) -> Result<(), String> {
     let window = window().ok_or("No window object found")?;
@@ -135,6 +140,88 @@ async fn get_erc20_balance(ethereum: &Object, address: &str, token_address: &str
     Ok(balance as f64 / 1e18) // Assuming 18 decimals for ERC20
 }
 
+pub(crate) async fn deploy_erc20(from: &str, params: &Erc20Params) -> Result<Erc20DeployResult, String> {
+    let data = params.deployment_data()?;
+
+    let chain_id = ethereum_request("eth_chainId", &Array::new())
+        .await?
+        .as_string()
+        .ok_or("Invalid chain id format")?;
+    let chain_id = parse_quantity(&chain_id)?;
+
+    let tx = Object::new();
+    Reflect::set(&tx, &JsValue::from_str("from"), &JsValue::from_str(from))
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+    Reflect::set(&tx, &JsValue::from_str("data"), &JsValue::from_str(&data))
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+
+    let transaction_hash = ethereum_request("eth_sendTransaction", &Array::of1(&tx))
+        .await?
+        .as_string()
+        .ok_or("Invalid transaction hash format")?;
+
+    let contract_address = wait_for_contract_address(&transaction_hash).await?;
+
+    Ok(Erc20DeployResult {
+        explorer_url: explorer_url(chain_id, &contract_address),
+        contract_address,
+        transaction_hash,
+        chain_id,
+    })
+}
+
+async fn wait_for_contract_address(transaction_hash: &str) -> Result<String, String> {
+    let params = Array::of1(&JsValue::from_str(transaction_hash));
+
+    for _ in 0..RECEIPT_POLL_ATTEMPTS {
+        let receipt = ethereum_request("eth_getTransactionReceipt", &params).await?;
+        if !receipt.is_null() && !receipt.is_undefined() {
+            let status = Reflect::get(&receipt, &JsValue::from_str("status"))
+                .map_err(|e| String::from(JsValueWrapper::from(e)))?
+                .as_string();
+            if status.as_deref() != Some("0x1") {
+                return Err(format!("Deployment transaction {} reverted", transaction_hash));
+            }
+
+            return Reflect::get(&receipt, &JsValue::from_str("contractAddress"))
+                .map_err(|e| String::from(JsValueWrapper::from(e)))?
+                .as_string()
+                .ok_or_else(|| "Receipt has no contract address".to_string());
+        }
+        TimeoutFuture::new(RECEIPT_POLL_INTERVAL_MS).await;
+    }
+
+    Err(format!("Timed out waiting for transaction {}", transaction_hash))
+}
+
+async fn ethereum_request(method: &str, params: &Array) -> Result<JsValue, String> {
+    let window = window().ok_or("No window object found")?;
+    let ethereum = Reflect::get(&window, &JsValue::from_str("ethereum"))
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+
+    if ethereum.is_undefined() {
+        return Err("MetaMask not installed".to_string());
+    }
+
+    let request = Object::new();
+    Reflect::set(&request, &JsValue::from_str("method"), &JsValue::from_str(method))
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+    Reflect::set(&request, &JsValue::from_str("params"), params)
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+
+    let request_fn = Reflect::get(&ethereum, &JsValue::from_str("request"))
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?
+        .dyn_into::<Function>()
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+
+    let promise = request_fn.call1(&ethereum, &request)
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+
+    JsFuture::from(Promise::from(promise))
+        .await
+        .map_err(|e| String::from(JsValueWrapper::from(e)))
+}
+
 fn get_known_tokens() -> Vec<TokenInfo> {
     vec![
         TokenInfo {
@@ -5,6 +5,8 @@ mod metamask;
 
 use leptos::*;
 use serde::{Deserialize, Serialize};
+use crate::auth::{current_session, request_sign_in, sign_out, verify_sign_in, SessionInfo};
+use crate::token::erc20::{Erc20DeployResult, Erc20Params};
 
 #[cfg(target_arch = "wasm32")]
 use {
@@ -16,7 +18,7 @@ use {
 
 #[cfg(target_arch = "wasm32")]
 use self::{
-    phantom::connect_phantom,
+    phantom::{connect_phantom, sign_message_phantom},
     metamask::connect_metamask,
 };
 
@@ -38,6 +40,7 @@ pub struct WalletState {
     pub connecting: bool,
     pub sol_balance: f64,
     pub token_balances: Vec<TokenBalance>,
+    pub session: Option<SessionInfo>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -227,6 +230,23 @@ impl WalletContext {
         Err("Wallet operations not supported in server environment".to_string())
     }
 
+    #[cfg(target_arch = "wasm32")]
+    pub async fn deploy_erc20(&self, params: &Erc20Params) -> Result<Erc20DeployResult, String> {
+        let state = self.state.get();
+        match (state.wallet_type, state.address) {
+            (Some(WalletType::MetaMask), Some(address)) => {
+                metamask::deploy_erc20(&address, params).await
+            }
+            (Some(_), Some(_)) => Err("ERC-20 deployment requires MetaMask".to_string()),
+            _ => Err("Wallet not connected".to_string()),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn deploy_erc20(&self, _params: &Erc20Params) -> Result<Erc20DeployResult, String> {
+        Err("Wallet operations not supported in server environment".to_string())
+    }
+
     pub fn disconnect(&self) {
         self.state.update(|state| {
             state.connected = false;
@@ -236,9 +256,60 @@ impl WalletContext {
             state.connecting = false;
             state.sol_balance = 0.0;
             state.token_balances = Vec::new();
+            state.session = None;
         });
     }
 
+    #[cfg(target_arch = "wasm32")]
+    pub async fn sign_message(&self, message: &str) -> Result<Vec<u8>, String> {
+        match self.state.get().wallet_type {
+            Some(WalletType::Phantom) => sign_message_phantom(message.as_bytes()).await,
+            Some(WalletType::MetaMask) => Err("Sign-in requires a Solana wallet".to_string()),
+            None => Err("No wallet connected".to_string()),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn sign_message(&self, _message: &str) -> Result<Vec<u8>, String> {
+        Err("Wallet operations not supported in server environment".to_string())
+    }
+
+    /// Runs the Sign-In With Solana exchange: the server issues a nonce
+    /// message, the wallet signs it and the server answers with a session
+    /// cookie that the mutating endpoints require.
+    pub async fn sign_in(&self) -> Result<SessionInfo, String> {
+        let address = self.state.get().address.ok_or("Wallet not connected")?;
+        let challenge = request_sign_in(address).await.map_err(|e| e.to_string())?;
+        let signature = self.sign_message(&challenge.message).await?;
+        let session = verify_sign_in(challenge.nonce, bs58::encode(signature).into_string())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.state.update(|state| {
+            state.session = Some(session.clone());
+        });
+        Ok(session)
+    }
+
+    /// Picks up a session left over from an earlier page load, as long as it
+    /// belongs to the wallet that is connected now.
+    pub async fn restore_session(&self) {
+        if let Ok(Some(session)) = current_session().await {
+            self.state.update(|state| {
+                if state.address.as_deref() == Some(session.address.as_str()) {
+                    state.session = Some(session);
+                }
+            });
+        }
+    }
+
+    pub async fn sign_out(&self) {
+        if self.state.get().session.is_some() {
+            let _ = sign_out().await;
+        }
+        self.disconnect();
+    }
+
     #[cfg(target_arch = "wasm32")]
     pub async fn connect(&self, wallet_type: WalletType) -> Result<(), String> {
         match wallet_type {
@@ -263,6 +334,7 @@ pub fn WalletProvider(children: Children) -> impl IntoView {
         connecting: false,
         sol_balance: 0.0,
         token_balances: Vec::new(),
+        session: None,
     });
 
     provide_context(WalletContext::new(state));
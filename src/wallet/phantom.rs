@@ -63,4 +63,28 @@ pub async fn connect_phantom(wallet_context: &WalletContext) -> Result<(), Strin
         let _ = wallet_context;
         Err("Phantom wallet connection not supported on this platform".to_string())
     }
-}
\ No newline at end of file
+}
+
+pub async fn sign_message_phantom(message: &[u8]) -> Result<Vec<u8>, String> {
+    let window = web_sys::window().ok_or("No window object")?;
+    let solana = Reflect::get(&window, &JsValue::from_str("solana"))
+        .map_err(|e| String::from(JsValueWrapper::from(e)))?;
+
+    let sign_message_fn = Reflect::get(&solana, &JsValue::from_str("signMessage"))
+        .map_err(|_| "Failed to get signMessage function")?
+        .dyn_into::<Function>()
+        .map_err(|_| "signMessage is not a function")?;
+
+    let encoded = js_sys::Uint8Array::from(message);
+    let promise = sign_message_fn.call2(&solana, &encoded, &JsValue::from_str("utf8"))
+        .map_err(|_| "Failed to call signMessage")?;
+
+    let result = JsFuture::from(Promise::from(promise))
+        .await
+        .map_err(|e| format!("Signing rejected: {:?}", e))?;
+
+    let signature = Reflect::get(&result, &JsValue::from_str("signature"))
+        .map_err(|_| "Failed to get signature")?;
+
+    Ok(js_sys::Uint8Array::new(&signature).to_vec())
+}
@@ -0,0 +1,685 @@
+//! Deploys `contracts/erc20/erc20.hex` to a small in-process EVM and drives
+//! it through its ERC-20 interface. The interpreter implements only the
+//! opcodes the contract uses, with EVM stack, memory and storage semantics
+//! and no gas accounting; anything else stops the test. The committed hex
+//! is also checked against `erc20.etk` with a small assembler for the subset
+//! of ETK the sources use.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigUint;
+use sha3::{Digest, Keccak256};
+use sol_tools::token::erc20::Erc20Params;
+
+const INITCODE: &str = include_str!("../contracts/erc20/erc20.hex");
+
+type Word = [u8; 32];
+
+#[derive(Clone, Debug, PartialEq)]
+struct Log {
+    topics: Vec<Word>,
+    data: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Return(Vec<u8>),
+    Revert,
+}
+
+struct Evm {
+    code: Vec<u8>,
+    storage: HashMap<Word, Word>,
+    logs: Vec<Log>,
+}
+
+fn keccak(bytes: &[u8]) -> Word {
+    Keccak256::digest(bytes).into()
+}
+
+fn to_big(word: &Word) -> BigUint {
+    BigUint::from_bytes_be(word)
+}
+
+fn to_word(value: &BigUint) -> Word {
+    let modulus = BigUint::from(1u8) << 256u32;
+    let bytes = (value % &modulus).to_bytes_be();
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+fn small(value: u64) -> Word {
+    to_word(&BigUint::from(value))
+}
+
+fn address(byte: u8) -> Word {
+    let mut word = [0u8; 32];
+    word[12..].fill(byte);
+    word
+}
+
+fn word_to_usize(word: &Word) -> usize {
+    assert!(word[..24].iter().all(|b| *b == 0), "offset out of range");
+    u64::from_be_bytes(word[24..].try_into().unwrap()) as usize
+}
+
+/// Offsets of `JUMPDEST`s that are instructions rather than push data.
+fn jump_destinations(code: &[u8]) -> HashSet<usize> {
+    let mut destinations = HashSet::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        match code[pc] {
+            0x5b => {
+                destinations.insert(pc);
+            }
+            op @ 0x60..=0x7f => pc += (op - 0x5f) as usize,
+            _ => {}
+        }
+        pc += 1;
+    }
+    destinations
+}
+
+fn read_padded(source: &[u8], offset: usize, len: usize) -> Vec<u8> {
+    (0..len).map(|i| offset.checked_add(i).and_then(|j| source.get(j)).copied().unwrap_or(0)).collect()
+}
+
+/// Runs `code` as `caller` with `input` as calldata (or, for initcode, as
+/// nothing), against `storage`. Storage writes and logs are only kept when
+/// the frame returns.
+fn execute(code: &[u8], input: &[u8], caller: Word, storage: &mut HashMap<Word, Word>, logs: &mut Vec<Log>) -> Outcome {
+    let destinations = jump_destinations(code);
+    let mut writes = storage.clone();
+    let mut emitted = Vec::new();
+    let mut stack: Vec<Word> = Vec::new();
+    let mut memory: Vec<u8> = Vec::new();
+    let mut pc = 0;
+
+    fn touch(memory: &mut Vec<u8>, offset: usize, len: usize) {
+        if len > 0 && memory.len() < offset + len {
+            memory.resize((offset + len + 31) / 32 * 32, 0);
+        }
+    }
+
+    let outcome = loop {
+        let Some(&op) = code.get(pc) else { break Outcome::Return(Vec::new()) };
+        pc += 1;
+        let mut pop = || stack.pop().expect("stack underflow");
+        match op {
+            0x00 => break Outcome::Return(Vec::new()),
+            0x01..=0x04 | 0x10 | 0x11 | 0x14 | 0x16 | 0x17 | 0x1b | 0x1c => {
+                let (a, b) = (pop(), pop());
+                let (x, y) = (to_big(&a), to_big(&b));
+                let modulus = BigUint::from(1u8) << 256u32;
+                let result = match op {
+                    0x01 => to_word(&(x + y)),
+                    0x02 => to_word(&(x * y)),
+                    0x03 => to_word(&(x + &modulus - y)),
+                    0x04 if y == BigUint::from(0u8) => [0u8; 32],
+                    0x04 => to_word(&(x / y)),
+                    0x10 => small((x < y) as u64),
+                    0x11 => small((x > y) as u64),
+                    0x14 => small((a == b) as u64),
+                    0x16 => std::array::from_fn(|i| a[i] & b[i]),
+                    0x17 => std::array::from_fn(|i| a[i] | b[i]),
+                    0x1b if x >= BigUint::from(256u16) => [0u8; 32],
+                    0x1b => to_word(&(y << word_to_usize(&a))),
+                    _ if x >= BigUint::from(256u16) => [0u8; 32],
+                    _ => to_word(&(y >> word_to_usize(&a))),
+                };
+                stack.push(result);
+            }
+            0x15 => {
+                let a = pop();
+                stack.push(small((a == [0u8; 32]) as u64));
+            }
+            0x19 => {
+                let a = pop();
+                stack.push(a.map(|b| !b));
+            }
+            0x20 => {
+                let (offset, len) = (word_to_usize(&pop()), word_to_usize(&pop()));
+                touch(&mut memory, offset, len);
+                stack.push(keccak(&memory[offset..offset + len]));
+            }
+            0x33 => stack.push(caller),
+            0x34 => stack.push([0u8; 32]),
+            0x35 => {
+                let offset = word_to_usize(&pop());
+                stack.push(read_padded(input, offset, 32).try_into().unwrap());
+            }
+            0x38 => stack.push(small(code.len() as u64)),
+            0x39 => {
+                let (dest, offset, len) = (word_to_usize(&pop()), word_to_usize(&pop()), word_to_usize(&pop()));
+                touch(&mut memory, dest, len);
+                memory[dest..dest + len].copy_from_slice(&read_padded(code, offset, len));
+            }
+            0x50 => {
+                pop();
+            }
+            0x51 => {
+                let offset = word_to_usize(&pop());
+                touch(&mut memory, offset, 32);
+                stack.push(memory[offset..offset + 32].try_into().unwrap());
+            }
+            0x52 => {
+                let (offset, value) = (word_to_usize(&pop()), pop());
+                touch(&mut memory, offset, 32);
+                memory[offset..offset + 32].copy_from_slice(&value);
+            }
+            0x54 => {
+                let key = pop();
+                stack.push(writes.get(&key).copied().unwrap_or([0u8; 32]));
+            }
+            0x55 => {
+                let (key, value) = (pop(), pop());
+                writes.insert(key, value);
+            }
+            0x56 | 0x57 => {
+                let dest = word_to_usize(&pop());
+                let taken = op == 0x56 || pop() != [0u8; 32];
+                if taken {
+                    assert!(destinations.contains(&dest), "jump to {} is not a JUMPDEST", dest);
+                    pc = dest;
+                }
+            }
+            0x5b => {}
+            0x60..=0x7f => {
+                let len = (op - 0x5f) as usize;
+                let bytes = read_padded(code, pc, len);
+                let mut word = [0u8; 32];
+                word[32 - len..].copy_from_slice(&bytes);
+                stack.push(word);
+                pc += len;
+            }
+            0x80..=0x8f => {
+                let depth = (op - 0x80) as usize;
+                stack.push(stack[stack.len() - 1 - depth]);
+            }
+            0x90..=0x9f => {
+                let depth = (op - 0x8f) as usize;
+                let top = stack.len() - 1;
+                stack.swap(top, top - depth);
+            }
+            0xa0..=0xa4 => {
+                let (offset, len) = (word_to_usize(&pop()), word_to_usize(&pop()));
+                let topics = (0..op - 0xa0).map(|_| pop()).collect();
+                touch(&mut memory, offset, len);
+                emitted.push(Log { topics, data: memory[offset..offset + len].to_vec() });
+            }
+            0xf3 => {
+                let (offset, len) = (word_to_usize(&pop()), word_to_usize(&pop()));
+                touch(&mut memory, offset, len);
+                break Outcome::Return(memory[offset..offset + len].to_vec());
+            }
+            0xfd => break Outcome::Revert,
+            op => panic!("opcode {:#04x} at {} is not implemented", op, pc - 1),
+        }
+        assert!(stack.len() <= 1024, "stack overflow");
+    };
+    if let Outcome::Return(_) = outcome {
+        *storage = writes;
+        logs.extend(emitted);
+    }
+    outcome
+}
+
+impl Evm {
+    /// Runs the deployment payload and keeps the runtime it returns.
+    fn deploy(params: &Erc20Params, deployer: Word) -> (Self, Vec<Log>) {
+        let payload = params.deployment_data().expect("valid params");
+        let initcode = hex::decode(payload.trim_start_matches("0x")).expect("hex payload");
+        let mut storage = HashMap::new();
+        let mut logs = Vec::new();
+        let Outcome::Return(code) = execute(&initcode, &[], deployer, &mut storage, &mut logs) else {
+            panic!("deployment reverted");
+        };
+        assert!(!code.is_empty(), "deployment returned no code");
+        (Self { code, storage, logs: Vec::new() }, logs)
+    }
+
+    fn call(&mut self, caller: Word, signature: &str, args: &[Word]) -> Outcome {
+        let mut input = keccak(signature.as_bytes())[..4].to_vec();
+        for arg in args {
+            input.extend_from_slice(arg);
+        }
+        execute(&self.code, &input, caller, &mut self.storage, &mut self.logs)
+    }
+
+    fn word(&mut self, signature: &str, args: &[Word]) -> Word {
+        match self.call(address(0xee), signature, args) {
+            Outcome::Return(data) => data.try_into().expect("one word"),
+            Outcome::Revert => panic!("{} reverted", signature),
+        }
+    }
+
+    fn balance(&mut self, owner: Word) -> Word {
+        self.word("balanceOf(address)", &[owner])
+    }
+
+    fn take_logs(&mut self) -> Vec<Log> {
+        std::mem::take(&mut self.logs)
+    }
+}
+
+/// One line of ETK source after macro expansion. `%push(expr)` immediates
+/// are sized once every label is placed.
+enum Item {
+    Bytes(Vec<u8>),
+    Label(String),
+    Push(Vec<String>),
+}
+
+fn opcode(mnemonic: &str) -> u8 {
+    let numbered = |prefix: &str, base: u8| {
+        mnemonic.strip_prefix(prefix).and_then(|n| n.parse::<u8>().ok()).map(|n| base + n)
+    };
+    let op = match mnemonic {
+        "stop" => Some(0x00),
+        "add" => Some(0x01),
+        "mul" => Some(0x02),
+        "sub" => Some(0x03),
+        "lt" => Some(0x10),
+        "gt" => Some(0x11),
+        "eq" => Some(0x14),
+        "iszero" => Some(0x15),
+        "and" => Some(0x16),
+        "or" => Some(0x17),
+        "not" => Some(0x19),
+        "shl" => Some(0x1b),
+        "shr" => Some(0x1c),
+        "keccak256" => Some(0x20),
+        "caller" => Some(0x33),
+        "callvalue" => Some(0x34),
+        "calldataload" => Some(0x35),
+        "codesize" => Some(0x38),
+        "codecopy" => Some(0x39),
+        "pop" => Some(0x50),
+        "mload" => Some(0x51),
+        "mstore" => Some(0x52),
+        "sload" => Some(0x54),
+        "sstore" => Some(0x55),
+        "jump" => Some(0x56),
+        "jumpi" => Some(0x57),
+        "jumpdest" => Some(0x5b),
+        "return" => Some(0xf3),
+        "revert" => Some(0xfd),
+        _ => numbered("push", 0x5f).or(numbered("dup", 0x7f)).or(numbered("swap", 0x8f)).or(numbered("log", 0xa0)),
+    };
+    op.unwrap_or_else(|| panic!("unknown instruction {}", mnemonic))
+}
+
+fn immediate(text: &str, len: usize) -> Vec<u8> {
+    let quoted = |function: &str| {
+        text.strip_prefix(function).and_then(|rest| rest.strip_prefix("(\"")).and_then(|rest| rest.strip_suffix("\")"))
+    };
+    let bytes = if let Some(signature) = quoted("topic") {
+        keccak(signature.as_bytes()).to_vec()
+    } else if let Some(signature) = quoted("selector") {
+        keccak(signature.as_bytes())[..4].to_vec()
+    } else {
+        let digits = text.strip_prefix("0x").unwrap_or_else(|| panic!("unsupported immediate {}", text));
+        hex::decode(format!("{:0>width$}", digits, width = len * 2)).expect("hex immediate")
+    };
+    assert_eq!(bytes.len(), len, "immediate {} does not fit push{}", text, len);
+    bytes
+}
+
+/// Splits `name(a, b)` into the name and its arguments.
+fn invocation(text: &str) -> (&str, Vec<&str>) {
+    let (name, rest) = text.split_once('(').unwrap_or_else(|| panic!("expected arguments in {}", text));
+    let args = rest.strip_suffix(')').unwrap_or_else(|| panic!("unclosed arguments in {}", text));
+    (name, args.split(',').map(str::trim).filter(|arg| !arg.is_empty()).collect())
+}
+
+fn parse(path: &std::path::Path, items: &mut Vec<Item>) {
+    let source = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let lines: Vec<&str> = source.lines().map(|line| line.split('#').next().unwrap().trim()).collect();
+    let mut macros: HashMap<&str, (Vec<&str>, Vec<&str>)> = HashMap::new();
+    let mut queue: Vec<String> = Vec::new();
+    let mut lines = lines.into_iter();
+    while let Some(line) = lines.next() {
+        if let Some(header) = line.strip_prefix("%macro ") {
+            let (name, params) = invocation(header);
+            let body = lines.by_ref().take_while(|line| *line != "%end").collect();
+            macros.insert(name, (params, body));
+        } else {
+            queue.push(line.to_string());
+        }
+    }
+
+    queue.reverse();
+    while let Some(line) = queue.pop() {
+        if line.is_empty() {
+            continue;
+        } else if let Some(label) = line.strip_suffix(':') {
+            items.push(Item::Label(label.to_string()));
+        } else if let Some(directive) = line.strip_prefix('%') {
+            let (name, args) = invocation(directive);
+            match name {
+                "push" => items.push(Item::Push(args[0].split('-').map(|term| term.trim().to_string()).collect())),
+                "include" => {
+                    let included = path.with_file_name(args[0].trim_matches('"'));
+                    items.push(Item::Bytes(assemble(&included)));
+                }
+                _ => {
+                    let (params, body) = macros.get(name).unwrap_or_else(|| panic!("unknown macro {}", name));
+                    for body_line in body.iter().rev() {
+                        let expanded = params
+                            .iter()
+                            .zip(&args)
+                            .fold(body_line.to_string(), |line, (param, arg)| line.replace(&format!("${}", param), arg));
+                        queue.push(expanded);
+                    }
+                }
+            }
+        } else {
+            let (mnemonic, argument) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+            let op = opcode(mnemonic);
+            let mut bytes = vec![op];
+            if (0x60..=0x7f).contains(&op) {
+                bytes.extend(immediate(argument.trim(), (op - 0x5f) as usize));
+            }
+            items.push(Item::Bytes(bytes));
+        }
+    }
+}
+
+/// Assembles the subset of ETK the contract sources use: instructions,
+/// labels, `%macro`s, `%push(label)` and `%push(label - label)`,
+/// `topic(..)`/`selector(..)` immediates and `%include`, which assembles the
+/// included file on its own with offsets starting at zero. Like `eas`, every
+/// `%push` starts as `push1` and grows until its value fits.
+fn assemble(path: &std::path::Path) -> Vec<u8> {
+    let mut items = Vec::new();
+    parse(path, &mut items);
+    let mut sizes = vec![1usize; items.len()];
+    loop {
+        let mut labels = HashMap::new();
+        let mut offset = 0;
+        for (item, size) in items.iter().zip(&sizes) {
+            offset += match item {
+                Item::Bytes(bytes) => bytes.len(),
+                Item::Label(label) => {
+                    assert!(labels.insert(label.as_str(), offset).is_none(), "duplicate label {}", label);
+                    0
+                }
+                Item::Push(_) => 1 + size,
+            };
+        }
+        let value = |terms: &[String]| {
+            let position = |term: &String| *labels.get(term.as_str()).unwrap_or_else(|| panic!("unknown label {}", term));
+            terms[1..].iter().fold(position(&terms[0]), |value, term| value - position(term))
+        };
+
+        let mut grown = false;
+        for (item, size) in items.iter().zip(sizes.iter_mut()) {
+            if let Item::Push(terms) = item {
+                let needed = (usize::BITS - value(terms).leading_zeros()).div_ceil(8).max(1) as usize;
+                if needed > *size {
+                    *size = needed;
+                    grown = true;
+                }
+            }
+        }
+        if !grown {
+            let mut code = Vec::with_capacity(offset);
+            for (item, size) in items.iter().zip(&sizes) {
+                match item {
+                    Item::Bytes(bytes) => code.extend_from_slice(bytes),
+                    Item::Label(_) => {}
+                    Item::Push(terms) => {
+                        code.push(0x5f + *size as u8);
+                        code.extend_from_slice(&value(terms).to_be_bytes()[std::mem::size_of::<usize>() - size..]);
+                    }
+                }
+            }
+            return code;
+        }
+    }
+}
+
+fn params() -> Erc20Params {
+    Erc20Params {
+        name: "Test Token".to_string(),
+        symbol: "TT".to_string(),
+        decimals: 6,
+        initial_supply: 1_000,
+        cap: None,
+        mintable: true,
+        burnable: true,
+    }
+}
+
+fn units(amount: u64) -> Word {
+    small(amount * 1_000_000)
+}
+
+fn transfer_log(from: Word, to: Word, amount: Word) -> Log {
+    Log { topics: vec![keccak(b"Transfer(address,address,uint256)"), from, to], data: amount.to_vec() }
+}
+
+fn approval_log(owner: Word, spender: Word, amount: Word) -> Log {
+    Log { topics: vec![keccak(b"Approval(address,address,uint256)"), owner, spender], data: amount.to_vec() }
+}
+
+fn success() -> Outcome {
+    Outcome::Return(small(1).to_vec())
+}
+
+const DEPLOYER: u8 = 0xd0;
+const ALICE: u8 = 0xa1;
+const BOB: u8 = 0xb0;
+const CAROL: u8 = 0xc0;
+
+#[test]
+fn deployment_sets_metadata_and_mints_to_the_deployer() {
+    let deployer = address(DEPLOYER);
+    let (mut evm, logs) = Evm::deploy(&params(), deployer);
+
+    assert_eq!(
+        logs,
+        vec![
+            Log {
+                topics: vec![keccak(b"OwnershipTransferred(address,address)"), [0u8; 32], deployer],
+                data: Vec::new(),
+            },
+            transfer_log([0u8; 32], deployer, units(1_000)),
+        ]
+    );
+    assert_eq!(evm.word("totalSupply()", &[]), units(1_000));
+    assert_eq!(evm.balance(deployer), units(1_000));
+    assert_eq!(evm.word("decimals()", &[]), small(6));
+    assert_eq!(evm.word("owner()", &[]), deployer);
+
+    let Outcome::Return(name) = evm.call(deployer, "name()", &[]) else { panic!("name() reverted") };
+    assert_eq!(name[..32], small(0x20));
+    assert_eq!(name[32..64], small(10));
+    assert_eq!(&name[64..74], b"Test Token");
+}
+
+#[test]
+fn deployment_rejects_a_cap_below_the_initial_supply() {
+    // `Erc20Params::validate` refuses this, so patch the cap argument into
+    // a valid payload to check that the contract refuses it too.
+    let mut payload = hex::decode(params().deployment_data().unwrap().trim_start_matches("0x")).unwrap();
+    let cap = INITCODE.trim().len() / 2 + 4 * 32;
+    payload[cap..cap + 32].copy_from_slice(&units(10));
+    let outcome = execute(&payload, &[], address(DEPLOYER), &mut HashMap::new(), &mut Vec::new());
+    assert_eq!(outcome, Outcome::Revert);
+}
+
+#[test]
+fn transfer_moves_balance_and_emits_transfer() {
+    let (deployer, alice) = (address(DEPLOYER), address(ALICE));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+
+    assert_eq!(evm.call(deployer, "transfer(address,uint256)", &[alice, units(250)]), success());
+    assert_eq!(evm.take_logs(), vec![transfer_log(deployer, alice, units(250))]);
+    assert_eq!(evm.balance(deployer), units(750));
+    assert_eq!(evm.balance(alice), units(250));
+    assert_eq!(evm.word("totalSupply()", &[]), units(1_000));
+}
+
+#[test]
+fn transfer_reverts_without_balance_or_to_the_zero_address() {
+    let (deployer, alice) = (address(DEPLOYER), address(ALICE));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+
+    assert_eq!(evm.call(alice, "transfer(address,uint256)", &[deployer, small(1)]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "transfer(address,uint256)", &[alice, units(1_001)]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "transfer(address,uint256)", &[[0u8; 32], small(1)]), Outcome::Revert);
+    assert!(evm.take_logs().is_empty());
+    assert_eq!(evm.balance(deployer), units(1_000));
+}
+
+#[test]
+fn approve_and_transfer_from_spend_the_allowance() {
+    let (deployer, alice, bob, carol) = (address(DEPLOYER), address(ALICE), address(BOB), address(CAROL));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+    evm.call(deployer, "transfer(address,uint256)", &[alice, units(100)]);
+    evm.take_logs();
+
+    assert_eq!(evm.call(alice, "approve(address,uint256)", &[bob, units(40)]), success());
+    assert_eq!(evm.take_logs(), vec![approval_log(alice, bob, units(40))]);
+    assert_eq!(evm.word("allowance(address,address)", &[alice, bob]), units(40));
+
+    assert_eq!(evm.call(bob, "transferFrom(address,address,uint256)", &[alice, carol, units(30)]), success());
+    assert_eq!(evm.take_logs(), vec![transfer_log(alice, carol, units(30))]);
+    assert_eq!(evm.word("allowance(address,address)", &[alice, bob]), units(10));
+    assert_eq!(evm.balance(alice), units(70));
+    assert_eq!(evm.balance(carol), units(30));
+    assert_eq!(evm.balance(bob), small(0));
+
+    // More than is left of the allowance, and spending without one.
+    assert_eq!(evm.call(bob, "transferFrom(address,address,uint256)", &[alice, carol, units(11)]), Outcome::Revert);
+    assert_eq!(evm.call(carol, "transferFrom(address,address,uint256)", &[alice, carol, small(1)]), Outcome::Revert);
+    assert!(evm.take_logs().is_empty());
+    assert_eq!(evm.balance(alice), units(70));
+}
+
+#[test]
+fn transfer_from_leaves_an_unlimited_allowance_unlimited() {
+    let (deployer, bob, carol) = (address(DEPLOYER), address(BOB), address(CAROL));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+    let unlimited = [0xffu8; 32];
+
+    assert_eq!(evm.call(deployer, "approve(address,uint256)", &[bob, unlimited]), success());
+    assert_eq!(evm.call(bob, "transferFrom(address,address,uint256)", &[deployer, carol, units(5)]), success());
+    assert_eq!(
+        evm.take_logs(),
+        vec![approval_log(deployer, bob, unlimited), transfer_log(deployer, carol, units(5))]
+    );
+    assert_eq!(evm.word("allowance(address,address)", &[deployer, bob]), unlimited);
+    assert_eq!(evm.balance(carol), units(5));
+}
+
+#[test]
+fn transfer_from_cannot_exceed_the_owner_balance() {
+    let (deployer, alice, bob) = (address(DEPLOYER), address(ALICE), address(BOB));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+
+    assert_eq!(evm.call(alice, "approve(address,uint256)", &[bob, units(10)]), success());
+    evm.take_logs();
+    assert_eq!(evm.call(bob, "transferFrom(address,address,uint256)", &[alice, bob, units(1)]), Outcome::Revert);
+    // The reverted call must not have spent the allowance either.
+    assert_eq!(evm.word("allowance(address,address)", &[alice, bob]), units(10));
+}
+
+#[test]
+fn committed_initcode_matches_the_source() {
+    let source = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("contracts/erc20/erc20.etk");
+    assert_eq!(hex::encode(assemble(&source)), INITCODE.trim());
+}
+
+#[test]
+fn owner_mints_up_to_the_cap() {
+    let (deployer, alice) = (address(DEPLOYER), address(ALICE));
+    let (mut evm, _) = Evm::deploy(&Erc20Params { cap: Some(1_500), ..params() }, deployer);
+    assert_eq!(evm.word("cap()", &[]), units(1_500));
+    assert_eq!(evm.word("mintable()", &[]), small(1));
+
+    assert_eq!(evm.call(deployer, "mint(address,uint256)", &[alice, units(400)]), Outcome::Return(Vec::new()));
+    assert_eq!(evm.take_logs(), vec![transfer_log([0u8; 32], alice, units(400))]);
+    assert_eq!(evm.balance(alice), units(400));
+    assert_eq!(evm.word("totalSupply()", &[]), units(1_400));
+
+    // One unit past the cap, then exactly up to it.
+    assert_eq!(evm.call(deployer, "mint(address,uint256)", &[alice, small(100_000_001)]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "mint(address,uint256)", &[alice, units(100)]), Outcome::Return(Vec::new()));
+    assert_eq!(evm.word("totalSupply()", &[]), units(1_500));
+    assert_eq!(evm.call(deployer, "mint(address,uint256)", &[alice, small(1)]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "mint(address,uint256)", &[[0u8; 32], small(1)]), Outcome::Revert);
+}
+
+#[test]
+fn only_the_owner_mints() {
+    let (deployer, alice) = (address(DEPLOYER), address(ALICE));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+
+    assert_eq!(evm.call(alice, "mint(address,uint256)", &[alice, units(1)]), Outcome::Revert);
+    assert!(evm.take_logs().is_empty());
+    assert_eq!(evm.balance(alice), small(0));
+    assert_eq!(evm.word("totalSupply()", &[]), units(1_000));
+}
+
+#[test]
+fn holders_burn_their_own_balance() {
+    let (deployer, alice) = (address(DEPLOYER), address(ALICE));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+    evm.call(deployer, "transfer(address,uint256)", &[alice, units(100)]);
+    evm.take_logs();
+    assert_eq!(evm.word("burnable()", &[]), small(1));
+
+    assert_eq!(evm.call(alice, "burn(uint256)", &[units(30)]), Outcome::Return(Vec::new()));
+    assert_eq!(evm.take_logs(), vec![transfer_log(alice, [0u8; 32], units(30))]);
+    assert_eq!(evm.balance(alice), units(70));
+    assert_eq!(evm.word("totalSupply()", &[]), units(970));
+
+    assert_eq!(evm.call(alice, "burn(uint256)", &[units(71)]), Outcome::Revert);
+    assert_eq!(evm.balance(alice), units(70));
+}
+
+#[test]
+fn disabled_features_revert() {
+    let deployer = address(DEPLOYER);
+    let (mut evm, _) = Evm::deploy(&Erc20Params { mintable: false, burnable: false, ..params() }, deployer);
+    assert_eq!(evm.word("mintable()", &[]), small(0));
+    assert_eq!(evm.word("burnable()", &[]), small(0));
+
+    assert_eq!(evm.call(deployer, "mint(address,uint256)", &[deployer, units(1)]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "burn(uint256)", &[units(1)]), Outcome::Revert);
+    assert!(evm.take_logs().is_empty());
+    assert_eq!(evm.balance(deployer), units(1_000));
+    assert_eq!(evm.word("totalSupply()", &[]), units(1_000));
+}
+
+#[test]
+fn ownership_can_be_transferred_and_renounced() {
+    let (deployer, alice) = (address(DEPLOYER), address(ALICE));
+    let (mut evm, _) = Evm::deploy(&params(), deployer);
+    let ownership_log = |previous: Word, new: Word| Log {
+        topics: vec![keccak(b"OwnershipTransferred(address,address)"), previous, new],
+        data: Vec::new(),
+    };
+
+    assert_eq!(evm.call(alice, "transferOwnership(address)", &[alice]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "transferOwnership(address)", &[[0u8; 32]]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "transferOwnership(address)", &[alice]), Outcome::Return(Vec::new()));
+    assert_eq!(evm.take_logs(), vec![ownership_log(deployer, alice)]);
+    assert_eq!(evm.word("owner()", &[]), alice);
+
+    // The previous owner lost its rights with the transfer.
+    assert_eq!(evm.call(deployer, "mint(address,uint256)", &[deployer, units(1)]), Outcome::Revert);
+    assert_eq!(evm.call(deployer, "renounceOwnership()", &[]), Outcome::Revert);
+    assert_eq!(evm.call(alice, "mint(address,uint256)", &[alice, units(1)]), Outcome::Return(Vec::new()));
+    evm.take_logs();
+
+    assert_eq!(evm.call(alice, "renounceOwnership()", &[]), Outcome::Return(Vec::new()));
+    assert_eq!(evm.take_logs(), vec![ownership_log(alice, [0u8; 32])]);
+    assert_eq!(evm.word("owner()", &[]), [0u8; 32]);
+    assert_eq!(evm.call(alice, "mint(address,uint256)", &[alice, units(1)]), Outcome::Revert);
+}